@@ -1,5 +1,5 @@
 import test from "ava";
-import { Decoder, HypersyncClient } from "../index.js";
+import { CallDecoder, Decoder, HypersyncClient } from "../index.js";
 
 test("Decodes event from etherscan", async (t) => {
   const decoder = Decoder.fromSignatures([
@@ -70,3 +70,204 @@ test.skip("Fetches event from base", async (t) => {
   // const decoded = await decoder.decodeLogs([log]);
   // t.is(decoded[0].indexed[1].val, -1n);
 });
+
+test("Decodes event with decoder built from ABI", async (t) => {
+  const abi = [
+    {
+      type: "event",
+      name: "Transfer",
+      anonymous: false,
+      inputs: [
+        { name: "from", type: "address", indexed: true },
+        { name: "to", type: "address", indexed: true },
+        { name: "value", type: "uint256", indexed: false },
+      ],
+    },
+  ];
+  const log = {
+    topics: [
+      "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+      "0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+      "0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+    ],
+    data: "0x0000000000000000000000000000000000000000000000000000000000000064",
+  };
+
+  for (const decoder of [
+    Decoder.fromAbi(abi),
+    Decoder.fromAbi(JSON.stringify({ contractName: "Token", abi })),
+    Decoder.fromAbis({ "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa": abi }),
+  ]) {
+    const decoded = await decoder.decodeLogs([log]);
+    t.is(decoded[0].body[0].val, 100n);
+  }
+});
+
+test("Decodes event params by name", async (t) => {
+  const decoder = Decoder.fromSignatures([
+    "Transfer(address indexed from, address indexed to, uint256 value)",
+  ]);
+  decoder.enableNamedParams();
+
+  const log = {
+    topics: [
+      "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+      "0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+      "0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+    ],
+    data: "0x0000000000000000000000000000000000000000000000000000000000000064",
+  };
+  const [decoded] = await decoder.decodeLogs([log]);
+
+  t.is(decoded.name, "Transfer");
+  t.is(decoded.signature, "Transfer(address,address,uint256)");
+  t.is(decoded.topic0, log.topics[0]);
+  t.is(decoded.params.to, "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+  t.is(decoded.params.value, 100n);
+  t.is(decoded.body[0].val, 100n);
+});
+
+test("Reports why logs couldn't be decoded", (t) => {
+  const decoder = Decoder.fromSignatures([
+    "Transfer(address indexed from, address indexed to, uint256 value)",
+  ]);
+  const topics = [
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+    "0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+    "0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+  ];
+
+  const results = decoder.decodeLogsWithDiagnosticsSync([
+    { topics, data: "0x0000000000000000000000000000000000000000000000000000000000000064" },
+    { topics },
+    { topics: [topics[1]], data: "0x" },
+    { topics, data: "0x1234" },
+  ]);
+
+  t.is(results[0].event.body[0].val, 100n);
+  t.is(results[1].failure.reason, "MissingData");
+  t.is(results[2].failure.reason, "UnknownTopic0");
+  t.is(results[3].failure.reason, "AbiMismatch");
+});
+
+test("Call decoder doesn't throw on malformed inputs", async (t) => {
+  const decoder = CallDecoder.fromSignatures([
+    "transfer(address to, uint256 amount)",
+  ]);
+  const input =
+    "0xa9059cbb000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0000000000000000000000000000000000000000000000000000000000000064";
+
+  const decoded = await decoder.decodeInputs([
+    input,
+    "0xnot hex",
+    "0xa905",
+    input.slice(0, 50),
+    "0xdeadbeef" + input.slice(10),
+  ]);
+
+  t.is(decoded[0][1].val, 100n);
+  t.deepEqual(decoded.slice(1), [null, null, null, null]);
+  t.is(decoder.decodeImpl("0xdeadbeef"), null);
+  t.is(t.throws(() => decoder.decodeImpl("0xa905")).code, "DecodeError");
+});
+
+test("Call decoder decodes trace outputs and reverts", (t) => {
+  const decoder = CallDecoder.fromSignatures([
+    "transfer(address to, uint256 amount) returns (bool)",
+    "error InsufficientBalance(uint256 available, uint256 required)",
+  ]);
+  const input =
+    "0xa9059cbb000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0000000000000000000000000000000000000000000000000000000000000064";
+  const word = (n) => n.toString(16).padStart(64, "0");
+  const reason = Buffer.from("not owner").toString("hex").padEnd(64, "0");
+  const errorString = "0x08c379a0" + word(32) + word(9) + reason;
+  const panic = "0x4e487b71" + word(0x11);
+  const custom = "0xcf479181" + word(1) + word(2);
+
+  const outputs = decoder.decodeTracesOutputSync([
+    { input, output: "0x" + word(1) },
+    { input, output: errorString, error: "Reverted" },
+  ]);
+  t.is(outputs[0][0].val, true);
+  t.is(outputs[1], null);
+
+  const reverts = decoder.decodeRevertsSync([errorString, panic, custom, "0x", "0xdeadbeef"]);
+  t.is(reverts[0].message, "not owner");
+  t.is(reverts[1].message, "arithmetic overflow or underflow");
+  t.is(reverts[2].name, "InsufficientBalance");
+  t.is(reverts[2].params[1].val, 2n);
+  t.deepEqual(reverts.slice(3), [null, null]);
+
+  const traces = decoder.decodeTracesRevertSync([
+    { input, output: panic, error: "Reverted" },
+    { input, output: "0x" + word(1) },
+  ]);
+  t.is(traces[0].name, "Panic");
+  t.is(traces[1], null);
+});
+
+test("Decoder picks events by log address", (t) => {
+  const token = "0x0101010101010101010101010101010101010101";
+  const decoder = Decoder.fromContracts([
+    {
+      addresses: [token],
+      signatures: [
+        "Transfer(address indexed from, address to, uint256 indexed value)",
+      ],
+    },
+    {
+      signatures: [
+        "Transfer(address indexed from, address indexed to, uint256 value)",
+      ],
+    },
+  ]);
+  const log = (address) => ({
+    address,
+    topics: [
+      "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+      "0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+      "0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+    ],
+    data: "0x" + "7".padStart(64, "0"),
+  });
+
+  const [ownEvent, globalEvent] = decoder.decodeLogsSync([
+    log(token),
+    log("0x0202020202020202020202020202020202020202"),
+  ]);
+  t.is(ownEvent.body[0].val, "0x0000000000000000000000000000000000000007");
+  t.is(globalEvent.body[0].val, 7n);
+});
+
+test("Decodes logs and inputs given as Buffers", (t) => {
+  const decoder = Decoder.fromSignatures([
+    "event Mint(address sender, address indexed owner, int24 indexed tickLower, int24 indexed tickUpper, uint128 amount, uint256 amount0, uint256 amount1)",
+  ]);
+  decoder.enableBinaryValues();
+
+  const hex = (s) => Buffer.from(s.slice(2), "hex");
+  const owner = "0x000000000000000000000000827922686190790b37229fd06084350e74485b72";
+  const log = {
+    topics: [
+      hex("0x7a53080ba414158be7ec69b987b5fb7d07dee101fe85488f0853ae16239d0bde"),
+      hex(owner),
+      hex("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+      hex("0x0000000000000000000000000000000000000000000000000000000000000001"),
+    ],
+    data: hex(
+      "0x000000000000000000000000827922686190790b37229fd06084350e74485b72000000000000000000000000000000000000000000000000000000000bebae76000000000000000000000000000000000000000000000000000000000000270f000000000000000000000000000000000000000000000000000000000000270f",
+    ),
+  };
+  const [decoded] = decoder.decodeLogsSync([log]);
+  t.true(Buffer.isBuffer(decoded.indexed[0].val));
+  t.deepEqual(decoded.indexed[0].val, hex(owner).subarray(12));
+  t.is(decoded.indexed[1].val, -1n);
+
+  const calls = CallDecoder.fromSignatures(["transfer(address to, uint256 amount)"]);
+  const input = hex(
+    "0xa9059cbb000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0000000000000000000000000000000000000000000000000000000000000064",
+  );
+  const [params] = calls.decodeInputsSync([input]);
+  t.is(params[0].val, "0x" + "bb".repeat(20));
+  t.is(params[1].val, 100n);
+});
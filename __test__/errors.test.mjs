@@ -0,0 +1,121 @@
+import test from "ava";
+import {
+  CancellationHandle,
+  Decoder,
+  HypersyncClient,
+  presetQueryLogs,
+} from "../index.js";
+
+test("Invalid arguments throw InvalidQuery", (t) => {
+  const err = t.throws(() => presetQueryLogs("not an address", 0));
+  t.is(err.code, "InvalidQuery");
+});
+
+test("Invalid signatures throw DecodeError", (t) => {
+  const err = t.throws(() => Decoder.fromSignatures(["Transfer("]));
+  t.is(err.code, "DecodeError");
+});
+
+test("Rejected calls carry the query", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const query = {
+    fromBlock: 0,
+    logs: [{ address: ["not an address"] }],
+    fieldSelection: {},
+  };
+  const err = await t.throwsAsync(client.get(query));
+  t.is(err.code, "InvalidQuery");
+  t.is(err.query.fromBlock, 0);
+});
+
+test("Aborted calls reject with Aborted", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const handle = new CancellationHandle();
+  handle.cancel();
+  const err = await t.throwsAsync(
+    client.get({ fromBlock: 0, fieldSelection: {} }, handle),
+  );
+  t.is(err.code, "Aborted");
+});
+
+test("Aborted collects and streams reject with Aborted", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const handle = new CancellationHandle();
+  handle.cancel();
+  const query = { fromBlock: 0, fieldSelection: {} };
+  const collectErr = await t.throwsAsync(client.collect(query, {}, handle));
+  t.is(collectErr.code, "Aborted");
+  const streamErr = await t.throwsAsync(
+    client.streamEvents(query, {}, handle),
+  );
+  t.is(streamErr.code, "Aborted");
+});
+
+test("Invalid timestamp ranges are rejected", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const err = await t.throwsAsync(client.getBlockRangeByTimestamp(10, 5));
+  t.is(err.code, "InvalidQuery");
+});
+
+test("Invalid hashes in lookups are rejected", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const err = await t.throwsAsync(client.getTransaction("0x12"));
+  t.is(err.code, "InvalidQuery");
+  t.truthy(err.query);
+});
+
+test("Aborted lookups reject with Aborted", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const handle = new CancellationHandle();
+  handle.cancel();
+  const hash = `0x${"ab".repeat(32)}`;
+  const err = await t.throwsAsync(client.getTransaction(hash, null, handle));
+  t.is(err.code, "Aborted");
+});
+
+test("Arrow streams reject followHead", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const err = await t.throwsAsync(
+    client.streamArrow(
+      { fromBlock: 0, fieldSelection: {} },
+      { followHead: true },
+    ),
+  );
+  t.is(err.code, "InvalidQuery");
+});
+
+test("SQLite streams reject reverse streams", async (t) => {
+  const client = new HypersyncClient({
+    url: "https://eth.hypersync.xyz",
+    apiToken: "",
+  });
+  const err = await t.throwsAsync(
+    client.streamSqlite(
+      "unused.db",
+      { fromBlock: 0, fieldSelection: {} },
+      { reverse: true },
+    ),
+  );
+  t.is(err.code, "InvalidQuery");
+});
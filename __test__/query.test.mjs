@@ -0,0 +1,131 @@
+import test from "ava";
+import {
+  Decoder,
+  JoinMode,
+  presetQueryEvents,
+  QueryBuilder,
+  queryFromCapnp,
+  queryFromJson,
+  queryToCapnp,
+  queryToJson,
+  validateQuery,
+} from "../index.js";
+
+const USDT = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+
+test("QueryBuilder builds a query", (t) => {
+  const builder = new QueryBuilder();
+  const query = builder
+    .fromBlock(100)
+    .toBlock(200)
+    .addLogFilter({
+      address: [USDT],
+      topic2: [
+        "0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+      ],
+    })
+    .addTransactionFilter({ from: [USDT] })
+    .selectFields({ block: ["Number"] })
+    .selectFields({ block: ["Hash", "Number"] })
+    .joinMode(JoinMode.JoinAll)
+    .build();
+
+  t.is(query.fromBlock, 100);
+  t.is(query.toBlock, 200);
+  t.deepEqual(query.logs[0].topics[0], []);
+  t.is(query.logs[0].topics.length, 3);
+  t.deepEqual(query.fieldSelection.block, ["Number", "Hash"]);
+  t.true(query.fieldSelection.log.includes("Topic3"));
+  t.is(query.joinMode, JoinMode.JoinAll);
+});
+
+test("QueryBuilder validates eagerly", (t) => {
+  const builder = new QueryBuilder().fromBlock(100);
+
+  for (const build of [
+    () => builder.toBlock(50),
+    () => builder.fromBlock(-1),
+    () => builder.addLogFilter({ topic0: ["0x12"] }),
+    () => builder.addTransactionFilter({ from: ["not an address"] }),
+  ]) {
+    t.is(t.throws(build).code, "InvalidQuery");
+  }
+});
+
+test("Queries are generated from event signatures", (t) => {
+  const to = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+  const query = presetQueryEvents(
+    {
+      signatures: [
+        "Transfer(address indexed from, address indexed to, uint256 value)",
+      ],
+      addresses: [USDT],
+      indexedArgs: { to: [to] },
+    },
+    100,
+  );
+
+  t.is(query.fromBlock, 100);
+  t.deepEqual(query.logs[0].topics, [
+    ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+    [],
+    ["0x000000000000000000000000" + to.slice(2)],
+  ]);
+  t.deepEqual(query.fieldSelection.log, [
+    "Data",
+    "Address",
+    "Topic0",
+    "Topic1",
+    "Topic2",
+  ]);
+
+  const err = t.throws(() =>
+    presetQueryEvents({ signatures: ["Transfer(address)"], indexedArgs: { to: [to] } }, 0),
+  );
+  t.is(err.code, "InvalidQuery");
+});
+
+test("validateQuery reports errors and warnings", (t) => {
+  const decoder = Decoder.fromSignatures([
+    "Transfer(address indexed from, address indexed to, uint256 value)",
+  ]);
+  const res = validateQuery(
+    {
+      fromBlock: 10,
+      toBlock: 5,
+      logs: [{ topics: [[], [], [], [], []] }],
+      transactions: [{ from: [USDT] }],
+      fieldSelection: { log: ["Data"] },
+    },
+    { decoder },
+  );
+
+  t.deepEqual(
+    res.errors.map((e) => [e.kind, e.path]),
+    [
+      ["InvalidRange", "toBlock"],
+      ["TooManyTopics", "logs[0].topics"],
+      ["MissingFields", "fieldSelection.log"],
+    ],
+  );
+  t.deepEqual(
+    res.warnings.map((w) => w.path),
+    ["fieldSelection.transaction"],
+  );
+});
+
+test("Queries round trip through JSON and Cap'n Proto", (t) => {
+  const query = new QueryBuilder()
+    .fromBlock(100)
+    .toBlock(200)
+    .addLogFilter({ address: [USDT] })
+    .joinMode(JoinMode.JoinAll)
+    .build();
+
+  const json = queryToJson(query);
+  t.is(JSON.parse(json).from_block, 100);
+  t.is(queryToJson(queryFromJson(json)), json);
+  t.is(queryToJson(queryFromCapnp(queryToCapnp(query))), json);
+
+  t.is(t.throws(() => queryFromJson("{")).code, "InvalidQuery");
+});
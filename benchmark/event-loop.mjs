@@ -0,0 +1,135 @@
+// Measures how long the event loop is blocked while ~100k logs are fetched and converted.
+//
+// Usage: ENVIO_API_TOKEN=... node benchmark/event-loop.mjs
+//
+// Env options:
+//   HYPERSYNC_URL  endpoint to query, default https://eth.hypersync.xyz
+//   NUM_LOGS       logs per run, default 100000
+//   RUNS           runs per scenario, default 3
+//   SCENARIOS      comma separated subset of the scenarios below
+
+import { monitorEventLoopDelay, performance } from "node:perf_hooks";
+import { setImmediate, setTimeout } from "node:timers/promises";
+
+import { HypersyncClient, presetQueryLogsOfEvent } from "../index.js";
+
+const url = process.env.HYPERSYNC_URL ?? "https://eth.hypersync.xyz";
+const numLogs = Number(process.env.NUM_LOGS ?? 100_000);
+const runs = Number(process.env.RUNS ?? 3);
+
+if (!process.env.ENVIO_API_TOKEN) {
+  console.error("ENVIO_API_TOKEN is required, see README.md");
+  process.exit(1);
+}
+
+// USDT Transfer logs, dense enough to reach 100k logs within a few thousand blocks
+const usdtContract = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+const transferTopic = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+const query = presetQueryLogsOfEvent(usdtContract, transferTopic, 17_000_000);
+const config = { maxNumLogs: numLogs };
+
+const clients = {
+  default: new HypersyncClient({ url, apiToken: process.env.ENVIO_API_TOKEN }),
+  checksum: new HypersyncClient({
+    url,
+    apiToken: process.env.ENVIO_API_TOKEN,
+    enableChecksumAddresses: true,
+  }),
+  binary: new HypersyncClient({ url, apiToken: process.env.ENVIO_API_TOKEN, binaryFields: true }),
+};
+
+/** Scenarios return the number of logs they received */
+const scenarios = {
+  async collect() {
+    const res = await clients.default.collect(query, config);
+    return res.data.logs.length;
+  },
+  async "collect checksummed"() {
+    const res = await clients.checksum.collect(query, config);
+    return res.data.logs.length;
+  },
+  async "collect binary"() {
+    const res = await clients.binary.collect(query, config);
+    return res.data.logs.length;
+  },
+  async stream() {
+    const stream = await clients.default.stream(query, config);
+    let count = 0;
+    let res;
+    while ((res = await stream.recv()) !== null) {
+      count += res.data.logs.length;
+    }
+    return count;
+  },
+  async "stream for await"() {
+    const stream = await clients.default.stream(query, config);
+    let count = 0;
+    for await (const res of stream) {
+      count += res.data.logs.length;
+    }
+    return count;
+  },
+  async "collectLazy + read rows"() {
+    const res = await clients.default.collectLazy(query, config);
+    const logs = res.data.logs;
+    for (let i = 0; i < logs.length; i++) {
+      logs.get(i);
+      // yield now and then, rows are converted on the main thread when they are read
+      if (i % 1000 === 999) {
+        await setImmediate();
+      }
+    }
+    return logs.length;
+  },
+};
+
+const selected = process.env.SCENARIOS?.split(",").map((s) => s.trim()) ?? Object.keys(scenarios);
+
+async function measure(run) {
+  const histogram = monitorEventLoopDelay({ resolution: 1 });
+  histogram.enable();
+  const start = performance.now();
+  const count = await run();
+  const elapsed = performance.now() - start;
+  // the delay of a stall is recorded once the timer of the histogram fires after it, which
+  // for a stall that resolved `run` is only after this continuation
+  await setTimeout(10);
+  histogram.disable();
+
+  const ms = (ns) => ns / 1e6;
+  return {
+    count,
+    elapsed,
+    p50: ms(histogram.percentile(50)),
+    p99: ms(histogram.percentile(99)),
+    max: ms(histogram.max),
+  };
+}
+
+const rows = [];
+for (const name of selected) {
+  const run = scenarios[name];
+  if (!run) {
+    console.error(`unknown scenario "${name}", available: ${Object.keys(scenarios).join(", ")}`);
+    process.exit(1);
+  }
+
+  // warm up the connection and the server side cache
+  await run();
+
+  const results = [];
+  for (let i = 0; i < runs; i++) {
+    results.push(await measure(run));
+  }
+  const median = (key) => results.map((r) => r[key]).sort((a, b) => a - b)[Math.floor(runs / 2)];
+  rows.push({
+    scenario: name,
+    logs: results[0].count,
+    "total ms": median("elapsed").toFixed(0),
+    "loop delay p50 ms": median("p50").toFixed(2),
+    "loop delay p99 ms": median("p99").toFixed(2),
+    "longest stall ms": median("max").toFixed(2),
+  });
+}
+
+console.table(rows);
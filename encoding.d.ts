@@ -0,0 +1,307 @@
+/**
+ * Response typings that follow the `binaryFields` and `numericMode` options.
+ *
+ * The types in `index.d.ts` describe responses of clients and streams with the default
+ * options. Pass the options to the generic types here to get the types of responses with
+ * other options, e.g.
+ *
+ * ```ts
+ * import type { QueryResponse } from '@envio-dev/hypersync-client/encoding'
+ *
+ * const client = new HypersyncClient({ ..., binaryFields: true, numericMode: 'decimalString' })
+ * const res = (await client.get(query)) as QueryResponse<{ binaryFields: true; numericMode: 'decimalString' }>
+ * ```
+ *
+ * Without type arguments the types are the same as in `index.d.ts`.
+ */
+
+import type {
+  DecodeFailure,
+  NumericMode,
+  RateLimitInfo,
+  ReorgStreamRollbackEvent,
+  RollbackGuard,
+} from './index'
+
+/**
+ * Options that change the representation of responses. For decoded values `binaryFields`
+ * stands for `enableBinaryValues` of the decoder.
+ */
+export interface EncodingOptions {
+  binaryFields?: boolean
+  numericMode?: NumericMode
+}
+
+/** Hash, address or other binary field */
+export type Binary<E extends EncodingOptions> = E extends { binaryFields: true }
+  ? Buffer
+  : E extends { binaryFields?: false }
+    ? string
+    : string | Buffer
+
+/** Quantity that is a `D` if no `numericMode` is set */
+export type Quantity<E extends EncodingOptions, D> = E extends { numericMode: 'bigint' }
+  ? bigint
+  : E extends { numericMode: 'decimalString' | 'hex' }
+    ? string
+    : E extends { numericMode: 'number-when-safe' }
+      ? number | string
+      : E extends { numericMode?: undefined }
+        ? D
+        : number | bigint | string
+
+/**
+ * Fractional quantity, a number in the default, `bigint` and `number-when-safe` modes. Hex
+ * can't represent fractions, so `hex` gives a decimal string like `decimalString`.
+ */
+export type Fraction<E extends EncodingOptions> = E extends { numericMode: 'decimalString' | 'hex' }
+  ? string
+  : E extends { numericMode?: 'bigint' | 'number-when-safe' | undefined }
+    ? number
+    : number | string
+
+export interface Log<E extends EncodingOptions = {}> {
+  removed?: boolean
+  logIndex?: Quantity<E, number>
+  transactionIndex?: Quantity<E, number>
+  transactionHash?: Binary<E>
+  blockHash?: Binary<E>
+  blockNumber?: Quantity<E, number>
+  address?: Binary<E>
+  data?: Binary<E>
+  topics: Array<Binary<E> | undefined | null>
+}
+
+export interface Block<E extends EncodingOptions = {}> {
+  number?: Quantity<E, number>
+  hash?: Binary<E>
+  parentHash?: Binary<E>
+  nonce?: Quantity<E, bigint>
+  sha3Uncles?: Binary<E>
+  logsBloom?: Binary<E>
+  transactionsRoot?: Binary<E>
+  stateRoot?: Binary<E>
+  receiptsRoot?: Binary<E>
+  miner?: Binary<E>
+  difficulty?: Quantity<E, bigint>
+  totalDifficulty?: Quantity<E, bigint>
+  extraData?: Binary<E>
+  size?: Quantity<E, bigint>
+  gasLimit?: Quantity<E, bigint>
+  gasUsed?: Quantity<E, bigint>
+  timestamp?: Quantity<E, number>
+  uncles?: Array<Binary<E>>
+  baseFeePerGas?: Quantity<E, bigint>
+  blobGasUsed?: Quantity<E, bigint>
+  excessBlobGas?: Quantity<E, bigint>
+  parentBeaconBlockRoot?: Binary<E>
+  withdrawalsRoot?: Binary<E>
+  withdrawals?: Array<Withdrawal<E>>
+  l1BlockNumber?: Quantity<E, number>
+  sendCount?: Quantity<E, string>
+  sendRoot?: Binary<E>
+  mixHash?: Binary<E>
+}
+
+export interface Withdrawal<E extends EncodingOptions = {}> {
+  index?: Quantity<E, string>
+  validatorIndex?: Quantity<E, string>
+  address?: Binary<E>
+  amount?: Quantity<E, string>
+}
+
+export interface Transaction<E extends EncodingOptions = {}> {
+  blockHash?: Binary<E>
+  blockNumber?: Quantity<E, number>
+  from?: Binary<E>
+  gas?: Quantity<E, bigint>
+  gasPrice?: Quantity<E, bigint>
+  hash?: Binary<E>
+  input?: Binary<E>
+  nonce?: Quantity<E, bigint>
+  to?: Binary<E>
+  transactionIndex?: Quantity<E, number>
+  value?: Quantity<E, bigint>
+  v?: string
+  r?: string
+  s?: string
+  yParity?: string
+  maxPriorityFeePerGas?: Quantity<E, bigint>
+  maxFeePerGas?: Quantity<E, bigint>
+  chainId?: Quantity<E, number>
+  accessList?: Array<AccessList<E>>
+  authorizationList?: Array<Authorization<E>>
+  maxFeePerBlobGas?: Quantity<E, bigint>
+  blobVersionedHashes?: Array<Binary<E>>
+  cumulativeGasUsed?: Quantity<E, bigint>
+  effectiveGasPrice?: Quantity<E, bigint>
+  gasUsed?: Quantity<E, bigint>
+  contractAddress?: Binary<E>
+  logsBloom?: Binary<E>
+  type?: Quantity<E, number>
+  root?: Binary<E>
+  status?: Quantity<E, number>
+  l1Fee?: Quantity<E, bigint>
+  l1GasPrice?: Quantity<E, bigint>
+  l1GasUsed?: Quantity<E, bigint>
+  l1FeeScalar?: Fraction<E>
+  gasUsedForL1?: Quantity<E, bigint>
+  blobGasPrice?: Quantity<E, bigint>
+  blobGasUsed?: Quantity<E, bigint>
+  depositNonce?: Quantity<E, bigint>
+  depositReceiptVersion?: Quantity<E, bigint>
+  l1BaseFeeScalar?: Quantity<E, bigint>
+  l1BlobBaseFee?: Quantity<E, bigint>
+  l1BlobBaseFeeScalar?: Quantity<E, bigint>
+  l1BlockNumber?: Quantity<E, number>
+  mint?: Quantity<E, bigint>
+  sighash?: Binary<E>
+  sourceHash?: Binary<E>
+}
+
+export interface AccessList<E extends EncodingOptions = {}> {
+  address?: Binary<E>
+  storageKeys?: Array<Binary<E>>
+}
+
+export interface Authorization<E extends EncodingOptions = {}> {
+  chainId: Quantity<E, bigint>
+  address: Binary<E>
+  nonce: Quantity<E, number>
+  yParity: number
+  r: string
+  s: string
+}
+
+export interface Trace<E extends EncodingOptions = {}> {
+  from?: Binary<E>
+  to?: Binary<E>
+  callType?: string
+  gas?: Quantity<E, bigint>
+  input?: Binary<E>
+  init?: Binary<E>
+  value?: Quantity<E, bigint>
+  author?: Binary<E>
+  rewardType?: string
+  blockHash?: Binary<E>
+  blockNumber?: Quantity<E, number>
+  address?: Binary<E>
+  code?: Binary<E>
+  gasUsed?: Quantity<E, bigint>
+  output?: Binary<E>
+  subtraces?: Quantity<E, number>
+  traceAddress?: Array<number>
+  transactionHash?: Binary<E>
+  transactionPosition?: Quantity<E, number>
+  type?: string
+  error?: string
+  actionAddress?: Binary<E>
+  balance?: Quantity<E, bigint>
+  refundAddress?: Binary<E>
+  sighash?: Binary<E>
+}
+
+export interface Event<E extends EncodingOptions = {}> {
+  transaction?: Transaction<E>
+  block?: Block<E>
+  log: Log<E>
+}
+
+export interface QueryResponseData<E extends EncodingOptions = {}> {
+  blocks: Array<Block<E>>
+  transactions: Array<Transaction<E>>
+  logs: Array<Log<E>>
+  traces: Array<Trace<E>>
+}
+
+export interface QueryResponse<E extends EncodingOptions = {}> {
+  archiveHeight?: number
+  nextBlock: number
+  totalExecutionTime: number
+  data: QueryResponseData<E>
+  rollbackGuard?: RollbackGuard
+}
+
+export interface QueryResponseWithRateLimit<E extends EncodingOptions = {}> {
+  response: QueryResponse<E>
+  rateLimit: RateLimitInfo
+}
+
+export interface EventResponse<E extends EncodingOptions = {}> {
+  archiveHeight?: number
+  nextBlock: number
+  totalExecutionTime: number
+  data: Array<Event<E>>
+  rollbackGuard?: RollbackGuard
+}
+
+export interface ReorgStreamDataEvent<E extends EncodingOptions = {}> {
+  type: 'Data'
+  response: QueryResponse<E>
+}
+
+export type ReorgStreamEvent<E extends EncodingOptions = {}> =
+  | ReorgStreamDataEvent<E>
+  | ReorgStreamRollbackEvent
+
+export interface DecodedSolValue<E extends EncodingOptions = {}> {
+  val: boolean | bigint | string | Binary<E> | Array<DecodedSolValue<E>>
+}
+
+export type NamedSolValue<E extends EncodingOptions = {}> =
+  | boolean
+  | bigint
+  | string
+  | Binary<E>
+  | Array<NamedSolValue<E>>
+  | { [name: string]: NamedSolValue<E> }
+
+export interface DecodedEvent<E extends EncodingOptions = {}> {
+  indexed: Array<DecodedSolValue<E>>
+  body: Array<DecodedSolValue<E>>
+  name?: string
+  signature?: string
+  topic0?: Binary<E>
+  params?: Record<string, NamedSolValue<E>>
+}
+
+export interface DecodeResult<E extends EncodingOptions = {}> {
+  event?: DecodedEvent<E>
+  failure?: DecodeFailure
+}
+
+export interface DecodedRevert<E extends EncodingOptions = {}> {
+  name: string
+  signature: string
+  params: Array<DecodedSolValue<E>>
+  message?: string
+}
+
+// Decoders take logs, transactions and traces with any encoding
+declare module './index' {
+  interface Decoder {
+    decodeLogs(logs: Array<Log<EncodingOptions>>): Promise<Array<DecodedEvent | undefined | null>>
+    decodeLogsSync(logs: Array<Log<EncodingOptions>>): Array<DecodedEvent | undefined | null>
+    decodeEvents(events: Array<Event<EncodingOptions>>): Promise<Array<DecodedEvent | undefined | null>>
+    decodeEventsSync(events: Array<Event<EncodingOptions>>): Array<DecodedEvent | undefined | null>
+    decodeLogsWithDiagnostics(logs: Array<Log<EncodingOptions>>): Promise<Array<DecodeResult>>
+    decodeLogsWithDiagnosticsSync(logs: Array<Log<EncodingOptions>>): Array<DecodeResult>
+    decodeEventsWithDiagnostics(events: Array<Event<EncodingOptions>>): Promise<Array<DecodeResult>>
+    decodeEventsWithDiagnosticsSync(events: Array<Event<EncodingOptions>>): Array<DecodeResult>
+  }
+
+  interface CallDecoder {
+    decodeTransactionsInput(
+      txs: Array<Transaction<EncodingOptions>>,
+    ): Promise<Array<Array<DecodedSolValue> | undefined | null>>
+    decodeTransactionsInputSync(
+      txs: Array<Transaction<EncodingOptions>>,
+    ): Array<Array<DecodedSolValue> | undefined | null>
+    decodeTracesInput(traces: Array<Trace<EncodingOptions>>): Promise<Array<Array<DecodedSolValue> | undefined | null>>
+    decodeTracesInputSync(traces: Array<Trace<EncodingOptions>>): Array<Array<DecodedSolValue> | undefined | null>
+    decodeTracesOutput(traces: Array<Trace<EncodingOptions>>): Promise<Array<Array<DecodedSolValue> | undefined | null>>
+    decodeTracesOutputSync(traces: Array<Trace<EncodingOptions>>): Array<Array<DecodedSolValue> | undefined | null>
+    decodeTracesRevert(traces: Array<Trace<EncodingOptions>>): Promise<Array<DecodedRevert | undefined | null>>
+    decodeTracesRevertSync(traces: Array<Trace<EncodingOptions>>): Array<DecodedRevert | undefined | null>
+  }
+}
@@ -35,8 +35,10 @@ async function main() {
     ]);
 
 
-    while (true) {
-        const res = await client.get(query);
+    // Stream and keep following new blocks once the stream caught up with the chain
+    const stream = await client.stream(query, { followHead: true });
+
+    for await (const res of stream) {
         if (res.data.transactions.length !== 0) {
             // Decode the log on a background thread so we don't block the event loop.
             // Can also use decoder.decodeLogsSync if it is more convenient.
@@ -50,16 +52,6 @@ async function main() {
         } else {
             console.log(`no tx`);
         }
-        let height = res.archiveHeight;
-        while (height < res.nextBlock) {
-            // wait if we are at the head
-            console.log(`waiting for chain to advance. Height is ${height}`);
-            height = await client.getHeight();
-            await new Promise(resolve => setTimeout(resolve, 1000));
-        }
-
-        // Continue query from nextBlock
-        query.fromBlock = res.nextBlock;
     }
 }
 
@@ -14,13 +14,9 @@ async function main() {
 
     console.log("Height stream created. Listening for height updates...");
 
-    // Track the last known height to detect changes
-
     try {
-        while (true) {
-            // Receive the next event from the height stream
-            const event = await heightStream.recv();
-
+        // Breaking out of the loop (or throwing) closes the stream automatically
+        for await (const event of heightStream) {
             // Handle different types of events
             switch (event.type) {
                 case "Height":
@@ -45,10 +41,6 @@ async function main() {
         }
     } catch (error) {
         console.error("Error in height stream:", error);
-    } finally {
-        // Always close the stream to clean up resources
-        await heightStream.close();
-        console.log("Height stream closed");
     }
 }
 
@@ -1,25 +1,137 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/**
+ * Stream for receiving arrow responses
+ *
+ * Can be consumed with `for await (const res of stream)`, breaking out of the
+ * loop closes the stream.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class ArrowResponseStream {
+  [Symbol.asyncIterator](): AsyncGenerator<ArrowResponse, void, undefined>
+  /** Close the response stream */
+  close(): Promise<void>
+  /** Receive the next arrow response from the stream */
+  recv(): Promise<ArrowResponse | null>
+}
+
 /** Decoder for Ethereum function calls */
 export declare class CallDecoder {
-  /** Create call decoder from function signatures */
+  /**
+   * Create call decoder from function signatures.
+   * Signatures of custom errors, like `error InsufficientBalance(uint256 available)`,
+   * are used to decode revert data.
+   */
   static fromSignatures(signatures: Array<string>): CallDecoder
   /** Create call decoder from function signatures with checksum option */
   static fromSignaturesWithChecksum(signatures: Array<string>, checksum: boolean): CallDecoder
+  /**
+   * Create call decoder from the functions and errors of a JSON ABI
+   *
+   * Takes the ABI as a JSON string or parsed JSON, either the plain ABI array or a
+   * Hardhat/Foundry artifact that contains it.
+   */
+  static fromAbi(abi: any): CallDecoder
+  /**
+   * Create call decoder from the functions and errors of several JSON ABIs keyed by contract address,
+   * e.g. a deployments file. Inputs are matched by selector regardless of the address called.
+   */
+  static fromAbis(abis: Record<string, any>): CallDecoder
+  /** Return addresses, bytes and fixed bytes values as Buffers instead of hex strings */
+  enableBinaryValues(): void
+  /** Return addresses, bytes and fixed bytes values as hex strings */
+  disableBinaryValues(): void
   /** Decode function call inputs asynchronously */
-  decodeInputs(inputs: Array<string>): Promise<Array<Array<DecodedSolValue> | undefined | null>>
+  decodeInputs(inputs: Array<string | Buffer>): Promise<Array<Array<DecodedSolValue> | undefined | null>>
   /** Decode transaction inputs asynchronously */
   decodeTransactionsInput(txs: Array<Transaction>): Promise<Array<Array<DecodedSolValue> | undefined | null>>
   /** Decode trace inputs asynchronously */
   decodeTracesInput(traces: Array<Trace>): Promise<Array<Array<DecodedSolValue> | undefined | null>>
-  /** Decode function call inputs synchronously */
-  decodeInputsSync(inputs: Array<string>): Array<Array<DecodedSolValue> | undefined | null>
-  /** Decode transaction inputs synchronously */
+  /**
+   * Decode function call inputs synchronously.
+   * Inputs that can't be decoded are returned as null.
+   */
+  decodeInputsSync(inputs: Array<string | Buffer>): Array<Array<DecodedSolValue> | undefined | null>
+  /**
+   * Decode transaction inputs synchronously.
+   * Inputs that can't be decoded are returned as null.
+   */
   decodeTransactionsInputSync(txs: Array<Transaction>): Array<Array<DecodedSolValue> | undefined | null>
-  /** Decode trace inputs synchronously */
+  /**
+   * Decode trace inputs synchronously.
+   * Inputs that can't be decoded are returned as null.
+   */
   decodeTracesInputSync(traces: Array<Trace>): Array<Array<DecodedSolValue> | undefined | null>
-  /** Decode a single input string */
-  decodeImpl(input: string): Array<DecodedSolValue> | null
+  /** Decode the return data of traces asynchronously, using the function called in the input */
+  decodeTracesOutput(traces: Array<Trace>): Promise<Array<Array<DecodedSolValue> | undefined | null>>
+  /**
+   * Decode the return data of traces synchronously, using the function called in the input.
+   * Reverted traces and outputs that can't be decoded are returned as null.
+   */
+  decodeTracesOutputSync(traces: Array<Trace>): Array<Array<DecodedSolValue> | undefined | null>
+  /** Decode why traces reverted asynchronously */
+  decodeTracesRevert(traces: Array<Trace>): Promise<Array<DecodedRevert | undefined | null>>
+  /**
+   * Decode why traces reverted synchronously, from the revert data in their output.
+   * Traces that didn't revert and revert data that can't be decoded are returned as null.
+   */
+  decodeTracesRevertSync(traces: Array<Trace>): Array<DecodedRevert | undefined | null>
+  /** Decode revert data asynchronously */
+  decodeReverts(data: Array<string | Buffer>): Promise<Array<DecodedRevert | undefined | null>>
+  /**
+   * Decode revert data synchronously.
+   * Data that is empty, has an unknown selector or can't be decoded is returned as null.
+   */
+  decodeRevertsSync(data: Array<string | Buffer>): Array<DecodedRevert | undefined | null>
+  /**
+   * Decode a single input.
+   * Returns null if the selector is unknown and throws if the input can't be decoded.
+   */
+  decodeImpl(input: string | Buffer): Array<DecodedSolValue> | null
+}
+
+/**
+ * Handle for cancelling in-flight client calls
+ *
+ * Pass it as the last argument to `collect`, `collectEvents`, `collectParquet`, `get`,
+ * `stream` or `streamEvents` and call `cancel()` to abort the call. Calls that were aborted reject with an
+ * error that has `code` set to `"Aborted"`.
+ *
+ * To drive it from an `AbortSignal`:
+ * `signal.addEventListener("abort", () => handle.cancel())`
+ */
+export declare class CancellationHandle {
+  /** Create a new cancellation handle */
+  constructor()
+  /**
+   * Cancel every call this handle was passed to.
+   * Calling it more than once has no effect.
+   */
+  cancel(): void
+  /** Whether `cancel()` has been called on this handle */
+  get isCancelled(): boolean
+}
+
+/**
+ * Storage for stream checkpoints, used by `streamWithCheckpoint` and
+ * `streamEventsWithCheckpoint` to resume where the last stream left off.
+ */
+export declare class CheckpointStore {
+  /** Store checkpoints as JSON in the file at the given path */
+  static file(path: string): CheckpointStore
+  /**
+   * Store checkpoints with custom async load and save functions, e.g. in a database.
+   * `load` should resolve to null if no checkpoint was saved yet.
+   */
+  static custom(load: () => Promise<Checkpoint | undefined | null>, save: (arg: Checkpoint) => Promise<void>): CheckpointStore
+  /** Load the last saved checkpoint, null if there is none */
+  load(): Promise<Checkpoint | null>
+  /** Save a checkpoint, replacing the previous one */
+  save(checkpoint: Checkpoint): Promise<void>
 }
 
 /** Decoder for Ethereum events and function calls */
@@ -28,10 +140,41 @@ export declare class Decoder {
   static fromSignatures(signatures: Array<string>): Decoder
   /** Create decoder from event signatures with checksum option */
   static fromSignaturesWithChecksum(signatures: Array<string>, checksum: boolean): Decoder
+  /**
+   * Create decoder from the events of a JSON ABI
+   *
+   * Takes the ABI as a JSON string or parsed JSON, either the plain ABI array or a
+   * Hardhat/Foundry artifact that contains it.
+   */
+  static fromAbi(abi: any): Decoder
+  /**
+   * Create decoder from the events of several JSON ABIs keyed by contract address,
+   * e.g. a deployments file. Logs are decoded with the ABI of their contract first, and
+   * with the events of all ABIs if it doesn't match.
+   * Keys that aren't addresses, like contract names, only add to the events of all ABIs.
+   */
+  static fromAbis(abis: Record<string, any>): Decoder
+  /**
+   * Create decoder that picks the events to decode a log with by its address, falling back
+   * to the entries without addresses. Useful when contracts emit events with the same
+   * topic0 but a different layout.
+   */
+  static fromContracts(contracts: Array<ContractEvents>): Decoder
   /** Enable checksummed addresses in decoded output */
   enableChecksummedAddresses(): void
   /** Disable checksummed addresses in decoded output */
   disableChecksummedAddresses(): void
+  /**
+   * Also return the name, signature and topic0 of decoded events, with their params
+   * keyed by name in `params`
+   */
+  enableNamedParams(): void
+  /** Only return positional `indexed` and `body` params in decoded events */
+  disableNamedParams(): void
+  /** Return addresses, bytes and fixed bytes values as Buffers instead of hex strings */
+  enableBinaryValues(): void
+  /** Return addresses, bytes and fixed bytes values as hex strings */
+  disableBinaryValues(): void
   /** Decode logs asynchronously */
   decodeLogs(logs: Array<Log>): Promise<Array<DecodedEvent | undefined | null>>
   /** Decode logs synchronously */
@@ -40,10 +183,30 @@ export declare class Decoder {
   decodeEvents(events: Array<Event>): Promise<Array<DecodedEvent | undefined | null>>
   /** Decode events synchronously */
   decodeEventsSync(events: Array<Event>): Array<DecodedEvent | undefined | null>
+  /** Decode logs asynchronously, returning why each log that couldn't be decoded failed */
+  decodeLogsWithDiagnostics(logs: Array<Log>): Promise<Array<DecodeResult>>
+  /** Decode logs synchronously, returning why each log that couldn't be decoded failed */
+  decodeLogsWithDiagnosticsSync(logs: Array<Log>): Array<DecodeResult>
+  /** Decode events asynchronously, returning why each event that couldn't be decoded failed */
+  decodeEventsWithDiagnostics(events: Array<Event>): Promise<Array<DecodeResult>>
+  /** Decode events synchronously, returning why each event that couldn't be decoded failed */
+  decodeEventsWithDiagnosticsSync(events: Array<Event>): Array<DecodeResult>
 }
 
-/** Stream for receiving event responses */
+/**
+ * Stream for receiving event responses
+ *
+ * Can be consumed with `for await (const res of stream)`, breaking out of the
+ * loop closes the stream. `recv()` adds the rows of a response in chunks, while
+ * `for await` creates each response in one turn of the event loop.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
 export declare class EventStream {
+  [Symbol.asyncIterator](): AsyncGenerator<EventResponse, void, undefined>
   /** Close the event stream */
   close(): Promise<void>
   /** Receive the next event response from the stream */
@@ -54,8 +217,17 @@ export declare class EventStream {
  * Stream for receiving height stream events
  * yields the immediate height of the chain and then
  * continues to yield height updates as they are received
+ *
+ * Can be consumed with `for await (const event of stream)`, breaking out of the
+ * loop closes the stream.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
  */
 export declare class HeightStream {
+  [Symbol.asyncIterator](): AsyncGenerator<HeightStreamEvent, void, undefined>
   /** Close the height stream */
   close(): Promise<void>
   /** Receive the next height stream event from the stream */
@@ -79,20 +251,169 @@ export declare class HypersyncClient {
   getHeight(): Promise<number>
   /** Get the chain_id of the source hypersync instance */
   getChainId(): Promise<number>
-  /** Collect blockchain data from the given query */
-  collect(query: Query, config: StreamConfig): Promise<QueryResponse>
-  /** Collect blockchain events from the given query */
-  collectEvents(query: Query, config: StreamConfig): Promise<EventResponse>
-  /** Collect blockchain data and save to parquet format */
-  collectParquet(path: string, query: Query, config: StreamConfig): Promise<void>
-  /** Get blockchain data for a single query */
-  get(query: Query): Promise<QueryResponse>
+  /**
+   * Get the number of the block at a unix timestamp in seconds.
+   *
+   * Returns the first block at or after the timestamp, or the last block at or before it
+   * with `direction: "Before"`. Returns null if there is no such block.
+   * Timestamps of probed blocks are cached, so lookups of nearby timestamps are cheaper.
+   */
+  getBlockNumberByTimestamp(timestamp: number, options?: BlockByTimestampOptions | undefined | null): Promise<number | null>
+  /**
+   * Get the block range of a time range `[startTimestamp, endTimestamp)` given as unix
+   * timestamps in seconds, to use as `fromBlock` and `toBlock` of a query.
+   *
+   * Returns null if the time range starts after the last block.
+   */
+  getBlockRangeByTimestamp(startTimestamp: number, endTimestamp: number): Promise<BlockRange | null>
+  /**
+   * Collect blockchain data from the given query
+   *
+   * Rows are added to the result a chunk at a time, so the event loop keeps running while a
+   * large result is created.
+   *
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collect(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<QueryResponse>
+  /**
+   * Collect blockchain events from the given query
+   *
+   * Rows are added to the result a chunk at a time, so the event loop keeps running while a
+   * large result is created.
+   *
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collectEvents(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<EventResponse>
+  /**
+   * Collect blockchain data and save to parquet format
+   *
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collectParquet(path: string, query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<void>
+  /**
+   * Collect blockchain data into CSV files, one per table in the directory at `path`
+   *
+   * Decoded logs are written to `decoded_logs.csv` when `config.eventSignature` is set.
+   * Binary columns are written as hex without prefix unless `config.hexOutput` is set.
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collectCsv(path: string, query: Query, config: StreamConfig, options?: FlatFileOptions | undefined | null, cancel?: CancellationHandle | undefined | null): Promise<void>
+  /**
+   * Collect blockchain data into newline delimited JSON files, one per table in the
+   * directory at `path`
+   *
+   * Decoded logs are written to `decoded_logs.ndjson` when `config.eventSignature` is set.
+   * Binary columns are written as hex without prefix unless `config.hexOutput` is set.
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collectNdjson(path: string, query: Query, config: StreamConfig, options?: FlatFileOptions | undefined | null, cancel?: CancellationHandle | undefined | null): Promise<void>
+  /**
+   * Collect blockchain data into the SQLite database at `path`, with a table per data type
+   *
+   * Tables are created from the selected fields, decoded logs are written to `decoded_logs`
+   * when `config.eventSignature` is set. Each response is inserted in one transaction along
+   * with the block to continue from, so calling this again resumes where the last call
+   * stopped. Rows of blocks that are rolled back are deleted.
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collectSqlite(path: string, query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<void>
+  /**
+   * Stream blockchain data into the SQLite database at `path`, see `collectSqlite`
+   *
+   * The returned stream yields the progress after every committed response or rollback.
+   */
+  streamSqlite(path: string, query: Query, config: StreamConfig): Promise<SqliteStream>
+  /**
+   * Collect blockchain data as Arrow IPC buffers, one per table
+   *
+   * `config.columnMapping` and `config.hexOutput` are applied to the columns.
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collectArrow(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<ArrowResponse>
+  /**
+   * Collect blockchain data without converting it up front, rows are converted to JS
+   * values only when they are accessed.
+   *
+   * Prefer this over `collect` for large results where only some rows or fields are used.
+   * `config.columnMapping` and `config.hexOutput` are ignored.
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  collectLazy(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<LazyQueryResponse>
+  /**
+   * Get blockchain data for a single query
+   *
+   * Pass a `CancellationHandle` to be able to abort the call.
+   */
+  get(query: Query, cancel?: CancellationHandle | undefined | null): Promise<QueryResponse>
   /** Get blockchain events for a single query */
   getEvents(query: Query): Promise<EventResponse>
+  /**
+   * Get a block by number or hash, null if it isn't found.
+   *
+   * Lookups by hash scan the chain, pass `hint` to narrow down the blocks to search in.
+   * Pass a `CancellationHandle` to be able to abort the lookup.
+   */
+  getBlock(numberOrHash: number | string, hint?: BlockHint | undefined | null, cancel?: CancellationHandle | undefined | null): Promise<Block | null>
+  /**
+   * Get a transaction by hash, null if it isn't found.
+   *
+   * Pass `hint` to narrow down the blocks to search in.
+   * Pass a `CancellationHandle` to be able to abort the lookup.
+   */
+  getTransaction(hash: string, hint?: BlockHint | undefined | null, cancel?: CancellationHandle | undefined | null): Promise<Transaction | null>
+  /**
+   * Get the logs emitted by a transaction, empty if the transaction isn't found.
+   *
+   * Pass `hint` to narrow down the blocks to search in.
+   * Pass a `CancellationHandle` to be able to abort the lookup.
+   */
+  getTransactionReceiptLogs(hash: string, hint?: BlockHint | undefined | null, cancel?: CancellationHandle | undefined | null): Promise<Array<Log>>
+  /**
+   * Get the traces of a transaction, empty if the transaction isn't found.
+   *
+   * Pass `hint` to narrow down the blocks to search in.
+   * Pass a `CancellationHandle` to be able to abort the lookup.
+   */
+  getTraces(txHash: string, hint?: BlockHint | undefined | null, cancel?: CancellationHandle | undefined | null): Promise<Array<Trace>>
   /** Stream chain height events */
   streamHeight(): Promise<HeightStream>
-  /** Stream blockchain data from the given query */
-  stream(query: Query, config: StreamConfig): Promise<QueryResponseStream>
+  /**
+   * Stream blockchain data from the given query
+   *
+   * Pass a `CancellationHandle` to be able to abort the stream, cancelling it stops the
+   * background tasks and makes pending and later `recv()` calls reject.
+   */
+  stream(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<QueryResponseStream>
+  /**
+   * Stream blockchain data as Arrow IPC buffers, one per table
+   *
+   * `config.columnMapping` and `config.hexOutput` are applied to the columns,
+   * `config.followHead` is not supported.
+   * Pass a `CancellationHandle` to be able to abort the stream.
+   */
+  streamArrow(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<ArrowResponseStream>
+  /**
+   * Stream blockchain data from the given query, resuming from the checkpoint in `store`
+   *
+   * If the store holds a checkpoint, the stream starts from its `nextBlock` instead of
+   * `query.fromBlock`, and ends right away if that is at or past `query.toBlock`. Before
+   * resuming, the block hash recorded in the checkpoint is compared against the chain and the
+   * call fails with `Rollback` if the chain was rolled back since.
+   *
+   * The checkpoint of a response is saved when the next response is requested, so a
+   * response only counts as processed once the consumer comes back for more.
+   * Pass a `CancellationHandle` to be able to abort the stream.
+   */
+  streamWithCheckpoint(query: Query, config: StreamConfig, store: CheckpointStore, cancel?: CancellationHandle | undefined | null): Promise<QueryResponseStream>
+  /**
+   * Stream blockchain data from the given query, detecting chain reorganizations
+   *
+   * Each response is checked against the block hashes delivered before using its rollback
+   * guard. When a response doesn't chain onto them, a `Rollback` event is emitted and the
+   * stream continues with the new data from the first rolled back block.
+   * Pass a `CancellationHandle` to be able to abort the stream.
+   */
+  streamWithReorgDetection(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<ReorgStream>
   /** Get blockchain data for a single query, with rate limit info */
   getWithRateLimit(query: Query): Promise<QueryResponseWithRateLimit>
   /**
@@ -105,18 +426,144 @@ export declare class HypersyncClient {
    * Returns immediately if no rate limit info observed or quota available.
    */
   waitForRateLimit(): Promise<void>
-  /** Stream blockchain events from the given query */
-  streamEvents(query: Query, config: StreamConfig): Promise<EventStream>
+  /**
+   * Stream blockchain events from the given query
+   *
+   * Pass a `CancellationHandle` to be able to abort the stream.
+   */
+  streamEvents(query: Query, config: StreamConfig, cancel?: CancellationHandle | undefined | null): Promise<EventStream>
+  /**
+   * Stream blockchain events from the given query, resuming from the checkpoint in `store`
+   *
+   * Works the same way as `streamWithCheckpoint`.
+   */
+  streamEventsWithCheckpoint(query: Query, config: StreamConfig, store: CheckpointStore, cancel?: CancellationHandle | undefined | null): Promise<EventStream>
+}
+
+/**
+ * Rows of one table of a lazy response.
+ *
+ * Rows stay in Arrow batches on the Rust side and are only converted to JS values when they
+ * are accessed. Columns that are null in every row are skipped entirely.
+ * Values are represented the same way as in `collect`, so `binaryFields`, `numericMode` and
+ * checksumming apply. Rows have a property per column, except for the topics of logs which are
+ * in a `topics` array like in `Log`, so log rows can be passed to `Decoder.decodeLogs`.
+ */
+export declare class LazyTable {
+  /** Number of rows */
+  get length(): number
+  /** Names of the properties rows can have, columns that are null in every row are skipped */
+  get columns(): Array<string>
+  /**
+   * Row at `index` as an object, null if the index is out of range.
+   *
+   * Only properties of columns that have a value in this row are set.
+   */
+  get(index: number): Record<string, unknown> | null
+  /**
+   * Value of `column` in the row at `index`, e.g. `getValue(0, "blockNumber")`.
+   *
+   * Null if the index is out of range, the value is null or the column is not in `columns`.
+   */
+  getValue(index: number, column: string): unknown
 }
 
-/** Stream for receiving query responses */
+/**
+ * Builder for queries, each method validates its input right away and throws an
+ * `InvalidQuery` error if it is invalid
+ */
+export declare class QueryBuilder {
+  constructor()
+  /** Block to start the query from, inclusive */
+  fromBlock(block: number): this
+  /** Block to end the query at, exclusive */
+  toBlock(block: number): this
+  /**
+   * Select logs matching the filter. Filters added with separate calls are or'ed together.
+   *
+   * If no log fields are selected when the query is built, the fields needed to decode
+   * the logs are selected.
+   */
+  addLogFilter(filter: LogFilterArgs): this
+  /**
+   * Select the logs of events, see `presetQueryEvents`. Also selects the log fields needed
+   * to decode them.
+   */
+  addEventFilter(events: EventQueryOptions): this
+  /** Select transactions matching the filter or selection */
+  addTransactionFilter(filter: TransactionSelection | TransactionFilter): this
+  /** Select traces matching the filter or selection */
+  addTraceFilter(filter: TraceSelection | TraceFilter): this
+  /** Add fields to the field selection, fields selected by earlier calls are kept */
+  selectFields(fields: FieldSelection): this
+  /** Select how the server joins related data, see `Query.joinMode` */
+  joinMode(mode: JoinMode): this
+  /** Build the query, the builder can be used again afterwards */
+  build(): Query
+}
+
+/**
+ * Stream for receiving query responses
+ *
+ * Can be consumed with `for await (const res of stream)`, breaking out of the
+ * loop closes the stream. `recv()` adds the rows of a response in chunks, while
+ * `for await` creates each response in one turn of the event loop.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
 export declare class QueryResponseStream {
+  [Symbol.asyncIterator](): AsyncGenerator<QueryResponse, void, undefined>
   /** Close the response stream */
   close(): Promise<void>
   /** Receive the next query response from the stream */
   recv(): Promise<QueryResponse | null>
 }
 
+/**
+ * Stream of data and rollback events
+ *
+ * A `Rollback` event means the blocks in `fromBlock..=toBlock` that were delivered before are no
+ * longer part of the chain. State derived from them should be reverted, the stream then
+ * continues with the new data starting at `fromBlock`.
+ *
+ * Can be consumed with `for await (const event of stream)`, breaking out of the
+ * loop closes the stream.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class ReorgStream {
+  [Symbol.asyncIterator](): AsyncGenerator<ReorgStreamEvent, void, undefined>
+  /** Close the stream */
+  close(): Promise<void>
+  /** Receive the next event, returns null when the stream is finished */
+  recv(): Promise<ReorgStreamEvent | null>
+}
+
+/**
+ * Stream of progress events of writing to a SQLite database, see `streamSqlite`
+ *
+ * Every event is sent after its changes are committed. Breaking out of the loop stops
+ * writing after the batch in progress.
+ *
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class SqliteStream {
+  [Symbol.asyncIterator](): AsyncGenerator<SqliteProgress, void, undefined>
+  /** Close the stream */
+  close(): Promise<void>
+  /** Receive the next progress event, returns null when the stream is finished */
+  recv(): Promise<SqliteProgress | null>
+}
+
 /**
  * Evm access list object
  *
@@ -127,6 +574,37 @@ export interface AccessList {
   storageKeys?: Array<string>
 }
 
+/** Response from a blockchain query in Arrow IPC format */
+export interface ArrowResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /**
+   * Next block to query for, the responses are paginated so,
+   *  the caller should continue the query from this block if they
+   *  didn't get responses up to the to_block they specified in the Query.
+   */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the query. */
+  totalExecutionTime: number
+  /** Response data */
+  data: ArrowResponseData
+  /** Rollback guard, supposed to be used to detect rollbacks */
+  rollbackGuard?: RollbackGuard
+}
+
+/**
+ * Tables of an arrow response, each encoded as an Arrow IPC stream that can be read with
+ * e.g. `tableFromIPC` of apache-arrow. Tables without data are not set.
+ */
+export interface ArrowResponseData {
+  blocks?: Buffer
+  transactions?: Buffer
+  logs?: Buffer
+  traces?: Buffer
+  /** Logs decoded with `StreamConfig.eventSignature` */
+  decodedLogs?: Buffer
+}
+
 /**
  * Evm authorization object
  *
@@ -135,7 +613,7 @@ export interface AccessList {
 export interface Authorization {
   /** uint256 */
   chainId: bigint
-  /** 20-byte hex */
+  /** 20 bytes */
   address: string
   /** uint64 */
   nonce: number
@@ -221,6 +699,11 @@ export type BlockField =  'Number'|
 'SendRoot'|
 'MixHash';
 
+export interface BlockByTimestampOptions {
+  /** Default: After */
+  direction?: TimestampDirection
+}
+
 /** Filter for selecting blocks based on hash and miner */
 export interface BlockFilter {
   /**
@@ -235,6 +718,27 @@ export interface BlockFilter {
   miner?: Array<string>
 }
 
+/**
+ * Blocks to search in for single-entity lookups. Lookups by hash scan from `fromBlock` to
+ * `toBlock` until they find a match, so narrowing the range makes them a lot faster.
+ * Lookups fail if they don't find a match within 100 requests.
+ */
+export interface BlockHint {
+  /** Block to start searching from, inclusive. Default: 0 */
+  fromBlock?: number
+  /** Block to stop searching at, exclusive. Default: the chain head */
+  toBlock?: number
+}
+
+/**
+ * Block range of a time range, `toBlock` is exclusive and not set if the time range
+ * ends after the last block
+ */
+export interface BlockRange {
+  fromBlock: number
+  toBlock?: number
+}
+
 /** Selection criteria for blocks with include and exclude filters */
 export interface BlockSelection {
   /** Blocks that match this filter will be included */
@@ -243,6 +747,19 @@ export interface BlockSelection {
   exclude?: BlockFilter
 }
 
+/** Position of a stream that can be persisted and resumed from */
+export interface Checkpoint {
+  /** Block to continue the stream from */
+  nextBlock: number
+  /** Number of the last scanned block, taken from the rollback guard of the response. */
+  blockNumber?: number
+  /**
+   * Hash of the last scanned block, taken from the rollback guard of the response.
+   * It is compared against the chain before resuming to detect rollbacks.
+   */
+  hash?: string
+}
+
 /** Configuration for the hypersync client. */
 export interface ClientConfig {
   /** HyperSync server URL. */
@@ -261,6 +778,21 @@ export interface ClientConfig {
   retryCeilingMs?: number
   /** Enable checksum addresses in responses. */
   enableChecksumAddresses?: boolean
+  /**
+   * Return hashes, addresses and other binary fields of blocks, transactions, logs and
+   * traces as Buffers instead of hex strings. The bytes are copied into the Buffers, and
+   * checksumming doesn't apply to them.
+   * See `encoding.d.ts` for the types of such responses.
+   * Default: false.
+   */
+  binaryFields?: boolean
+  /**
+   * Representation of quantities of blocks, transactions, logs and traces, e.g. `decimalString`
+   * to serialize responses to JSON without bigint workarounds.
+   * Default: bigints for amounts and numbers for block numbers, indexes and timestamps.
+   * See `encoding.d.ts` for the types of responses in other modes.
+   */
+  numericMode?: NumericMode
   /** Query serialization format to use for HTTP requests. Default: Json. */
   serializationFormat?: SerializationFormat
   /** Whether to use query caching when using CapnProto serialization format. */
@@ -270,6 +802,12 @@ export interface ClientConfig {
    * sending requests that will be rejected with 429. Default: true.
    */
   proactiveRateLimitSleep?: boolean
+  /**
+   * Validate queries with `validateQuery` before sending them. Warnings are logged and
+   * queries with errors are rejected with `InvalidQuery`. When `eventSignature` is set in the
+   * stream config, the log fields needed to decode it have to be selected. Default: false.
+   */
+  debug?: boolean
 }
 
 /**
@@ -289,12 +827,19 @@ export interface ColumnMapping {
   decodedLog?: Record<string, DataType>
 }
 
+/** Compression of the files written by `collectCsv` and `collectNdjson` */
+export type Compression = 'None'|
+'Gzip'|
+'Zstd';
+
 export type ConnectedTag =  'Connected';
 
 /**
  * DataType is an enumeration representing the different data types that can be used in the column mapping.
  * Each variant corresponds to a specific data type.
  */
+export type DataTag =  'Data';
+
 export type DataType =  'Float64'|
 'Float32'|
 'UInt64'|
@@ -302,16 +847,117 @@ export type DataType =  'Float64'|
 'Int64'|
 'Int32';
 
+/** Events of a set of contracts, used to create an address aware decoder */
+export interface ContractEvents {
+  /**
+   * Addresses of the contracts. Entries without addresses are used for logs of any other
+   * contract, or when the events of their contract don't match the log.
+   */
+  addresses?: Array<string>
+  /** Event signatures */
+  signatures?: Array<string>
+  /**
+   * JSON ABI as a JSON string or parsed JSON, either the plain ABI array or a
+   * Hardhat/Foundry artifact that contains it
+   */
+  abi?: any
+}
+
+export interface DecodeFailure {
+  reason: DecodeFailureReason
+  /** Description of the failure */
+  detail: string
+}
+
+/** Why a log couldn't be decoded */
+export type DecodeFailureReason = /**
+ * No event of the decoder matches topic0.
+ * Usually means the log is from an unrelated event.
+ */
+'UnknownTopic0'|
+/**
+ * Topics or data of the log are missing, check the field selection of the query.
+ * Also reported when the log has fewer topics than the event with its topic0.
+ */
+'MissingData'|
+/** The log matches an event by topic0 but its topics or data don't fit the event params */
+'AbiMismatch'|
+/** Topics or data of the log are not valid hex */
+'MalformedLog';
+
 /** Decoded EVM log */
 export interface DecodedEvent {
   indexed: Array<DecodedSolValue>
   body: Array<DecodedSolValue>
+  /** Name of the event, only set if named params are enabled on the decoder */
+  name?: string
+  /**
+   * Canonical signature of the event, e.g. `Transfer(address,address,uint256)`.
+   * Only set if named params are enabled on the decoder.
+   */
+  signature?: string
+  /**
+   * Topic0 of the event, a Buffer if binary values are enabled on the decoder.
+   * Only set if named params are enabled on the decoder.
+   */
+  topic0?: string
+  /**
+   * Params keyed by name, tuples with named components become nested objects.
+   * Params without a name are keyed by their position.
+   * Only set if named params are enabled on the decoder.
+   */
+  params?: Record<string, NamedSolValue>
+}
+
+/** Result of decoding a single log, exactly one of `event` and `failure` is set */
+export interface DecodeResult {
+  event?: DecodedEvent
+  failure?: DecodeFailure
+}
+
+/** Decoded revert data of a failed call */
+export interface DecodedRevert {
+  /**
+   * Name of the error, `Error` for reverts with a reason string and `Panic` for failed
+   * assertions and other checks inserted by the compiler
+   */
+  name: string
+  /** Canonical signature of the error, e.g. `Error(string)` */
+  signature: string
+  params: Array<DecodedSolValue>
+  /** Reason string of `Error(string)` or description of the `Panic(uint256)` code */
+  message?: string
 }
 
 export interface DecodedSolValue {
   val: boolean | bigint | string | Array<DecodedSolValue>
 }
 
+/**
+ * Category of an error thrown by the client, available as `error.code`
+ *
+ * Errors of client calls also carry `httpStatus` when the server responded, `retryAfter` in
+ * seconds for `RateLimited` errors when known and the `query` that failed.
+ */
+export type ErrorCode = /** The query or one of the arguments is invalid */
+'InvalidQuery'|
+/** The server rejected the request because of rate limits, see `retryAfter` */
+'RateLimited'|
+/** The api token is missing or not valid */
+'Unauthorized'|
+/** A request timed out */
+'Timeout'|
+/** The server failed to process the request */
+'ServerError'|
+/** Data could not be decoded or converted */
+'DecodeError'|
+/** The call was aborted through a `CancellationHandle` */
+'Aborted'|
+/** The chain was rolled back since the checkpoint a stream resumes from was saved */
+'Rollback'|
+/** Any other error */
+'Unknown';
+
 /** Data relating to a single event (log) */
 export interface Event {
   /** Transaction that triggered this event */
@@ -323,6 +969,25 @@ export interface Event {
 }
 
 /** Response from an event query */
+/** Events to query the logs of */
+export interface EventQueryOptions {
+  /** Event signatures, e.g. `Transfer(address indexed from, address indexed to, uint256 value)` */
+  signatures?: Array<string>
+  /**
+   * JSON ABI as a JSON string or parsed JSON, either the plain ABI array or a
+   * Hardhat/Foundry artifact that contains it
+   */
+  abi?: any
+  /** Addresses of the contracts, empty means match all */
+  addresses?: Array<string>
+  /**
+   * Values of indexed params keyed by param name, a log matches if the param equals any of
+   * the values. Values are given as strings, e.g. `"0xab..."` for addresses or `"1000"` for
+   * numbers. Every event has to have an indexed param with each of the names.
+   */
+  indexedArgs?: Record<string, Array<string>>
+}
+
 export interface EventResponse {
   /** Current height of the source hypersync instance */
   archiveHeight?: number
@@ -370,6 +1035,12 @@ export interface FieldSelection {
   trace?: Array<TraceField>
 }
 
+/** Options for `collectCsv` and `collectNdjson` */
+export interface FlatFileOptions {
+  /** Default: None */
+  compression?: Compression
+}
+
 export interface HeightStreamConnectedEvent {
   type: ConnectedTag
 }
@@ -423,6 +1094,35 @@ export declare enum JoinMode {
   JoinNothing = 2
 }
 
+/**
+ * Response from a blockchain query where rows are kept in Arrow batches and only converted
+ * to JS values when they are accessed
+ */
+export interface LazyQueryResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /**
+   * Next block to query for, the responses are paginated so,
+   *  the caller should continue the query from this block if they
+   *  didn't get responses up to the to_block they specified in the Query.
+   */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the query. */
+  totalExecutionTime: number
+  /** Response data */
+  data: LazyResponseData
+  /** Rollback guard, supposed to be used to detect rollbacks */
+  rollbackGuard?: RollbackGuard
+}
+
+/** Tables of a lazy response */
+export interface LazyResponseData {
+  blocks: LazyTable
+  transactions: LazyTable
+  logs: LazyTable
+  traces: LazyTable
+}
+
 /**
  * Evm log object
  *
@@ -468,6 +1168,16 @@ export interface LogFilter {
   topics?: Array<Array<string>>
 }
 
+/** Log filter with a field per topic, missing topics match anything */
+export interface LogFilterArgs {
+  /** Addresses of the contracts, empty means match all */
+  address?: Array<string>
+  topic0?: Array<string>
+  topic1?: Array<string>
+  topic2?: Array<string>
+  topic3?: Array<string>
+}
+
 /** Selection criteria for logs with include and exclude filters */
 export interface LogSelection {
   /** Logs that match this filter will be included */
@@ -476,6 +1186,19 @@ export interface LogSelection {
   exclude?: LogFilter
 }
 
+/** Decoded value where tuples with named components are objects keyed by component name */
+export type NamedSolValue = boolean | bigint | string | Array<NamedSolValue> | { [name: string]: NamedSolValue }
+
+/** Representation of quantities like block numbers, gas and values in responses. */
+export type NumericMode = /** All quantities as bigints, except the fractional `l1FeeScalar` which stays a number */
+'bigint'|
+/** All quantities as decimal strings */
+'decimalString'|
+/** All quantities as prefixed hex strings i.e. 0x1b4, except the fractional `l1FeeScalar` which is a decimal string */
+'hex'|
+/** Quantities up to `Number.MAX_SAFE_INTEGER` as numbers, larger ones as decimal strings */
+'number-when-safe';
+
 /**
  * Returns a query object for all Blocks and hashes of the Transactions within the block range
  * (from_block, to_block].  Also returns the block_hash and block_number fields on each Transaction
@@ -495,6 +1218,13 @@ export declare function presetQueryBlocksAndTransactions(fromBlock: number, toBl
  */
 export declare function presetQueryLogs(contractAddress: string, fromBlock: number, toBlock?: number | undefined | null): Query
 
+/**
+ * Returns a query for the logs of the given events within the block range, with the topic
+ * filters computed from the event signatures and the fields needed to decode the logs selected.
+ * If to_block is None then query runs to the head of the chain.
+ */
+export declare function presetQueryEvents(events: EventQueryOptions, fromBlock: number, toBlock?: number | undefined | null): Query
+
 /**
  * Returns a query for all Logs within the block range from the given address with a
  * matching topic0 event signature.  Topic0 is the keccak256 hash of the event signature.
@@ -502,6 +1232,18 @@ export declare function presetQueryLogs(contractAddress: string, fromBlock: numb
  */
 export declare function presetQueryLogsOfEvent(contractAddress: string, topic0: string, fromBlock: number, toBlock?: number | undefined | null): Query
 
+/** Parse a query from the Cap'n Proto message the client sends to the server */
+export declare function queryFromCapnp(bytes: Buffer): Query
+
+/** Parse a query from the JSON the client sends to the server */
+export declare function queryFromJson(json: string): Query
+
+/** Serialize a query to the Cap'n Proto message the client sends to the server */
+export declare function queryToCapnp(query: Query): Buffer
+
+/** Serialize a query to the JSON the client sends to the server */
+export declare function queryToJson(query: Query, pretty?: boolean | undefined | null): string
+
 /** Query for retrieving blockchain data */
 export interface Query {
   /** The block to start the query from */
@@ -605,6 +1347,25 @@ export interface QueryResponseData {
   traces: Array<Trace>
 }
 
+export interface QueryIssue {
+  kind: QueryIssueKind
+  /** Path of the offending part of the query, e.g. `logs[0].topics` */
+  path: string
+  message: string
+}
+
+/** Kind of a problem found by `validateQuery` */
+export type QueryIssueKind = /** A value can't be parsed, e.g. an address or topic that isn't valid hex */
+'InvalidValue'|
+/** The block range or a limit is out of range */
+'InvalidRange'|
+/** A log filter has more than 4 topics */
+'TooManyTopics'|
+/** Fields needed for the selected data or for decoding are not selected */
+'MissingFields'|
+/** The query doesn't select any data */
+'EmptyQuery';
+
 /** Response from a query that includes rate limit information. */
 export interface QueryResponseWithRateLimit {
   /** The query response data. */
@@ -613,6 +1374,15 @@ export interface QueryResponseWithRateLimit {
   rateLimit: RateLimitInfo
 }
 
+/**
+ * Result of `validateQuery`. The server rejects queries with errors, while warnings point
+ * at queries that likely don't return what was intended.
+ */
+export interface QueryValidation {
+  errors: Array<QueryIssue>
+  warnings: Array<QueryIssue>
+}
+
 /** Rate limit information from server response headers. */
 export interface RateLimitInfo {
   /** Total request quota for the current window. */
@@ -627,6 +1397,34 @@ export interface RateLimitInfo {
 
 export type ReconnectingTag =  'Reconnecting';
 
+export interface ReorgStreamDataEvent {
+  type: DataTag
+  response: QueryResponse
+}
+
+/**
+ * Reorg stream event, switch on 'event.type' to get different payload options
+ *
+ * switch (event.type) {
+ *   case "Data":
+ *     console.log("Next block:", event.response.nextBlock);
+ *     break;
+ *   case "Rollback":
+ *     console.log("Revert blocks", event.fromBlock, "to", event.toBlock);
+ *     break;
+ * }
+ */
+export type ReorgStreamEvent =
+  ReorgStreamDataEvent | ReorgStreamRollbackEvent
+
+export interface ReorgStreamRollbackEvent {
+  type: RollbackTag
+  /** First block that was rolled back, the stream continues from this block */
+  fromBlock: number
+  /** Last block that was delivered before the rollback was detected */
+  toBlock: number
+}
+
 export interface RollbackGuard {
   /** Block number of the last scanned block */
   blockNumber: number
@@ -648,6 +1446,8 @@ export interface RollbackGuard {
   firstParentHash: string
 }
 
+export type RollbackTag =  'Rollback';
+
 /** Determines query serialization format for HTTP requests. */
 export type SerializationFormat = /** Use JSON serialization (default) */
 'Json'|
@@ -666,6 +1466,14 @@ export type SerializationFormat = /** Use JSON serialization (default) */
 export declare function setLogLevel(level: string): void
 
 /** Config for hypersync event streaming. */
+/** Progress of a `streamSqlite` stream */
+export interface SqliteProgress {
+  /** Block the database holds data up to, exclusive */
+  nextBlock: number
+  /** Set if rows starting from this block were deleted because the chain was rolled back */
+  rolledBackFrom?: number
+}
+
 export interface StreamConfig {
   /**
    * Column mapping for stream function output.
@@ -698,8 +1506,25 @@ export interface StreamConfig {
   responseBytesFloor?: number
   /** Stream data in reverse order. Default: false. */
   reverse?: boolean
+  /**
+   * Keep following the chain head after catching up instead of ending the stream.
+   * New blocks are queried as soon as the server reports a new height. Default: false.
+   */
+  followHead?: boolean
+  /**
+   * Return hashes, addresses and other binary fields of blocks, transactions, logs and
+   * traces as Buffers instead of hex strings, the bytes are copied into the Buffers.
+   * Default: `binaryFields` of the client.
+   */
+  binaryFields?: boolean
 }
 
+/** Direction to search in when no block has exactly the requested timestamp */
+export type TimestampDirection = /** First block with a timestamp at or after the requested one */
+'After'|
+/** Last block with a timestamp at or before the requested one */
+'Before';
+
 /**
  * Evm trace object
  *
@@ -818,6 +1643,7 @@ export interface Transaction {
   l1Fee?: bigint
   l1GasPrice?: bigint
   l1GasUsed?: bigint
+  /** Fractional, a decimal string if `numericMode` is `decimalString` or `hex` */
   l1FeeScalar?: number
   gasUsedForL1?: bigint
   blobGasPrice?: bigint
@@ -919,6 +1745,14 @@ export interface TransactionSelection {
   exclude?: TransactionFilter
 }
 
+/**
+ * Check a query for mistakes without sending it.
+ *
+ * Pass the decoder the logs will be decoded with to also check that the fields it needs
+ * are selected.
+ */
+export declare function validateQuery(query: Query, options?: { decoder?: Decoder }): QueryValidation
+
 /**
  * Evm withdrawal object
  *
@@ -572,25 +572,46 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.ArrowResponseStream = nativeBinding.ArrowResponseStream
 module.exports.CallDecoder = nativeBinding.CallDecoder
+module.exports.CancellationHandle = nativeBinding.CancellationHandle
+module.exports.CheckpointStore = nativeBinding.CheckpointStore
 module.exports.Decoder = nativeBinding.Decoder
 module.exports.EventStream = nativeBinding.EventStream
 module.exports.HeightStream = nativeBinding.HeightStream
 module.exports.HypersyncClient = nativeBinding.HypersyncClient
+module.exports.LazyTable = nativeBinding.LazyTable
+module.exports.QueryBuilder = nativeBinding.QueryBuilder
 module.exports.QueryResponseStream = nativeBinding.QueryResponseStream
+module.exports.ReorgStream = nativeBinding.ReorgStream
+module.exports.SqliteStream = nativeBinding.SqliteStream
 module.exports.BlockField = nativeBinding.BlockField
+module.exports.Compression = nativeBinding.Compression
 module.exports.ConnectedTag = nativeBinding.ConnectedTag
+module.exports.DataTag = nativeBinding.DataTag
 module.exports.DataType = nativeBinding.DataType
+module.exports.DecodeFailureReason = nativeBinding.DecodeFailureReason
+module.exports.ErrorCode = nativeBinding.ErrorCode
 module.exports.HeightTag = nativeBinding.HeightTag
 module.exports.HexOutput = nativeBinding.HexOutput
 module.exports.JoinMode = nativeBinding.JoinMode
 module.exports.LogField = nativeBinding.LogField
+module.exports.NumericMode = nativeBinding.NumericMode
 module.exports.presetQueryBlocksAndTransactionHashes = nativeBinding.presetQueryBlocksAndTransactionHashes
 module.exports.presetQueryBlocksAndTransactions = nativeBinding.presetQueryBlocksAndTransactions
+module.exports.presetQueryEvents = nativeBinding.presetQueryEvents
 module.exports.presetQueryLogs = nativeBinding.presetQueryLogs
 module.exports.presetQueryLogsOfEvent = nativeBinding.presetQueryLogsOfEvent
+module.exports.queryFromCapnp = nativeBinding.queryFromCapnp
+module.exports.queryFromJson = nativeBinding.queryFromJson
+module.exports.QueryIssueKind = nativeBinding.QueryIssueKind
+module.exports.queryToCapnp = nativeBinding.queryToCapnp
+module.exports.queryToJson = nativeBinding.queryToJson
 module.exports.ReconnectingTag = nativeBinding.ReconnectingTag
+module.exports.RollbackTag = nativeBinding.RollbackTag
 module.exports.SerializationFormat = nativeBinding.SerializationFormat
 module.exports.setLogLevel = nativeBinding.setLogLevel
+module.exports.TimestampDirection = nativeBinding.TimestampDirection
 module.exports.TraceField = nativeBinding.TraceField
 module.exports.TransactionField = nativeBinding.TransactionField
+module.exports.validateQuery = nativeBinding.validateQuery
@@ -0,0 +1,97 @@
+use alloy_json_abi::{Event, Function, JsonAbi};
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// Parse a JSON ABI given either as a string or as parsed JSON.
+///
+/// Accepts the plain ABI array as well as Hardhat/Foundry artifacts that contain it
+/// under an `abi` key.
+pub fn parse_abi(abi: Value) -> Result<JsonAbi> {
+    let abi = match abi {
+        Value::String(json) => serde_json::from_str(&json).context("parse abi json")?,
+        abi => abi,
+    };
+    let abi = match abi {
+        Value::Object(mut artifact) => artifact.remove("abi").context("get abi of artifact")?,
+        abi => abi,
+    };
+
+    serde_json::from_value(abi).context("parse abi")
+}
+
+/// Events in the ABI that can be matched by topic0.
+/// Anonymous events are skipped since they don't have one.
+pub fn abi_events(abi: &JsonAbi) -> Vec<Event> {
+    abi.events()
+        .filter(|event| !event.anonymous)
+        .cloned()
+        .collect()
+}
+
+/// Events from signatures and the events of an optional JSON ABI
+pub fn parse_events(signatures: &[String], abi: Option<Value>) -> Result<Vec<Event>> {
+    let mut events = signatures
+        .iter()
+        .map(|sig| Event::parse(sig).with_context(|| format!("parse event signature {}", sig)))
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(abi) = abi {
+        events.extend(abi_events(&parse_abi(abi)?));
+    }
+    Ok(events)
+}
+
+/// Functions in the ABI
+pub fn abi_functions(abi: &JsonAbi) -> Vec<Function> {
+    abi.functions().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &str = r#"[
+        {"type":"event","name":"Transfer","anonymous":false,"inputs":[
+            {"name":"from","type":"address","indexed":true},
+            {"name":"to","type":"address","indexed":true},
+            {"name":"value","type":"uint256","indexed":false}]},
+        {"type":"event","name":"OrderFilled","anonymous":false,"inputs":[
+            {"name":"maker","type":"address","indexed":true},
+            {"name":"order","type":"tuple","indexed":false,"internalType":"struct Order","components":[
+                {"name":"amount","type":"uint256"},
+                {"name":"tokens","type":"address[]"}]}]},
+        {"type":"event","name":"Anon","anonymous":true,"inputs":[]},
+        {"type":"function","name":"fill","stateMutability":"nonpayable","inputs":[
+            {"name":"order","type":"tuple","components":[
+                {"name":"amount","type":"uint256"},
+                {"name":"tokens","type":"address[]"}]}],
+            "outputs":[{"name":"ok","type":"bool"}]}
+    ]"#;
+
+    #[test]
+    fn test_parse_abi_formats() {
+        let from_str = parse_abi(Value::String(ABI.into())).unwrap();
+        let from_value = parse_abi(serde_json::from_str(ABI).unwrap()).unwrap();
+        let artifact = serde_json::json!({
+            "contractName": "Exchange",
+            "abi": serde_json::from_str::<Value>(ABI).unwrap(),
+        });
+        let from_artifact = parse_abi(artifact).unwrap();
+
+        assert_eq!(from_str, from_value);
+        assert_eq!(from_str, from_artifact);
+        assert!(parse_abi(serde_json::json!({ "bytecode": "0x" })).is_err());
+    }
+
+    #[test]
+    fn test_abi_items() {
+        let abi = parse_abi(Value::String(ABI.into())).unwrap();
+
+        let events = abi_events(&abi);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| !event.anonymous));
+
+        let functions = abi_functions(&abi);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].inputs[0].components.len(), 2);
+    }
+}
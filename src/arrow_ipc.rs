@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use arrow::{array::RecordBatch, ipc::writer::StreamWriter};
+use napi::bindgen_prelude::Buffer;
+
+use crate::types::RollbackGuard;
+
+/// Tables of an arrow response, each encoded as an Arrow IPC stream that can be read with
+/// e.g. `tableFromIPC` of apache-arrow. Tables without data are not set.
+#[napi(object)]
+pub struct ArrowResponseData {
+    pub blocks: Option<Buffer>,
+    pub transactions: Option<Buffer>,
+    pub logs: Option<Buffer>,
+    pub traces: Option<Buffer>,
+    /// Logs decoded with `StreamConfig.eventSignature`
+    pub decoded_logs: Option<Buffer>,
+}
+
+/// Response from a blockchain query in Arrow IPC format
+#[napi(object)]
+pub struct ArrowResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Next block to query for, the responses are paginated so,
+    ///  the caller should continue the query from this block if they
+    ///  didn't get responses up to the to_block they specified in the Query.
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the query.
+    pub total_execution_time: i64,
+    /// Response data
+    pub data: ArrowResponseData,
+    /// Rollback guard, supposed to be used to detect rollbacks
+    pub rollback_guard: Option<RollbackGuard>,
+}
+
+pub fn convert_arrow_response(resp: hypersync_client::ArrowResponse) -> Result<ArrowResponse> {
+    let data = resp.data;
+    Ok(ArrowResponse {
+        archive_height: resp
+            .archive_height
+            .map(|h| h.try_into())
+            .transpose()
+            .context("convert height")?,
+        next_block: resp.next_block.try_into().context("convert next_block")?,
+        total_execution_time: resp
+            .total_execution_time
+            .try_into()
+            .context("convert total_execution_time")?,
+        data: ArrowResponseData {
+            blocks: write_ipc(&data.blocks).context("write blocks")?,
+            transactions: write_ipc(&data.transactions).context("write transactions")?,
+            logs: write_ipc(&data.logs).context("write logs")?,
+            traces: write_ipc(&data.traces).context("write traces")?,
+            decoded_logs: write_ipc(&data.decoded_logs).context("write decoded logs")?,
+        },
+        rollback_guard: resp
+            .rollback_guard
+            .map(RollbackGuard::try_from)
+            .transpose()
+            .context("convert rollback guard")?,
+    })
+}
+
+/// Encode batches as an Arrow IPC stream, None if there are no batches
+fn write_ipc(batches: &[RecordBatch]) -> Result<Option<Buffer>> {
+    encode_batches(batches).map(|bytes| bytes.map(Buffer::from))
+}
+
+fn encode_batches(batches: &[RecordBatch]) -> Result<Option<Vec<u8>>> {
+    let Some(first) = batches.first() else {
+        return Ok(None);
+    };
+
+    let mut bytes = Vec::new();
+    let mut writer =
+        StreamWriter::try_new(&mut bytes, &first.schema()).context("create ipc writer")?;
+    for batch in batches {
+        writer.write(batch).context("write batch")?;
+    }
+    writer.finish().context("finish ipc stream")?;
+    drop(writer);
+
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{BinaryArray, UInt64Array},
+        datatypes::{DataType, Field, Schema},
+        ipc::reader::StreamReader,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_encode_batches() {
+        assert_eq!(encode_batches(&[]).unwrap(), None);
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("number", DataType::UInt64, false),
+            Field::new("hash", DataType::Binary, true),
+        ]));
+        let batch = |numbers: Vec<u64>| {
+            let hashes = numbers
+                .iter()
+                .map(|n| Some(n.to_be_bytes().to_vec()))
+                .collect::<Vec<_>>();
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(UInt64Array::from(numbers)),
+                    Arc::new(BinaryArray::from_iter(hashes)),
+                ],
+            )
+            .unwrap()
+        };
+        let batches = [batch(vec![1, 2]), batch(vec![3])];
+
+        let bytes = encode_batches(&batches).unwrap().unwrap();
+        let read = StreamReader::try_new(bytes.as_slice(), None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, batches);
+    }
+}
@@ -0,0 +1,106 @@
+use std::{future::Future, sync::Arc};
+
+use anyhow::Result;
+use tokio::sync::mpsc;
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use crate::error::{coded, ErrorCode};
+
+/// Handle for cancelling in-flight client calls
+///
+/// Pass it as the last argument to `collect`, `collectEvents`, `collectParquet`, `get`,
+/// `stream` or `streamEvents` and call `cancel()` to abort the call. Calls that were aborted reject with an
+/// error that has `code` set to `"Aborted"`.
+///
+/// To drive it from an `AbortSignal`:
+/// `signal.addEventListener("abort", () => handle.cancel())`
+#[napi]
+#[derive(Default, Clone)]
+pub struct CancellationHandle {
+    token: CancellationToken,
+}
+
+#[napi]
+impl CancellationHandle {
+    /// Create a new cancellation handle
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every call this handle was passed to.
+    /// Calling it more than once has no effect.
+    #[napi]
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Whether `cancel()` has been called on this handle
+    #[napi(getter)]
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl CancellationHandle {
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+/// Error returned when a call is aborted through a `CancellationHandle`
+fn aborted_err() -> anyhow::Error {
+    coded(ErrorCode::Aborted, "operation aborted")
+}
+
+/// Run the future until it completes or the token is cancelled.
+///
+/// Dropping the future on cancellation drops any in-flight http requests with it.
+pub async fn run_cancellable<T, F>(token: Option<CancellationToken>, fut: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match token {
+        Some(token) => token
+            .run_until_cancelled(fut)
+            .await
+            .unwrap_or_else(|| Err(aborted_err())),
+        None => fut.await,
+    }
+}
+
+/// Receive the next item, closing the receiver and failing if the token is cancelled first
+pub async fn recv_cancellable<T>(
+    rx: &mut mpsc::Receiver<T>,
+    token: Option<CancellationToken>,
+) -> Result<Option<T>> {
+    match token {
+        Some(token) => match token.run_until_cancelled(rx.recv()).await {
+            Some(item) => Ok(item),
+            None => {
+                rx.close();
+                Err(aborted_err())
+            }
+        },
+        None => Ok(rx.recv().await),
+    }
+}
+
+/// Close the receiver as soon as the token is cancelled, so the tasks feeding it stop.
+///
+/// The returned guard stops the watcher when it is dropped along with the stream.
+pub fn close_on_cancel<T: Send + 'static>(
+    token: &CancellationToken,
+    rx: &Arc<tokio::sync::Mutex<mpsc::Receiver<T>>>,
+) -> DropGuard {
+    let token = token.child_token();
+    let watcher = token.clone();
+    let rx = Arc::downgrade(rx);
+    tokio::spawn(async move {
+        watcher.cancelled().await;
+        if let Some(rx) = rx.upgrade() {
+            rx.lock().await.close();
+        }
+    });
+    token.drop_guard()
+}
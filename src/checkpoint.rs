@@ -0,0 +1,405 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context, Result};
+use hypersync_client::{format::Hex, net_types};
+use napi::{bindgen_prelude::Promise, threadsafe_function::ThreadsafeFunction, Status};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{coded, ClientResult, ErrorCode};
+
+/// Position of a stream that can be persisted and resumed from
+#[napi(object)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// Block to continue the stream from
+    pub next_block: i64,
+    /// Number of the last scanned block, taken from the rollback guard of the response.
+    pub block_number: Option<i64>,
+    /// Hash of the last scanned block, taken from the rollback guard of the response.
+    /// It is compared against the chain before resuming to detect rollbacks.
+    pub hash: Option<String>,
+}
+
+impl Checkpoint {
+    pub fn from_response<T>(resp: &hypersync_client::QueryResponse<T>) -> Result<Self> {
+        Ok(Self {
+            next_block: resp.next_block.try_into().context("convert next_block")?,
+            block_number: resp
+                .rollback_guard
+                .as_ref()
+                .map(|rg| rg.block_number.try_into())
+                .transpose()
+                .context("convert rollback guard block_number")?,
+            hash: resp.rollback_guard.as_ref().map(|rg| rg.hash.encode_hex()),
+        })
+    }
+}
+
+type LoadFn = ThreadsafeFunction<(), Promise<Option<Checkpoint>>, (), Status, false, true>;
+type SaveFn = ThreadsafeFunction<Checkpoint, Promise<()>, Checkpoint, Status, false, true>;
+
+enum Backend {
+    File(PathBuf),
+    Custom { load: LoadFn, save: SaveFn },
+}
+
+/// Storage for stream checkpoints, used by `streamWithCheckpoint` and
+/// `streamEventsWithCheckpoint` to resume where the last stream left off.
+#[napi]
+#[derive(Clone)]
+pub struct CheckpointStore {
+    backend: Arc<Backend>,
+}
+
+#[napi]
+impl CheckpointStore {
+    /// Store checkpoints as JSON in the file at the given path
+    #[napi]
+    pub fn file(path: String) -> CheckpointStore {
+        Self {
+            backend: Arc::new(Backend::File(path.into())),
+        }
+    }
+
+    /// Store checkpoints with custom async load and save functions, e.g. in a database.
+    /// `load` should resolve to null if no checkpoint was saved yet.
+    #[napi]
+    pub fn custom(load: LoadFn, save: SaveFn) -> CheckpointStore {
+        Self {
+            backend: Arc::new(Backend::Custom { load, save }),
+        }
+    }
+
+    /// Load the last saved checkpoint, null if there is none
+    #[napi(ts_return_type = "Promise<Checkpoint | null>")]
+    pub async fn load(&self) -> ClientResult<Option<Checkpoint>> {
+        self.load_impl().await.into()
+    }
+
+    /// Save a checkpoint, replacing the previous one
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async fn save(&self, checkpoint: Checkpoint) -> ClientResult<()> {
+        self.save_impl(checkpoint).await.into()
+    }
+}
+
+impl CheckpointStore {
+    pub async fn load_impl(&self) -> Result<Option<Checkpoint>> {
+        match self.backend.as_ref() {
+            Backend::File(path) => {
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || read_checkpoint_file(&path))
+                    .await
+                    .context("join file read task")?
+            }
+            Backend::Custom { load, .. } => {
+                let promise = load
+                    .call_async_catch(())
+                    .await
+                    .context("call checkpoint load function")?;
+                promise.await.context("await checkpoint load function")
+            }
+        }
+    }
+
+    pub async fn save_impl(&self, checkpoint: Checkpoint) -> Result<()> {
+        match self.backend.as_ref() {
+            Backend::File(path) => {
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || write_checkpoint_file(&path, &checkpoint))
+                    .await
+                    .context("join file write task")?
+            }
+            Backend::Custom { save, .. } => {
+                let promise = save
+                    .call_async_catch(checkpoint)
+                    .await
+                    .context("call checkpoint save function")?;
+                promise.await.context("await checkpoint save function")
+            }
+        }
+    }
+}
+
+/// Records stream progress into a `CheckpointStore`.
+///
+/// The checkpoint of a response is only saved when the next response is requested, so a
+/// response counts as done once the consumer comes back for more.
+#[derive(Clone)]
+pub struct CheckpointWriter {
+    store: CheckpointStore,
+    pending: Arc<tokio::sync::Mutex<Option<Checkpoint>>>,
+}
+
+impl CheckpointWriter {
+    pub fn new(store: CheckpointStore) -> Self {
+        Self {
+            store,
+            pending: Default::default(),
+        }
+    }
+
+    /// Save the checkpoint of the previously delivered response, if there is one
+    pub async fn commit(&self) -> Result<()> {
+        let mut pending = self.pending.lock().await;
+        if let Some(checkpoint) = pending.as_ref() {
+            self.store.save_impl(checkpoint.clone()).await?;
+            *pending = None;
+        }
+        Ok(())
+    }
+
+    /// Remember the checkpoint of a response that was handed to the consumer
+    pub async fn stage(&self, checkpoint: Checkpoint) {
+        *self.pending.lock().await = Some(checkpoint);
+    }
+}
+
+fn read_checkpoint_file(path: &std::path::Path) -> Result<Option<Checkpoint>> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).context("read checkpoint file"),
+    };
+
+    serde_json::from_slice(&data)
+        .map(Some)
+        .context("parse checkpoint file")
+}
+
+fn write_checkpoint_file(path: &std::path::Path, checkpoint: &Checkpoint) -> Result<()> {
+    let data = serde_json::to_vec(checkpoint).context("serialize checkpoint")?;
+
+    // Write to a temporary file first so a crash mid-write can't corrupt the last checkpoint
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    std::fs::write(&tmp_path, data).context("write temporary checkpoint file")?;
+    std::fs::rename(&tmp_path, path).context("replace checkpoint file")
+}
+
+/// Load the checkpoint from the store and move the query to start from it.
+///
+/// Returns None if the checkpoint is at or past the end of the query, so there is nothing left
+/// to stream. Fails with `Rollback` if the block recorded in the checkpoint is no longer part of
+/// the chain.
+pub async fn resume_query(
+    client: &hypersync_client::Client,
+    query: net_types::Query,
+    store: &CheckpointStore,
+) -> Result<Option<net_types::Query>> {
+    let checkpoint = match store.load_impl().await.context("load checkpoint")? {
+        Some(checkpoint) => checkpoint,
+        None => return Ok(Some(query)),
+    };
+
+    let chain_hash = match checkpoint.block_number {
+        Some(block_number) if checkpoint.hash.is_some() => {
+            let block_number = u64::try_from(block_number).context("convert block_number")?;
+            get_block_hash(client, block_number)
+                .await
+                .context("get block hash of checkpoint")?
+        }
+        _ => None,
+    };
+
+    apply_checkpoint(query, &checkpoint, chain_hash.as_deref())
+}
+
+/// Move the query to start from the checkpoint, given the chain's hash of the checkpoint block
+fn apply_checkpoint(
+    mut query: net_types::Query,
+    checkpoint: &Checkpoint,
+    chain_hash: Option<&str>,
+) -> Result<Option<net_types::Query>> {
+    if let (Some(block_number), Some(hash)) = (checkpoint.block_number, &checkpoint.hash) {
+        // custom stores can return hashes in any case
+        if !chain_hash.is_some_and(|chain_hash| chain_hash.eq_ignore_ascii_case(hash)) {
+            return Err(coded(
+                ErrorCode::Rollback,
+                format!(
+                    "checkpoint hash mismatch at block {}: stored {} but chain has {}, the chain was rolled back since the checkpoint was saved",
+                    block_number,
+                    hash,
+                    chain_hash.unwrap_or("no block"),
+                ),
+            ));
+        }
+    }
+
+    let next_block = u64::try_from(checkpoint.next_block).context("convert next_block")?;
+    if query
+        .to_block
+        .is_some_and(|to_block| next_block >= to_block)
+    {
+        return Ok(None);
+    }
+    query.from_block = query.from_block.max(next_block);
+
+    Ok(Some(query))
+}
+
+/// Get the hash of the block with the given number, None if the server doesn't have it
+pub async fn get_block_hash(
+    client: &hypersync_client::Client,
+    block_number: u64,
+) -> Result<Option<String>> {
+    let query = net_types::Query {
+        from_block: block_number,
+        to_block: Some(block_number + 1),
+        include_all_blocks: true,
+        field_selection: net_types::FieldSelection {
+            block: [net_types::BlockField::Number, net_types::BlockField::Hash]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let res = client.get(&query).await.context("run block query")?;
+
+    Ok(res
+        .data
+        .blocks
+        .iter()
+        .flatten()
+        .find(|b| b.number == Some(block_number))
+        .and_then(|b| b.hash.as_ref())
+        .map(|h| h.encode_hex()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::HypersyncError;
+
+    const HASH: &str = "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3";
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "hypersync-checkpoint-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_checkpoint_file_roundtrip() {
+        let path = temp_path("roundtrip");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(read_checkpoint_file(&path).unwrap(), None);
+
+        let checkpoint = Checkpoint {
+            next_block: 20_000_001,
+            block_number: Some(20_000_000),
+            hash: Some(HASH.to_string()),
+        };
+        write_checkpoint_file(&path, &checkpoint).unwrap();
+        assert_eq!(read_checkpoint_file(&path).unwrap(), Some(checkpoint));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_resume_from_store() {
+        let path = temp_path("resume");
+        let checkpoint = Checkpoint {
+            next_block: 101,
+            block_number: Some(100),
+            hash: Some(HASH.to_string()),
+        };
+        write_checkpoint_file(&path, &checkpoint).unwrap();
+
+        let store = CheckpointStore::file(path.to_string_lossy().into_owned());
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let loaded = rt.block_on(store.load_impl()).unwrap().unwrap();
+        assert_eq!(loaded, checkpoint);
+
+        let query = net_types::Query {
+            from_block: 10,
+            ..Default::default()
+        };
+        let resumed = apply_checkpoint(query.clone(), &loaded, Some(HASH))
+            .unwrap()
+            .unwrap();
+        assert_eq!(resumed.from_block, 101);
+
+        // a range that ends at or before the checkpoint is already finished
+        for to_block in [100, 101] {
+            let finished = net_types::Query {
+                to_block: Some(to_block),
+                ..query.clone()
+            };
+            assert_eq!(
+                apply_checkpoint(finished, &loaded, Some(HASH)).unwrap(),
+                None
+            );
+        }
+        let unfinished = net_types::Query {
+            to_block: Some(102),
+            ..query.clone()
+        };
+        assert_eq!(
+            apply_checkpoint(unfinished, &loaded, Some(HASH))
+                .unwrap()
+                .unwrap()
+                .from_block,
+            101
+        );
+
+        // a query starting past the checkpoint isn't moved back
+        let later = net_types::Query {
+            from_block: 200,
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_checkpoint(later, &loaded, Some(HASH))
+                .unwrap()
+                .unwrap()
+                .from_block,
+            200
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_resume_hash_mismatch() {
+        let checkpoint = Checkpoint {
+            next_block: 101,
+            block_number: Some(100),
+            hash: Some(HASH.to_string()),
+        };
+        let query = net_types::Query::default();
+
+        let other = format!("0x{}", "00".repeat(32));
+        let err = apply_checkpoint(query.clone(), &checkpoint, Some(&other)).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch at block 100"));
+        assert_eq!(HypersyncError::from(err).code, ErrorCode::Rollback);
+
+        let err = apply_checkpoint(query.clone(), &checkpoint, None).unwrap_err();
+        assert!(err.to_string().contains("no block"));
+        assert_eq!(HypersyncError::from(err).code, ErrorCode::Rollback);
+
+        // hashes are compared regardless of case
+        let upper = format!("0x{}", HASH[2..].to_uppercase());
+        assert!(apply_checkpoint(query.clone(), &checkpoint, Some(&upper)).is_ok());
+
+        // checkpoints without a hash can't be checked and are resumed as is
+        let unchecked = Checkpoint {
+            hash: None,
+            ..checkpoint
+        };
+        assert_eq!(
+            apply_checkpoint(query, &unchecked, None)
+                .unwrap()
+                .unwrap()
+                .from_block,
+            101
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use napi::{
+    bindgen_prelude::{
+        Array, FromNapiValue, JsObjectValue, JsValue, Object, ObjectRef, ToNapiValue,
+    },
+    sys, Env, Unknown,
+};
+
+/// Rows that are added to a JS result per turn of the event loop
+const CHUNK_ROWS: usize = 500;
+
+/// Result whose rows are added to its JS object in chunks, letting the event loop run between
+/// chunks instead of blocking it until the whole result is created
+///
+/// Converts to a promise of the complete object if more than one chunk is needed, which the
+/// promise of an async function adopts.
+pub struct Chunked<T> {
+    shell: T,
+    rows: VecDeque<Box<dyn AppendRows>>,
+}
+
+impl<T> Chunked<T> {
+    /// `shell` is the result with empty arrays in place of `rows`
+    pub fn new(shell: T, rows: Vec<Box<dyn AppendRows>>) -> Self {
+        Self {
+            shell,
+            rows: rows.into(),
+        }
+    }
+}
+
+impl<T: ToNapiValue> ToNapiValue for Chunked<T> {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        let object = Object::from_napi_value(env, T::to_napi_value(env, val.shell)?)?;
+        let pending = Pending {
+            object: object.create_ref()?,
+            rows: val.rows,
+        };
+        let env = Env::from_raw(env);
+        ToNapiValue::to_napi_value(env.raw(), pending.append_chunk(&env)?)
+    }
+}
+
+/// Rows of one array of a result, e.g. the logs of a `QueryResponse`
+pub struct PendingRows<T> {
+    /// Property names leading from the result object to the array
+    path: &'static [&'static str],
+    rows: std::vec::IntoIter<T>,
+    next_index: u32,
+}
+
+impl<T: ToNapiValue + Send + 'static> PendingRows<T> {
+    pub fn boxed(path: &'static [&'static str], rows: Vec<T>) -> Box<dyn AppendRows> {
+        Box::new(Self {
+            path,
+            rows: rows.into_iter(),
+            next_index: 0,
+        })
+    }
+}
+
+pub trait AppendRows: Send {
+    /// Append up to `limit` rows to the array in `object`, returns how many were appended
+    fn append(&mut self, object: Object, limit: usize) -> napi::Result<usize>;
+
+    fn is_done(&self) -> bool;
+}
+
+impl<T: ToNapiValue + Send> AppendRows for PendingRows<T> {
+    fn append(&mut self, object: Object, limit: usize) -> napi::Result<usize> {
+        let (name, parents) = self.path.split_last().expect("path to rows is empty");
+        let mut parent = object;
+        for name in parents {
+            parent = parent.get_named_property_unchecked(name)?;
+        }
+        let mut array: Array = parent.get_named_property_unchecked(name)?;
+
+        let mut appended = 0;
+        for row in self.rows.by_ref().take(limit) {
+            array.set(self.next_index, row)?;
+            self.next_index += 1;
+            appended += 1;
+        }
+        Ok(appended)
+    }
+
+    fn is_done(&self) -> bool {
+        self.rows.as_slice().is_empty()
+    }
+}
+
+/// JS object of a result and the rows that are yet to be added to it
+struct Pending {
+    object: ObjectRef<false>,
+    rows: VecDeque<Box<dyn AppendRows>>,
+}
+
+impl Pending {
+    /// Append the next chunk of rows, returns the object if it is complete, or else a promise
+    /// that appends the remaining chunks in later turns of the event loop
+    fn append_chunk(mut self, env: &Env) -> napi::Result<Unknown<'_>> {
+        let object = self.object.get_value(env)?;
+        let mut limit = CHUNK_ROWS;
+        while limit > 0 {
+            let Some(rows) = self.rows.front_mut() else {
+                break;
+            };
+            limit -= rows.append(object, limit)?;
+            if rows.is_done() {
+                self.rows.pop_front();
+            }
+        }
+
+        if self.rows.is_empty() {
+            self.object.unref(env)?;
+            return Ok(object.to_unknown());
+        }
+
+        // Resolving on the runtime hands control back to the event loop before the next chunk
+        let promise = env
+            .spawn_future_with_callback(async { Ok(()) }, move |env, ()| self.append_chunk(env))?;
+        Ok(promise.to_unknown())
+    }
+}
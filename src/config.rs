@@ -33,6 +33,13 @@ pub struct StreamConfig {
     pub response_bytes_floor: Option<i64>,
     /// Stream data in reverse order. Default: false.
     pub reverse: Option<bool>,
+    /// Keep following the chain head after catching up instead of ending the stream.
+    /// New blocks are queried as soon as the server reports a new height. Default: false.
+    pub follow_head: Option<bool>,
+    /// Return hashes, addresses and other binary fields of blocks, transactions, logs and
+    /// traces as Buffers instead of hex strings, the bytes are copied into the Buffers.
+    /// Default: `binaryFields` of the client.
+    pub binary_fields: Option<bool>,
 }
 
 /// Determines format of Binary column
@@ -179,6 +186,17 @@ pub struct ClientConfig {
     pub retry_ceiling_ms: Option<i64>,
     /// Enable checksum addresses in responses.
     pub enable_checksum_addresses: Option<bool>,
+    /// Return hashes, addresses and other binary fields of blocks, transactions, logs and
+    /// traces as Buffers instead of hex strings. The bytes are copied into the Buffers, and
+    /// checksumming doesn't apply to them.
+    /// See `encoding.d.ts` for the types of such responses.
+    /// Default: false.
+    pub binary_fields: Option<bool>,
+    /// Representation of quantities of blocks, transactions, logs and traces, e.g. `decimalString`
+    /// to serialize responses to JSON without bigint workarounds.
+    /// Default: bigints for amounts and numbers for block numbers, indexes and timestamps.
+    /// See `encoding.d.ts` for the types of responses in other modes.
+    pub numeric_mode: Option<NumericMode>,
     /// Query serialization format to use for HTTP requests. Default: Json.
     pub serialization_format: Option<SerializationFormat>,
     /// Whether to use query caching when using CapnProto serialization format.
@@ -186,6 +204,10 @@ pub struct ClientConfig {
     /// Whether to proactively sleep when the rate limit is exhausted instead of
     /// sending requests that will be rejected with 429. Default: true.
     pub proactive_rate_limit_sleep: Option<bool>,
+    /// Validate queries with `validateQuery` before sending them. Warnings are logged and
+    /// queries with errors are rejected with `InvalidQuery`. When `eventSignature` is set in the
+    /// stream config, the log fields needed to decode it have to be selected. Default: false.
+    pub debug: Option<bool>,
 }
 
 impl From<ClientConfig> for hypersync_client::ClientConfig {
@@ -236,3 +258,21 @@ pub enum SerializationFormat {
     /// Use Cap'n Proto binary serialization
     CapnProto,
 }
+
+/// Representation of quantities like block numbers, gas and values in responses.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericMode {
+    /// All quantities as bigints, except the fractional `l1FeeScalar` which stays a number
+    #[napi(value = "bigint")]
+    BigInt,
+    /// All quantities as decimal strings
+    #[napi(value = "decimalString")]
+    DecimalString,
+    /// All quantities as prefixed hex strings i.e. 0x1b4, except the fractional `l1FeeScalar` which is a decimal string
+    #[napi(value = "hex")]
+    Hex,
+    /// Quantities up to `Number.MAX_SAFE_INTEGER` as numbers, larger ones as decimal strings
+    #[napi(value = "number-when-safe")]
+    NumberWhenSafe,
+}
@@ -1,48 +1,353 @@
-use std::sync::Arc;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
+use alloy_dyn_abi::{DynSolEvent, DynSolValue, Specifier};
+use alloy_primitives::{Address, B256};
 use anyhow::{Context, Result};
-use hypersync_client::format::{Data, Hex, LogArgument};
+use hypersync_client::format::LogArgument;
+use napi::Env;
 
 use crate::{
-    map_err,
-    types::{DecodedEvent, DecodedSolValue, Event, Log},
+    abi::{abi_events, parse_abi, parse_events},
+    error::{js_error, ClientResult, ErrorCode, WithCode},
+    query::LogField,
+    types::{DecodedEvent, DecodedSolValue, Encoding, Event, Log, NamedSolValue, StringOrBuffer},
 };
 
+#[derive(Hash, Eq, PartialEq)]
+struct EventKey {
+    topic0: B256,
+    num_topics: usize,
+}
+
+struct EventDef {
+    event: alloy_json_abi::Event,
+    resolved: DynSolEvent,
+}
+
+/// Event definitions keyed by topic0 and number of topics, so events that only differ in
+/// which params are indexed can be told apart
+#[derive(Default)]
+struct EventMap(HashMap<EventKey, EventDef>);
+
+impl EventMap {
+    fn new(events: impl IntoIterator<Item = alloy_json_abi::Event>) -> Result<Self> {
+        events
+            .into_iter()
+            .map(|event| {
+                let key = EventKey {
+                    topic0: event.selector(),
+                    num_topics: event.num_topics(),
+                };
+                let resolved = event
+                    .resolve()
+                    .with_context(|| format!("resolve event {}", event.signature()))?;
+                Ok((key, EventDef { event, resolved }))
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    fn from_signatures(signatures: &[String]) -> Result<Self> {
+        Self::new(parse_events(signatures, None)?)
+    }
+
+    fn get(&self, topic0: B256, num_topics: usize) -> Option<&EventDef> {
+        self.0.get(&EventKey { topic0, num_topics })
+    }
+
+    /// Numbers of topics of the events with the given topic0
+    fn topic_counts(&self, topic0: B256) -> impl Iterator<Item = usize> + '_ {
+        self.0
+            .keys()
+            .filter(move |key| key.topic0 == topic0)
+            .map(|key| key.num_topics)
+    }
+
+    fn max_num_topics(&self) -> usize {
+        self.0.keys().map(|key| key.num_topics).max().unwrap_or(0)
+    }
+}
+
+/// Events of a set of contracts, used to create an address aware decoder
+#[napi(object)]
+pub struct ContractEvents {
+    /// Addresses of the contracts. Entries without addresses are used for logs of any other
+    /// contract, or when the events of their contract don't match the log.
+    pub addresses: Option<Vec<String>>,
+    /// Event signatures
+    pub signatures: Option<Vec<String>>,
+    /// JSON ABI as a JSON string or parsed JSON, either the plain ABI array or a
+    /// Hardhat/Foundry artifact that contains it
+    pub abi: Option<serde_json::Value>,
+}
+
+/// Event definitions of specific contracts, with a global set for logs of any other address
+#[derive(Default)]
+struct EventRegistry {
+    global: EventMap,
+    contracts: HashMap<Address, Arc<EventMap>>,
+}
+
+impl EventRegistry {
+    fn from_contracts(contracts: Vec<ContractEvents>) -> Result<Self> {
+        let mut global = Vec::new();
+        let mut registry = Self::default();
+        for (i, contract) in contracts.into_iter().enumerate() {
+            let addresses = contract
+                .addresses
+                .clone()
+                .unwrap_or_default()
+                .iter()
+                .map(|address| {
+                    Address::from_str(address)
+                        .with_context(|| format!("parse contract address {}", address))
+                })
+                .collect::<Result<Vec<_>>>()
+                .with_context(|| format!("contracts[{}]", i))?;
+            let events = parse_events(&contract.signatures.unwrap_or_default(), contract.abi)
+                .with_context(|| format!("contracts[{}]", i))?;
+
+            if addresses.is_empty() {
+                global.extend(events);
+                continue;
+            }
+            let events =
+                Arc::new(EventMap::new(events).with_context(|| format!("contracts[{}]", i))?);
+            for address in addresses {
+                if registry.contracts.insert(address, events.clone()).is_some() {
+                    anyhow::bail!("contract {} is listed more than once", address);
+                }
+            }
+        }
+        registry.global = EventMap::new(global)?;
+        Ok(registry)
+    }
+
+    /// Events of the log's contract take precedence over the global ones.
+    ///
+    /// Fails with `TopicCount` if events with the topic0 exist but none of them has the given
+    /// number of topics.
+    fn get(
+        &self,
+        address: Option<Address>,
+        topic0: B256,
+        num_topics: usize,
+    ) -> Result<&EventDef, Failure> {
+        let maps = address
+            .and_then(|address| self.contracts.get(&address))
+            .map(|events| events.as_ref())
+            .into_iter()
+            .chain([&self.global]);
+
+        if let Some(def) = maps
+            .clone()
+            .find_map(|events| events.get(topic0, num_topics))
+        {
+            return Ok(def);
+        }
+
+        let mut expected = maps
+            .flat_map(|events| events.topic_counts(topic0))
+            .collect::<Vec<_>>();
+        if expected.is_empty() {
+            return Err(Failure::UnknownTopic0);
+        }
+        expected.sort_unstable();
+        expected.dedup();
+        Err(Failure::TopicCount {
+            expected,
+            actual: num_topics,
+        })
+    }
+}
+
+impl From<EventMap> for EventRegistry {
+    fn from(global: EventMap) -> Self {
+        Self {
+            global,
+            contracts: HashMap::new(),
+        }
+    }
+}
+
+/// Why a log couldn't be decoded
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeFailureReason {
+    /// No event of the decoder matches topic0.
+    /// Usually means the log is from an unrelated event.
+    UnknownTopic0,
+    /// Topics or data of the log are missing, check the field selection of the query.
+    /// Also reported when the log has fewer topics than the event with its topic0.
+    MissingData,
+    /// The log matches an event by topic0 but its topics or data don't fit the event params
+    AbiMismatch,
+    /// Topics or data of the log are not valid hex
+    MalformedLog,
+}
+
+#[napi(object)]
+pub struct DecodeFailure {
+    pub reason: DecodeFailureReason,
+    /// Description of the failure
+    pub detail: String,
+}
+
+/// Result of decoding a single log, exactly one of `event` and `failure` is set
+#[napi(object, object_from_js = false)]
+pub struct DecodeResult {
+    pub event: Option<DecodedEvent>,
+    pub failure: Option<DecodeFailure>,
+}
+
+enum Failure {
+    UnknownTopic0,
+    /// The topic0 is known but no event with it has the log's number of topics
+    TopicCount {
+        expected: Vec<usize>,
+        actual: usize,
+    },
+    MissingData(&'static str),
+    AbiMismatch(anyhow::Error),
+    MalformedLog(anyhow::Error),
+}
+
+impl From<Result<DecodedEvent, Failure>> for DecodeResult {
+    fn from(res: Result<DecodedEvent, Failure>) -> Self {
+        let failure = match res {
+            Ok(event) => {
+                return Self {
+                    event: Some(event),
+                    failure: None,
+                }
+            }
+            Err(failure) => failure,
+        };
+
+        let (reason, detail) = match failure {
+            Failure::UnknownTopic0 => (
+                DecodeFailureReason::UnknownTopic0,
+                "no event matches topic0".to_owned(),
+            ),
+            Failure::TopicCount { expected, actual } => {
+                // fewer topics than any event expects usually means topic1..topic3 weren't selected
+                let missing = expected.iter().all(|&n| n > actual);
+                let expected = expected
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" or ");
+                if missing {
+                    (
+                        DecodeFailureReason::MissingData,
+                        format!(
+                            "log has {} topics but its event has {}, check that topic1..topic3 are selected",
+                            actual, expected
+                        ),
+                    )
+                } else {
+                    (
+                        DecodeFailureReason::AbiMismatch,
+                        format!(
+                            "log has {} topics but the events matching its topic0 have {}",
+                            actual, expected
+                        ),
+                    )
+                }
+            }
+            Failure::MissingData(detail) => (DecodeFailureReason::MissingData, detail.to_owned()),
+            Failure::AbiMismatch(e) => (DecodeFailureReason::AbiMismatch, format!("{:#}", e)),
+            Failure::MalformedLog(e) => (DecodeFailureReason::MalformedLog, format!("{:#}", e)),
+        };
+        Self {
+            event: None,
+            failure: Some(DecodeFailure { reason, detail }),
+        }
+    }
+}
+
 /// Decoder for Ethereum events and function calls
 #[napi]
 #[derive(Clone)]
 pub struct Decoder {
-    inner: Arc<hypersync_client::Decoder>,
+    inner: Arc<EventRegistry>,
     checksummed_addresses: bool,
+    named_params: bool,
+    binary_values: bool,
 }
 
 #[napi]
 impl Decoder {
     /// Create decoder from event signatures
     #[napi]
-    pub fn from_signatures(signatures: Vec<String>) -> napi::Result<Decoder> {
-        let inner = hypersync_client::Decoder::from_signatures(&signatures)
-            .context("create inner decoder")
-            .map_err(map_err)?;
-        Ok(Self {
-            inner: Arc::new(inner),
-            checksummed_addresses: false,
-        })
+    pub fn from_signatures(env: Env, signatures: Vec<String>) -> napi::Result<Decoder> {
+        Self::build(
+            &env,
+            EventMap::from_signatures(&signatures).map(Into::into),
+            false,
+        )
     }
 
     /// Create decoder from event signatures with checksum option
     #[napi]
     pub fn from_signatures_with_checksum(
+        env: Env,
         signatures: Vec<String>,
         checksum: bool,
     ) -> napi::Result<Decoder> {
-        let inner = hypersync_client::Decoder::from_signatures(&signatures)
-            .context("create inner decoder")
-            .map_err(map_err)?;
-        Ok(Self {
-            inner: Arc::new(inner),
-            checksummed_addresses: checksum,
-        })
+        Self::build(
+            &env,
+            EventMap::from_signatures(&signatures).map(Into::into),
+            checksum,
+        )
+    }
+
+    /// Create decoder from the events of a JSON ABI
+    ///
+    /// Takes the ABI as a JSON string or parsed JSON, either the plain ABI array or a
+    /// Hardhat/Foundry artifact that contains it.
+    #[napi]
+    pub fn from_abi(env: Env, abi: serde_json::Value) -> napi::Result<Decoder> {
+        let events = parse_abi(abi)
+            .and_then(|abi| EventMap::new(abi_events(&abi)))
+            .map(Into::into);
+        Self::build(&env, events, false)
+    }
+
+    /// Create decoder from the events of several JSON ABIs keyed by contract address,
+    /// e.g. a deployments file. Logs are decoded with the ABI of their contract first, and
+    /// with the events of all ABIs if it doesn't match.
+    /// Keys that aren't addresses, like contract names, only add to the events of all ABIs.
+    #[napi(ts_args_type = "abis: Record<string, any>")]
+    pub fn from_abis(env: Env, abis: HashMap<String, serde_json::Value>) -> napi::Result<Decoder> {
+        let registry = abis
+            .into_iter()
+            .map(|(key, abi)| {
+                let events = parse_abi(abi)
+                    .map(|abi| abi_events(&abi))
+                    .with_context(|| format!("parse abi of {}", key))?;
+                Ok((Address::from_str(&key).ok(), events))
+            })
+            .collect::<Result<Vec<_>>>()
+            .and_then(|abis| {
+                let mut contracts = HashMap::new();
+                for (address, events) in &abis {
+                    if let Some(address) = address {
+                        contracts.insert(*address, Arc::new(EventMap::new(events.clone())?));
+                    }
+                }
+                let global = EventMap::new(abis.into_iter().flat_map(|(_, events)| events))?;
+                Ok(EventRegistry { global, contracts })
+            });
+        Self::build(&env, registry, false)
+    }
+
+    /// Create decoder that picks the events to decode a log with by its address, falling back
+    /// to the entries without addresses. Useful when contracts emit events with the same
+    /// topic0 but a different layout.
+    #[napi]
+    pub fn from_contracts(env: Env, contracts: Vec<ContractEvents>) -> napi::Result<Decoder> {
+        Self::build(&env, EventRegistry::from_contracts(contracts), false)
     }
 
     /// Enable checksummed addresses in decoded output
@@ -57,30 +362,60 @@ impl Decoder {
         self.checksummed_addresses = false;
     }
 
-    /// Decode logs asynchronously
+    /// Also return the name, signature and topic0 of decoded events, with their params
+    /// keyed by name in `params`
+    #[napi]
+    pub fn enable_named_params(&mut self) {
+        self.named_params = true;
+    }
+
+    /// Only return positional `indexed` and `body` params in decoded events
+    #[napi]
+    pub fn disable_named_params(&mut self) {
+        self.named_params = false;
+    }
+
+    /// Return addresses, bytes and fixed bytes values as Buffers instead of hex strings
+    #[napi]
+    pub fn enable_binary_values(&mut self) {
+        self.binary_values = true;
+    }
+
+    /// Return addresses, bytes and fixed bytes values as hex strings
     #[napi]
-    pub async fn decode_logs(&self, logs: Vec<Log>) -> Vec<Option<DecodedEvent>> {
+    pub fn disable_binary_values(&mut self) {
+        self.binary_values = false;
+    }
+
+    /// Decode logs asynchronously
+    #[napi(ts_return_type = "Promise<Array<DecodedEvent | undefined | null>>")]
+    pub async fn decode_logs(&self, logs: Vec<Log>) -> ClientResult<Vec<Option<DecodedEvent>>> {
         let decoder = self.clone();
         tokio::task::spawn_blocking(move || decoder.decode_logs_sync(logs))
             .await
-            .unwrap()
+            .context("join decode task")
+            .into()
     }
 
     /// Decode logs synchronously
     #[napi]
     pub fn decode_logs_sync(&self, logs: Vec<Log>) -> Vec<Option<DecodedEvent>> {
         logs.iter()
-            .map(|log| self.decode_impl(log).ok().flatten())
+            .map(|log| self.decode_impl(log).ok())
             .collect::<Vec<_>>()
     }
 
     /// Decode events asynchronously
-    #[napi]
-    pub async fn decode_events(&self, events: Vec<Event>) -> Vec<Option<DecodedEvent>> {
+    #[napi(ts_return_type = "Promise<Array<DecodedEvent | undefined | null>>")]
+    pub async fn decode_events(
+        &self,
+        events: Vec<Event>,
+    ) -> ClientResult<Vec<Option<DecodedEvent>>> {
         let decoder = self.clone();
         tokio::task::spawn_blocking(move || decoder.decode_events_sync(events))
             .await
-            .unwrap()
+            .context("join decode task")
+            .into()
     }
 
     /// Decode events synchronously
@@ -88,51 +423,511 @@ impl Decoder {
     pub fn decode_events_sync(&self, events: Vec<Event>) -> Vec<Option<DecodedEvent>> {
         events
             .iter()
-            .map(|event| self.decode_impl(&event.log).ok().flatten())
+            .map(|event| self.decode_impl(&event.log).ok())
             .collect::<Vec<_>>()
     }
 
-    fn decode_impl(&self, log: &Log) -> Result<Option<DecodedEvent>> {
+    /// Decode logs asynchronously, returning why each log that couldn't be decoded failed
+    #[napi(ts_return_type = "Promise<Array<DecodeResult>>")]
+    pub async fn decode_logs_with_diagnostics(
+        &self,
+        logs: Vec<Log>,
+    ) -> ClientResult<Vec<DecodeResult>> {
+        let decoder = self.clone();
+        tokio::task::spawn_blocking(move || decoder.decode_logs_with_diagnostics_sync(logs))
+            .await
+            .context("join decode task")
+            .into()
+    }
+
+    /// Decode logs synchronously, returning why each log that couldn't be decoded failed
+    #[napi]
+    pub fn decode_logs_with_diagnostics_sync(&self, logs: Vec<Log>) -> Vec<DecodeResult> {
+        logs.iter()
+            .map(|log| self.decode_impl(log).into())
+            .collect()
+    }
+
+    /// Decode events asynchronously, returning why each event that couldn't be decoded failed
+    #[napi(ts_return_type = "Promise<Array<DecodeResult>>")]
+    pub async fn decode_events_with_diagnostics(
+        &self,
+        events: Vec<Event>,
+    ) -> ClientResult<Vec<DecodeResult>> {
+        let decoder = self.clone();
+        tokio::task::spawn_blocking(move || decoder.decode_events_with_diagnostics_sync(events))
+            .await
+            .context("join decode task")
+            .into()
+    }
+
+    /// Decode events synchronously, returning why each event that couldn't be decoded failed
+    #[napi]
+    pub fn decode_events_with_diagnostics_sync(&self, events: Vec<Event>) -> Vec<DecodeResult> {
+        events
+            .iter()
+            .map(|event| self.decode_impl(&event.log).into())
+            .collect()
+    }
+
+    fn decode_impl(&self, log: &Log) -> Result<DecodedEvent, Failure> {
         let topics = log
             .topics
             .iter()
             .map(|v| {
                 v.as_ref()
-                    .map(|v| LogArgument::decode_hex(v).context("decode topic"))
+                    .map(|v| {
+                        v.to_data()
+                            .and_then(|v| Ok(LogArgument::try_from(v.as_ref())?))
+                            .context("decode topic")
+                    })
                     .transpose()
             })
             .collect::<Result<Vec<_>>>()
-            .context("decode topics")?;
+            .context("decode topics")
+            .map_err(Failure::MalformedLog)?;
+
+        let topic0 = match topics.first() {
+            Some(Some(topic0)) => topic0,
+            _ => return Err(Failure::MissingData("log has no topic0")),
+        };
 
-        let topic0 = topics
-            .first()
-            .context("get topic0")?
+        // only parse the address if it matters, so decoders without contracts accept any log
+        let address = match &log.address {
+            Some(address) if !self.inner.contracts.is_empty() => Some(
+                parse_address(address)
+                    .context("decode address")
+                    .map_err(Failure::MalformedLog)?,
+            ),
+            _ => None,
+        };
+
+        let def = self.inner.get(
+            address,
+            B256::from_slice(topic0.as_slice()),
+            topics.iter().filter(|t| t.is_some()).count(),
+        )?;
+
+        let data = log
+            .data
             .as_ref()
-            .context("topic0 is null")?;
+            .ok_or(Failure::MissingData("log has no data"))?;
+        let data = data
+            .to_data()
+            .context("decode data")
+            .map_err(Failure::MalformedLog)?;
+
+        let topics = topics
+            .iter()
+            .map_while(|t| t.as_ref())
+            .map(|t| B256::from_slice(t.as_slice()));
+        let decoded = def
+            .resolved
+            .decode_log_parts(topics, &data)
+            .with_context(|| format!("decode log as {}", def.event.signature()))
+            .map_err(Failure::AbiMismatch)?;
 
-        let data = log.data.as_ref().context("get log.data")?;
-        let data = Data::decode_hex(data).context("decode data")?;
+        let mut event = DecodedEvent::default();
+        if self.named_params {
+            event.name = Some(def.event.name.clone());
+            event.signature = Some(def.event.signature());
+            event.topic0 = Some(StringOrBuffer::from_slice(
+                topic0.as_slice(),
+                self.encoding(),
+            ));
+            event.params = Some(self.named_params(&def.event, &decoded.indexed, &decoded.body));
+        }
+        event.indexed = decoded
+            .indexed
+            .into_iter()
+            .map(|v| DecodedSolValue::new(v, self.encoding()))
+            .collect();
+        event.body = decoded
+            .body
+            .into_iter()
+            .map(|v| DecodedSolValue::new(v, self.encoding()))
+            .collect();
 
-        let decoded = match self
+        Ok(event)
+    }
+
+    /// Key the decoded values by param name, in the order the params are declared
+    fn named_params(
+        &self,
+        event: &alloy_json_abi::Event,
+        indexed: &[DynSolValue],
+        body: &[DynSolValue],
+    ) -> NamedSolValue {
+        let mut indexed = indexed.iter();
+        let mut body = body.iter();
+
+        let params = event
+            .inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, input)| {
+                let val = if input.indexed {
+                    indexed.next()
+                } else {
+                    body.next()
+                }?;
+                let name = if input.name.is_empty() {
+                    i.to_string()
+                } else {
+                    input.name.clone()
+                };
+                let val = NamedSolValue::new(val.clone(), &input.components, self.encoding());
+                Some((name, val))
+            })
+            .collect();
+
+        NamedSolValue::Struct(params)
+    }
+}
+
+/// Log fields needed to decode logs with up to `num_topics` topics
+fn log_fields(num_topics: usize, with_address: bool) -> Vec<LogField> {
+    let mut fields = vec![LogField::Data];
+    if with_address {
+        fields.push(LogField::Address);
+    }
+    fields.extend(
+        [
+            LogField::Topic0,
+            LogField::Topic1,
+            LogField::Topic2,
+            LogField::Topic3,
+        ]
+        .into_iter()
+        .take(num_topics),
+    );
+    fields
+}
+
+/// Log fields that have to be selected to decode logs with the event signature of a
+/// `StreamConfig`
+pub fn event_signature_log_fields(signature: &str) -> Result<Vec<LogField>> {
+    let events = parse_events(&[signature.to_owned()], None)
+        .context("parse event signature")
+        .with_code(ErrorCode::InvalidQuery)?;
+    let num_topics = events.iter().map(|e| e.num_topics()).max().unwrap_or(0);
+    Ok(log_fields(num_topics, false))
+}
+
+impl Decoder {
+    /// Log fields that have to be selected in a query to decode its logs
+    pub fn required_log_fields(&self) -> Vec<LogField> {
+        let num_topics = self
             .inner
-            .decode(topic0.as_slice(), &topics, &data)
-            .context("decode log")?
-        {
-            Some(v) => v,
-            None => return Ok(None),
+            .contracts
+            .values()
+            .map(|events| events.max_num_topics())
+            .chain([self.inner.global.max_num_topics()])
+            .max()
+            .unwrap_or(0);
+
+        log_fields(num_topics, !self.inner.contracts.is_empty())
+    }
+
+    fn build(
+        env: &Env,
+        events: Result<EventRegistry>,
+        checksummed_addresses: bool,
+    ) -> napi::Result<Self> {
+        let events = events
+            .context("create decoder")
+            .with_code(ErrorCode::DecodeError)
+            .map_err(js_error(env))?;
+        Ok(Self {
+            inner: Arc::new(events),
+            checksummed_addresses,
+            named_params: false,
+            binary_values: false,
+        })
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding {
+            checksum_addresses: self.checksummed_addresses,
+            binary: self.binary_values,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parse the address of a log, hex strings may be checksummed
+fn parse_address(address: &StringOrBuffer) -> Result<Address> {
+    match address {
+        StringOrBuffer::String(s) => Ok(Address::from_str(s)?),
+        StringOrBuffer::Buffer(bytes) => Ok(Address::try_from(bytes.as_slice())?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Address, U256};
+    use hypersync_client::format::{Data, Hex};
+    use napi::bindgen_prelude::{Either3, Either4};
+
+    use super::*;
+
+    const ABI: &str = r#"[
+        {"type":"event","name":"OrderFilled","anonymous":false,"inputs":[
+            {"name":"maker","type":"address","indexed":true},
+            {"name":"order","type":"tuple","indexed":false,"components":[
+                {"name":"amount","type":"uint256"},
+                {"name":"tokens","type":"address[]"}]},
+            {"name":"","type":"bool","indexed":false}]}
+    ]"#;
+
+    fn field<'a>(val: &'a NamedSolValue, name: &str) -> &'a NamedSolValue {
+        match val {
+            NamedSolValue::Struct(fields) => &fields.iter().find(|(n, _)| n == name).unwrap().1,
+            _ => panic!("not a struct"),
+        }
+    }
+
+    fn decoder() -> (Decoder, B256) {
+        let abi = parse_abi(serde_json::Value::String(ABI.into())).unwrap();
+        let event = abi_events(&abi).remove(0);
+        let topic0 = event.selector();
+        let decoder = Decoder {
+            inner: Arc::new(EventMap::new([event]).unwrap().into()),
+            checksummed_addresses: false,
+            named_params: true,
+            binary_values: false,
+        };
+        (decoder, topic0)
+    }
+
+    #[test]
+    fn test_named_params() {
+        let (decoder, topic0) = decoder();
+
+        let token = Address::repeat_byte(0xbb);
+        let data = DynSolValue::Tuple(vec![
+            DynSolValue::Tuple(vec![
+                DynSolValue::Uint(U256::from(100), 256),
+                DynSolValue::Array(vec![DynSolValue::Address(token)]),
+            ]),
+            DynSolValue::Bool(true),
+        ])
+        .abi_encode_params();
+        let log = Log {
+            topics: vec![
+                Some(topic0.to_string().into()),
+                Some(B256::left_padding_from(&[0xaa; 20]).to_string().into()),
+            ],
+            data: Some(Data::from(data).encode_hex().into()),
+            ..Default::default()
         };
 
-        Ok(Some(DecodedEvent {
-            indexed: decoded
-                .indexed
-                .into_iter()
-                .map(|v| DecodedSolValue::new(v, self.checksummed_addresses))
-                .collect(),
-            body: decoded
-                .body
-                .into_iter()
-                .map(|v| DecodedSolValue::new(v, self.checksummed_addresses))
-                .collect(),
-        }))
+        let decoded = decoder.decode_impl(&log).ok().unwrap();
+        assert_eq!(decoded.name.as_deref(), Some("OrderFilled"));
+        assert_eq!(
+            decoded.signature.as_deref(),
+            Some("OrderFilled(address,(uint256,address[]),bool)")
+        );
+        assert_eq!(decoded.topic0, Some(topic0.to_string().into()));
+        assert_eq!(decoded.body.len(), 2);
+
+        let params = decoded.params.unwrap();
+        assert!(matches!(
+            field(&params, "maker"),
+            NamedSolValue::Value(Either3::C(addr))
+                if addr.as_str() == Some(&format!("0x{}", "aa".repeat(20)))
+        ));
+        let order = field(&params, "order");
+        assert!(matches!(
+            field(order, "amount"),
+            NamedSolValue::Value(Either3::B(_))
+        ));
+        assert!(
+            matches!(field(order, "tokens"), NamedSolValue::Array(tokens) if tokens.len() == 1)
+        );
+        assert!(matches!(
+            field(&params, "2"),
+            NamedSolValue::Value(Either3::A(true))
+        ));
+    }
+
+    #[test]
+    fn test_decode_binary_log() {
+        let (mut decoder, topic0) = decoder();
+        decoder.enable_binary_values();
+
+        let data = DynSolValue::Tuple(vec![
+            DynSolValue::Tuple(vec![
+                DynSolValue::Uint(U256::from(100), 256),
+                DynSolValue::Array(vec![]),
+            ]),
+            DynSolValue::Bool(true),
+        ])
+        .abi_encode_params();
+        let log = Log {
+            topics: vec![
+                Some(StringOrBuffer::Buffer(topic0.to_vec())),
+                Some(StringOrBuffer::Buffer(
+                    B256::left_padding_from(&[0xaa; 20]).to_vec(),
+                )),
+            ],
+            data: Some(StringOrBuffer::Buffer(data)),
+            ..Default::default()
+        };
+
+        let decoded = decoder.decode_impl(&log).ok().unwrap();
+        assert!(matches!(
+            &decoded.indexed[0].val,
+            Either4::C(StringOrBuffer::Buffer(maker)) if *maker == [0xaa; 20]
+        ));
+        assert_eq!(
+            decoded.topic0,
+            Some(StringOrBuffer::Buffer(topic0.to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_decode_failure_reasons() {
+        let (decoder, topic0) = decoder();
+        let maker = B256::left_padding_from(&[0xaa; 20]).to_string();
+        let reason = |topics: Vec<Option<String>>, data: Option<&str>| {
+            let log = Log {
+                topics: topics.into_iter().map(|t| t.map(Into::into)).collect(),
+                data: data.map(|d| d.to_owned().into()),
+                ..Default::default()
+            };
+            DecodeResult::from(decoder.decode_impl(&log))
+                .failure
+                .map(|f| f.reason)
+        };
+
+        assert_eq!(
+            reason(vec![Some(B256::ZERO.to_string())], Some("0x")),
+            Some(DecodeFailureReason::UnknownTopic0)
+        );
+        // the indexed param is missing, e.g. because topic1 wasn't selected
+        assert_eq!(
+            reason(vec![Some(topic0.to_string())], Some("0x")),
+            Some(DecodeFailureReason::MissingData)
+        );
+        // more topics than the event has
+        assert_eq!(
+            reason(
+                vec![
+                    Some(topic0.to_string()),
+                    Some(maker.clone()),
+                    Some(maker.clone())
+                ],
+                Some("0x")
+            ),
+            Some(DecodeFailureReason::AbiMismatch)
+        );
+        assert_eq!(
+            reason(vec![], Some("0x")),
+            Some(DecodeFailureReason::MissingData)
+        );
+        assert_eq!(
+            reason(vec![Some(topic0.to_string()), Some(maker.clone())], None),
+            Some(DecodeFailureReason::MissingData)
+        );
+        assert_eq!(
+            reason(
+                vec![Some(topic0.to_string()), Some(maker.clone())],
+                Some("0x1234")
+            ),
+            Some(DecodeFailureReason::AbiMismatch)
+        );
+        assert_eq!(
+            reason(vec![Some(topic0.to_string()), Some(maker)], Some("0xzz")),
+            Some(DecodeFailureReason::MalformedLog)
+        );
+    }
+
+    #[test]
+    fn test_registry_picks_events_by_address() {
+        let token = Address::repeat_byte(0x01);
+        let other = Address::repeat_byte(0x02);
+        let registry = EventRegistry::from_contracts(vec![
+            ContractEvents {
+                addresses: Some(vec![token.to_string()]),
+                signatures: Some(vec![
+                    "Transfer(address indexed from, address to, uint256 indexed value)".into(),
+                ]),
+                abi: None,
+            },
+            ContractEvents {
+                addresses: None,
+                signatures: Some(vec![
+                    "Transfer(address indexed from, address indexed to, uint256 value)".into(),
+                ]),
+                abi: None,
+            },
+        ])
+        .unwrap();
+        let decoder = Decoder {
+            inner: Arc::new(registry),
+            checksummed_addresses: false,
+            named_params: false,
+            binary_values: false,
+        };
+
+        let topic0 = alloy_json_abi::Event::parse("Transfer(address,address,uint256)")
+            .unwrap()
+            .selector();
+        let log = |address: Address| Log {
+            address: Some(address.to_string().into()),
+            topics: vec![
+                Some(topic0.to_string().into()),
+                Some(B256::left_padding_from(&[0xaa; 20]).to_string().into()),
+                Some(B256::left_padding_from(&[0xbb; 20]).to_string().into()),
+            ],
+            data: Some(format!("0x{:064x}", 7).into()),
+            ..Default::default()
+        };
+
+        // the value is indexed in the contract's Transfer, so the data holds `to`
+        let decoded = decoder.decode_impl(&log(token)).ok().unwrap();
+        assert!(
+            matches!(&decoded.body[0].val, Either4::C(to) if to.as_str().is_some_and(|to| to.ends_with("07")))
+        );
+        // other contracts use the global Transfer, where the data holds the value
+        let decoded = decoder.decode_impl(&log(other)).ok().unwrap();
+        assert!(matches!(&decoded.body[0].val, Either4::B(_)));
+
+        assert!(EventRegistry::from_contracts(vec![ContractEvents {
+            addresses: Some(vec!["0xnot an address".into()]),
+            signatures: None,
+            abi: None,
+        }])
+        .is_err());
+    }
+
+    #[test]
+    fn test_event_signature_log_fields() {
+        let fields = event_signature_log_fields(
+            "Transfer(address indexed from, address indexed to, uint256 value)",
+        )
+        .unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                LogField::Data,
+                LogField::Topic0,
+                LogField::Topic1,
+                LogField::Topic2
+            ]
+        );
+        assert!(event_signature_log_fields("Transfer(").is_err());
+
+        let query = crate::query::Query {
+            field_selection: crate::query::FieldSelection {
+                log: Some(vec![LogField::Data, LogField::Topic0]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let res = crate::validate::validate(&query, Some(&fields));
+        assert_eq!(res.errors.len(), 1);
+        assert!(res.errors[0].message.contains("Topic1"));
     }
 }
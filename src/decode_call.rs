@@ -1,46 +1,210 @@
-use crate::map_err;
-use crate::types::{DecodedSolValue, Trace, Transaction};
-use anyhow::Context;
-use hypersync_client::format::{Data, Hex};
-use std::sync::Arc;
+use crate::abi::{abi_functions, parse_abi};
+use crate::error::{js_error, ClientResult, ErrorCode, WithCode};
+use crate::types::{DecodedRevert, DecodedSolValue, Encoding, StringOrBuffer, Trace, Transaction};
+use alloy_dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt};
+use alloy_json_abi::{Error as AbiError, Function, JsonAbi};
+use alloy_primitives::Selector;
+use anyhow::{Context, Result};
+use napi::Env;
+use std::{collections::HashMap, sync::Arc};
+
+/// Functions and errors keyed by selector
+struct CallAbi {
+    functions: HashMap<Selector, Function>,
+    errors: HashMap<Selector, AbiError>,
+}
+
+impl CallAbi {
+    /// `Error(string)` and `Panic(uint256)` are always known, since the compiler emits them
+    fn new(
+        functions: impl IntoIterator<Item = Function>,
+        errors: impl IntoIterator<Item = AbiError>,
+    ) -> Result<Self> {
+        let builtin = [
+            AbiError::parse("Error(string message)").context("parse Error(string)")?,
+            AbiError::parse("Panic(uint256 code)").context("parse Panic(uint256)")?,
+        ];
+
+        Ok(Self {
+            functions: functions
+                .into_iter()
+                .map(|function| (function.selector(), function))
+                .collect(),
+            errors: builtin
+                .into_iter()
+                .chain(errors)
+                .map(|error| (error.selector(), error))
+                .collect(),
+        })
+    }
+
+    /// Parse function signatures, signatures starting with `error` are parsed as custom errors
+    fn from_signatures(signatures: &[String]) -> Result<Self> {
+        let mut functions = Vec::new();
+        let mut errors = Vec::new();
+        for sig in signatures {
+            if sig.trim_start().starts_with("error ") {
+                errors.push(
+                    AbiError::parse(sig)
+                        .with_context(|| format!("parse error signature {}", sig))?,
+                );
+            } else {
+                functions.push(
+                    Function::parse(sig)
+                        .with_context(|| format!("parse function signature {}", sig))?,
+                );
+            }
+        }
+        Self::new(functions, errors)
+    }
+
+    fn from_abis(abis: &[JsonAbi]) -> Result<Self> {
+        Self::new(
+            abis.iter().flat_map(abi_functions),
+            abis.iter()
+                .flat_map(|abi| abi.errors().cloned().collect::<Vec<_>>()),
+        )
+    }
+
+    /// Decode calldata, returns None if the selector is unknown
+    fn decode_input(&self, data: &[u8]) -> Result<Option<Vec<DynSolValue>>> {
+        let (selector, args) = split_selector(data)?;
+
+        let function = match self.functions.get(selector) {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+
+        function
+            .abi_decode_input(args)
+            .with_context(|| format!("decode input of {}", function.signature()))
+            .map(Some)
+    }
+
+    /// Decode the return data of a call, using the function matching the selector of the calldata.
+    /// Returns None if the selector is unknown.
+    fn decode_output(&self, input: &[u8], output: &[u8]) -> Result<Option<Vec<DynSolValue>>> {
+        let (selector, _) = split_selector(input)?;
+
+        let function = match self.functions.get(selector) {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+
+        function
+            .abi_decode_output(output)
+            .with_context(|| format!("decode output of {}", function.signature()))
+            .map(Some)
+    }
+
+    /// Decode revert data, returns None if it is empty or the selector is unknown
+    fn decode_revert(&self, data: &[u8]) -> Result<Option<(&AbiError, Vec<DynSolValue>)>> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let (selector, args) = split_selector(data)?;
+
+        let error = match self.errors.get(selector) {
+            Some(error) => error,
+            None => return Ok(None),
+        };
+
+        let params = error
+            .abi_decode_input(args)
+            .with_context(|| format!("decode revert data of {}", error.signature()))?;
+        Ok(Some((error, params)))
+    }
+}
+
+fn split_selector(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    if data.len() < 4 {
+        anyhow::bail!(
+            "data is {} bytes long, too short to hold a selector",
+            data.len()
+        );
+    }
+    Ok(data.split_at(4))
+}
+
+/// Message of a revert, the reason string of `Error(string)` or what a `Panic(uint256)` code means
+fn revert_message(error: &AbiError, params: &[DynSolValue]) -> Option<String> {
+    match (error.signature().as_str(), params) {
+        ("Error(string)", [DynSolValue::String(reason)]) => Some(reason.clone()),
+        ("Panic(uint256)", [DynSolValue::Uint(code, _)]) => {
+            let code = u64::try_from(*code).ok();
+            let description = match code {
+                Some(0x00) => "generic compiler inserted panic",
+                Some(0x01) => "assertion failed",
+                Some(0x11) => "arithmetic overflow or underflow",
+                Some(0x12) => "division or modulo by zero",
+                Some(0x21) => "invalid enum value",
+                Some(0x22) => "invalid storage byte array encoding",
+                Some(0x31) => "pop on empty array",
+                Some(0x32) => "array index out of bounds",
+                Some(0x41) => "out of memory",
+                Some(0x51) => "call to zero-initialized function",
+                _ => "unknown panic code",
+            };
+            Some(description.to_owned())
+        }
+        _ => None,
+    }
+}
 
 /// Decoder for Ethereum function calls
 #[napi]
 #[derive(Clone)]
 pub struct CallDecoder {
-    inner: Arc<hypersync_client::CallDecoder>,
+    inner: Arc<CallAbi>,
     checksummed_addresses: bool,
+    binary_values: bool,
 }
 
 #[napi]
 impl CallDecoder {
-    /// Create call decoder from function signatures
+    /// Create call decoder from function signatures.
+    /// Signatures of custom errors, like `error InsufficientBalance(uint256 available)`,
+    /// are used to decode revert data.
     #[napi]
-    pub fn from_signatures(signatures: Vec<String>) -> napi::Result<CallDecoder> {
-        let inner = hypersync_client::CallDecoder::from_signatures(&signatures)
-            .context("build inner decoder")
-            .map_err(map_err)?;
-
-        Ok(Self {
-            inner: Arc::new(inner),
-            checksummed_addresses: false,
-        })
+    pub fn from_signatures(env: Env, signatures: Vec<String>) -> napi::Result<CallDecoder> {
+        Self::build(&env, CallAbi::from_signatures(&signatures), false)
     }
 
     /// Create call decoder from function signatures with checksum option
     #[napi]
     pub fn from_signatures_with_checksum(
+        env: Env,
         signatures: Vec<String>,
         checksum: bool,
     ) -> napi::Result<CallDecoder> {
-        let inner = hypersync_client::CallDecoder::from_signatures(&signatures)
-            .context("build inner decoder")
-            .map_err(map_err)?;
+        Self::build(&env, CallAbi::from_signatures(&signatures), checksum)
+    }
 
-        Ok(Self {
-            inner: Arc::new(inner),
-            checksummed_addresses: checksum,
-        })
+    /// Create call decoder from the functions and errors of a JSON ABI
+    ///
+    /// Takes the ABI as a JSON string or parsed JSON, either the plain ABI array or a
+    /// Hardhat/Foundry artifact that contains it.
+    #[napi]
+    pub fn from_abi(env: Env, abi: serde_json::Value) -> napi::Result<CallDecoder> {
+        let items = parse_abi(abi).and_then(|abi| CallAbi::from_abis(&[abi]));
+        Self::build(&env, items, false)
+    }
+
+    /// Create call decoder from the functions and errors of several JSON ABIs keyed by contract address,
+    /// e.g. a deployments file. Inputs are matched by selector regardless of the address called.
+    #[napi(ts_args_type = "abis: Record<string, any>")]
+    pub fn from_abis(
+        env: Env,
+        abis: HashMap<String, serde_json::Value>,
+    ) -> napi::Result<CallDecoder> {
+        let items = abis
+            .into_iter()
+            .map(|(address, abi)| {
+                parse_abi(abi).with_context(|| format!("parse abi of {}", address))
+            })
+            .collect::<Result<Vec<_>>>()
+            .and_then(|abis| CallAbi::from_abis(&abis));
+        Self::build(&env, items, false)
     }
 
     pub fn enable_checksummed_addresses(&mut self) {
@@ -51,90 +215,392 @@ impl CallDecoder {
         self.checksummed_addresses = false;
     }
 
-    /// Decode function call inputs asynchronously
+    /// Return addresses, bytes and fixed bytes values as Buffers instead of hex strings
     #[napi]
-    pub async fn decode_inputs(&self, inputs: Vec<String>) -> Vec<Option<Vec<DecodedSolValue>>> {
+    pub fn enable_binary_values(&mut self) {
+        self.binary_values = true;
+    }
+
+    /// Return addresses, bytes and fixed bytes values as hex strings
+    #[napi]
+    pub fn disable_binary_values(&mut self) {
+        self.binary_values = false;
+    }
+
+    /// Decode function call inputs asynchronously
+    #[napi(ts_return_type = "Promise<Array<Array<DecodedSolValue> | undefined | null>>")]
+    pub async fn decode_inputs(
+        &self,
+        #[napi(ts_arg_type = "Array<string | Buffer>")] inputs: Vec<StringOrBuffer>,
+    ) -> ClientResult<Vec<Option<Vec<DecodedSolValue>>>> {
         let decoder = self.clone();
 
         tokio::task::spawn_blocking(move || decoder.decode_inputs_sync(inputs))
             .await
-            .unwrap()
+            .context("join decode task")
+            .into()
     }
 
     /// Decode transaction inputs asynchronously
-    #[napi]
+    #[napi(ts_return_type = "Promise<Array<Array<DecodedSolValue> | undefined | null>>")]
     pub async fn decode_transactions_input(
         &self,
         txs: Vec<Transaction>,
-    ) -> Vec<Option<Vec<DecodedSolValue>>> {
+    ) -> ClientResult<Vec<Option<Vec<DecodedSolValue>>>> {
         let decoder = self.clone();
 
         tokio::task::spawn_blocking(move || decoder.decode_transactions_input_sync(txs))
             .await
-            .unwrap()
+            .context("join decode task")
+            .into()
     }
 
     /// Decode trace inputs asynchronously
-    #[napi]
+    #[napi(ts_return_type = "Promise<Array<Array<DecodedSolValue> | undefined | null>>")]
     pub async fn decode_traces_input(
         &self,
         traces: Vec<Trace>,
-    ) -> Vec<Option<Vec<DecodedSolValue>>> {
+    ) -> ClientResult<Vec<Option<Vec<DecodedSolValue>>>> {
         let decoder = self.clone();
 
         tokio::task::spawn_blocking(move || decoder.decode_traces_input_sync(traces))
             .await
-            .unwrap()
+            .context("join decode task")
+            .into()
     }
 
-    /// Decode function call inputs synchronously
+    /// Decode function call inputs synchronously.
+    /// Inputs that can't be decoded are returned as null.
     #[napi]
-    pub fn decode_inputs_sync(&self, inputs: Vec<String>) -> Vec<Option<Vec<DecodedSolValue>>> {
+    pub fn decode_inputs_sync(
+        &self,
+        #[napi(ts_arg_type = "Array<string | Buffer>")] inputs: Vec<StringOrBuffer>,
+    ) -> Vec<Option<Vec<DecodedSolValue>>> {
         inputs
-            .into_iter()
-            .map(|input| self.decode_impl(input))
+            .iter()
+            .map(|input| self.decode_input(input).ok().flatten())
             .collect()
     }
 
-    /// Decode transaction inputs synchronously
+    /// Decode transaction inputs synchronously.
+    /// Inputs that can't be decoded are returned as null.
     #[napi]
     pub fn decode_transactions_input_sync(
         &self,
         txs: Vec<Transaction>,
     ) -> Vec<Option<Vec<DecodedSolValue>>> {
-        txs.into_iter()
-            .map(|tx| self.decode_impl(tx.input?))
+        txs.iter()
+            .map(|tx| self.decode_input(tx.input.as_ref()?).ok().flatten())
             .collect()
     }
 
-    /// Decode trace inputs synchronously
+    /// Decode trace inputs synchronously.
+    /// Inputs that can't be decoded are returned as null.
     #[napi]
     pub fn decode_traces_input_sync(
         &self,
         traces: Vec<Trace>,
     ) -> Vec<Option<Vec<DecodedSolValue>>> {
         traces
-            .into_iter()
-            .map(|trace| self.decode_impl(trace.input?))
+            .iter()
+            .map(|trace| self.decode_input(trace.input.as_ref()?).ok().flatten())
             .collect()
     }
 
-    /// Decode a single input string
+    /// Decode the return data of traces asynchronously, using the function called in the input
+    #[napi(ts_return_type = "Promise<Array<Array<DecodedSolValue> | undefined | null>>")]
+    pub async fn decode_traces_output(
+        &self,
+        traces: Vec<Trace>,
+    ) -> ClientResult<Vec<Option<Vec<DecodedSolValue>>>> {
+        let decoder = self.clone();
+
+        tokio::task::spawn_blocking(move || decoder.decode_traces_output_sync(traces))
+            .await
+            .context("join decode task")
+            .into()
+    }
+
+    /// Decode the return data of traces synchronously, using the function called in the input.
+    /// Reverted traces and outputs that can't be decoded are returned as null.
     #[napi]
-    pub fn decode_impl(&self, input: String) -> Option<Vec<DecodedSolValue>> {
-        let input = Data::decode_hex(input.as_str())
-            .context("decode input")
+    pub fn decode_traces_output_sync(
+        &self,
+        traces: Vec<Trace>,
+    ) -> Vec<Option<Vec<DecodedSolValue>>> {
+        traces
+            .iter()
+            .map(|trace| {
+                if trace.error.is_some() {
+                    return None;
+                }
+                self.decode_output(trace.input.as_ref()?, trace.output.as_ref()?)
+                    .ok()
+                    .flatten()
+            })
+            .collect()
+    }
+
+    /// Decode why traces reverted asynchronously
+    #[napi(ts_return_type = "Promise<Array<DecodedRevert | undefined | null>>")]
+    pub async fn decode_traces_revert(
+        &self,
+        traces: Vec<Trace>,
+    ) -> ClientResult<Vec<Option<DecodedRevert>>> {
+        let decoder = self.clone();
+
+        tokio::task::spawn_blocking(move || decoder.decode_traces_revert_sync(traces))
+            .await
+            .context("join decode task")
+            .into()
+    }
+
+    /// Decode why traces reverted synchronously, from the revert data in their output.
+    /// Traces that didn't revert and revert data that can't be decoded are returned as null.
+    #[napi]
+    pub fn decode_traces_revert_sync(&self, traces: Vec<Trace>) -> Vec<Option<DecodedRevert>> {
+        traces
+            .iter()
+            .map(|trace| {
+                trace.error.as_ref()?;
+                self.decode_revert(trace.output.as_ref()?).ok().flatten()
+            })
+            .collect()
+    }
+
+    /// Decode revert data asynchronously
+    #[napi(ts_return_type = "Promise<Array<DecodedRevert | undefined | null>>")]
+    pub async fn decode_reverts(
+        &self,
+        #[napi(ts_arg_type = "Array<string | Buffer>")] data: Vec<StringOrBuffer>,
+    ) -> ClientResult<Vec<Option<DecodedRevert>>> {
+        let decoder = self.clone();
+
+        tokio::task::spawn_blocking(move || decoder.decode_reverts_sync(data))
+            .await
+            .context("join decode task")
+            .into()
+    }
+
+    /// Decode revert data synchronously.
+    /// Data that is empty, has an unknown selector or can't be decoded is returned as null.
+    #[napi]
+    pub fn decode_reverts_sync(
+        &self,
+        #[napi(ts_arg_type = "Array<string | Buffer>")] data: Vec<StringOrBuffer>,
+    ) -> Vec<Option<DecodedRevert>> {
+        data.iter()
+            .map(|data| self.decode_revert(data).ok().flatten())
+            .collect()
+    }
+
+    /// Decode a single input.
+    /// Returns null if the selector is unknown and throws if the input can't be decoded.
+    #[napi]
+    pub fn decode_impl(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "string | Buffer")] input: StringOrBuffer,
+    ) -> napi::Result<Option<Vec<DecodedSolValue>>> {
+        self.decode_input(&input)
+            .with_code(ErrorCode::DecodeError)
+            .map_err(js_error(&env))
+    }
+}
+
+impl CallDecoder {
+    fn build(env: &Env, items: Result<CallAbi>, checksummed_addresses: bool) -> napi::Result<Self> {
+        let items = items
+            .context("create call decoder")
+            .with_code(ErrorCode::DecodeError)
+            .map_err(js_error(env))?;
+        Ok(Self {
+            inner: Arc::new(items),
+            checksummed_addresses,
+            binary_values: false,
+        })
+    }
+
+    fn decode_input(&self, input: &StringOrBuffer) -> Result<Option<Vec<DecodedSolValue>>> {
+        let input = input.to_data().context("decode input")?;
+        let decoded = self.inner.decode_input(&input)?;
+
+        Ok(decoded.map(|decoded| self.convert_values(decoded)))
+    }
+
+    fn decode_output(
+        &self,
+        input: &StringOrBuffer,
+        output: &StringOrBuffer,
+    ) -> Result<Option<Vec<DecodedSolValue>>> {
+        let input = input.to_data().context("decode input")?;
+        let output = output.to_data().context("decode output")?;
+        let decoded = self.inner.decode_output(&input, &output)?;
+
+        Ok(decoded.map(|decoded| self.convert_values(decoded)))
+    }
+
+    fn decode_revert(&self, data: &StringOrBuffer) -> Result<Option<DecodedRevert>> {
+        let data = data.to_data().context("decode revert data")?;
+        let (error, params) = match self.inner.decode_revert(&data)? {
+            Some(decoded) => decoded,
+            None => return Ok(None),
+        };
+
+        Ok(Some(DecodedRevert {
+            name: error.name.clone(),
+            signature: error.signature(),
+            message: revert_message(error, &params),
+            params: self.convert_values(params),
+        }))
+    }
+
+    fn convert_values(&self, values: Vec<DynSolValue>) -> Vec<DecodedSolValue> {
+        values
+            .into_iter()
+            .map(|value| DecodedSolValue::new(value, self.encoding()))
+            .collect()
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding {
+            checksum_addresses: self.checksummed_addresses,
+            binary: self.binary_values,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hypersync_client::format::{Data, Hex};
+    use napi::bindgen_prelude::Either4;
+
+    use super::*;
+
+    const TRANSFER: &str = "transfer(address to, uint256 amount) returns (bool)";
+    // transfer(0xbb..bb, 100)
+    const TRANSFER_INPUT: &str = "0xa9059cbb000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb0000000000000000000000000000000000000000000000000000000000000064";
+
+    fn decoder() -> CallDecoder {
+        CallDecoder {
+            inner: Arc::new(
+                CallAbi::from_signatures(&[
+                    TRANSFER.to_owned(),
+                    "error InsufficientBalance(uint256 available, uint256 required)".to_owned(),
+                ])
+                .unwrap(),
+            ),
+            checksummed_addresses: false,
+            binary_values: false,
+        }
+    }
+
+    fn hex(s: &str) -> StringOrBuffer {
+        StringOrBuffer::String(s.to_owned())
+    }
+
+    #[test]
+    fn test_decode_input() {
+        let decoded = decoder()
+            .decode_input(&hex(TRANSFER_INPUT))
+            .unwrap()
             .unwrap();
-        let decoded_input = self
-            .inner
-            .decode_input(&input)
-            .context("decode log")
+        assert_eq!(decoded.len(), 2);
+        assert!(matches!(
+            &decoded[0].val,
+            Either4::C(to) if to.as_str() == Some(&format!("0x{}", "bb".repeat(20)))
+        ));
+    }
+
+    #[test]
+    fn test_decode_garbage_hex() {
+        assert!(decoder().decode_input(&hex("0xnot hex")).is_err());
+        assert!(decoder().decode_input(&hex("")).is_err());
+    }
+
+    #[test]
+    fn test_decode_truncated_calldata() {
+        let decoder = decoder();
+        assert!(decoder.decode_input(&hex("0xa905")).is_err());
+        assert!(decoder.decode_input(&hex(&TRANSFER_INPUT[..50])).is_err());
+    }
+
+    #[test]
+    fn test_decode_unknown_selector() {
+        let input = format!("0xdeadbeef{}", &TRANSFER_INPUT[10..]);
+        assert!(decoder().decode_input(&hex(&input)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sync_batch_skips_bad_inputs() {
+        let decoded =
+            decoder().decode_inputs_sync(vec![hex("0x"), hex("garbage"), hex(TRANSFER_INPUT)]);
+        assert!(decoded[0].is_none());
+        assert!(decoded[1].is_none());
+        assert!(decoded[2].is_some());
+    }
+
+    #[test]
+    fn test_decode_output() {
+        let output = format!("0x{}1", "0".repeat(63));
+        let decoded = decoder()
+            .decode_output(&hex(TRANSFER_INPUT), &hex(&output))
+            .unwrap()
             .unwrap();
-        decoded_input.map(|decoded_input| {
-            decoded_input
-                .into_iter()
-                .map(|value| DecodedSolValue::new(value, self.checksummed_addresses))
-                .collect()
-        })
+        assert!(matches!(decoded[0].val, Either4::A(true)));
+
+        let unknown = format!("0xdeadbeef{}", &TRANSFER_INPUT[10..]);
+        assert!(decoder()
+            .decode_output(&hex(&unknown), &hex(&output))
+            .unwrap()
+            .is_none());
+        assert!(decoder()
+            .decode_output(&hex(TRANSFER_INPUT), &hex("0x01"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_revert() {
+        let decoder = decoder();
+        let revert = |values: Vec<DynSolValue>, sig: &str| {
+            let error = AbiError::parse(sig).unwrap();
+            let data = error.abi_encode_input(&values).unwrap();
+            decoder
+                .decode_revert(&hex(&Data::from(data).encode_hex()))
+                .unwrap()
+                .unwrap()
+        };
+
+        let decoded = revert(
+            vec![DynSolValue::String("not owner".into())],
+            "Error(string)",
+        );
+        assert_eq!(decoded.name, "Error");
+        assert_eq!(decoded.message.as_deref(), Some("not owner"));
+
+        let decoded = revert(
+            vec![DynSolValue::Uint(alloy_primitives::U256::from(0x11), 256)],
+            "Panic(uint256)",
+        );
+        assert_eq!(
+            decoded.message.as_deref(),
+            Some("arithmetic overflow or underflow")
+        );
+
+        let decoded = revert(
+            vec![
+                DynSolValue::Uint(alloy_primitives::U256::from(1), 256),
+                DynSolValue::Uint(alloy_primitives::U256::from(2), 256),
+            ],
+            "InsufficientBalance(uint256,uint256)",
+        );
+        assert_eq!(decoded.signature, "InsufficientBalance(uint256,uint256)");
+        assert_eq!(decoded.params.len(), 2);
+        assert_eq!(decoded.message, None);
+
+        assert!(decoder.decode_revert(&hex("0x")).unwrap().is_none());
+        assert!(decoder.decode_revert(&hex("0xdeadbeef")).unwrap().is_none());
+        assert!(decoder.decode_revert(&hex("0x08c379a0")).is_err());
     }
 }
@@ -0,0 +1,248 @@
+use std::fmt;
+
+use napi::{bindgen_prelude::ToNapiValue, sys, Env, JsValue, Status};
+
+use crate::query::Query;
+
+/// Category of an error thrown by the client, available as `error.code`
+///
+/// Errors of client calls also carry `httpStatus` when the server responded, `retryAfter` in
+/// seconds for `RateLimited` errors when known and the `query` that failed.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// The query or one of the arguments is invalid
+    InvalidQuery,
+    /// The server rejected the request because of rate limits, see `retryAfter`
+    RateLimited,
+    /// The api token is missing or not valid
+    Unauthorized,
+    /// A request timed out
+    Timeout,
+    /// The server failed to process the request
+    ServerError,
+    /// Data could not be decoded or converted
+    DecodeError,
+    /// The call was aborted through a `CancellationHandle`
+    Aborted,
+    /// The chain was rolled back since the checkpoint a stream resumes from was saved
+    Rollback,
+    /// Any other error
+    Unknown,
+}
+
+/// Marks an error with a code, so it doesn't have to be guessed from the message
+#[derive(Debug)]
+struct Coded {
+    code: ErrorCode,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Coded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Coded {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+pub trait WithCode<T> {
+    /// Mark the error with the given code
+    fn with_code(self, code: ErrorCode) -> anyhow::Result<T>;
+}
+
+impl<T> WithCode<T> for anyhow::Result<T> {
+    fn with_code(self, code: ErrorCode) -> anyhow::Result<T> {
+        self.map_err(|error| anyhow::Error::new(Coded { code, error }))
+    }
+}
+
+/// Create an error with the given code and message
+pub fn coded<M>(code: ErrorCode, message: M) -> anyhow::Error
+where
+    M: fmt::Display + fmt::Debug + Send + Sync + 'static,
+{
+    anyhow::Error::new(Coded {
+        code,
+        error: anyhow::Error::msg(message),
+    })
+}
+
+/// Error thrown to JS as an `Error` with `code` and the structured fields below set on it
+pub struct HypersyncError {
+    pub code: ErrorCode,
+    /// Message including the chain of causes
+    pub message: String,
+    /// HTTP status of the failed request, if it got a response
+    pub http_status: Option<u16>,
+    /// Seconds to wait before retrying, set for `RateLimited` errors when known
+    pub retry_after_secs: Option<u64>,
+    /// Query of the failed call
+    pub query: Option<Query>,
+}
+
+impl From<anyhow::Error> for HypersyncError {
+    fn from(e: anyhow::Error) -> Self {
+        let code = e
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<Coded>())
+            .map(|coded| coded.code);
+        let http_status = e
+            .chain()
+            .find_map(|cause| parse_http_status(&cause.to_string()));
+        let message = format!("{:?}", e);
+
+        Self {
+            code: code.unwrap_or_else(|| guess_code(http_status, &message)),
+            message,
+            http_status,
+            retry_after_secs: None,
+            query: None,
+        }
+    }
+}
+
+/// Find the status code in errors like "http response status code 429 Too Many Requests"
+fn parse_http_status(msg: &str) -> Option<u16> {
+    let (_, rest) = msg.split_once("status code ")?;
+    rest.get(..3)?.parse().ok()
+}
+
+fn guess_code(http_status: Option<u16>, message: &str) -> ErrorCode {
+    match http_status {
+        Some(401 | 403) => return ErrorCode::Unauthorized,
+        Some(429) => return ErrorCode::RateLimited,
+        Some(408 | 504) => return ErrorCode::Timeout,
+        Some(400 | 404 | 413 | 422) => return ErrorCode::InvalidQuery,
+        Some(500..=599) => return ErrorCode::ServerError,
+        _ => (),
+    }
+
+    let message = message.to_lowercase();
+    if message.contains("rate limited") {
+        ErrorCode::RateLimited
+    } else if message.contains("timed out") || message.contains("timeout") {
+        ErrorCode::Timeout
+    } else {
+        ErrorCode::Unknown
+    }
+}
+
+/// Convert errors of sync calls into a JS error, like `ClientResult` does for async calls
+pub fn js_error(env: &Env) -> impl Fn(anyhow::Error) -> napi::Error + '_ {
+    move |e| HypersyncError::from(e).into_napi_error(env)
+}
+
+impl HypersyncError {
+    /// Build the JS error object and wrap it, so napi throws it as is
+    pub fn into_napi_error(self, env: &Env) -> napi::Error {
+        match self.into_js_error(env) {
+            Ok(error) => error,
+            Err(e) => e,
+        }
+    }
+
+    fn into_js_error(self, env: &Env) -> napi::Result<napi::Error> {
+        let mut error = env.create_error(napi::Error::new(Status::GenericFailure, self.message))?;
+        error.set("code", self.code)?;
+        if let Some(http_status) = self.http_status {
+            error.set("httpStatus", http_status)?;
+        }
+        if let Some(retry_after_secs) = self.retry_after_secs {
+            error.set("retryAfter", retry_after_secs as f64)?;
+        }
+        if let Some(query) = self.query {
+            error.set("query", query)?;
+        }
+        Ok(napi::Error::from(error.to_unknown()))
+    }
+}
+
+/// Result of an async call that rejects with a `HypersyncError`
+///
+/// Errors of async functions can only carry a napi status as `code`, so the error object is
+/// built here instead, when the result is converted on the JS thread.
+pub struct ClientResult<T>(pub Result<T, HypersyncError>);
+
+impl<T> From<Result<T, HypersyncError>> for ClientResult<T> {
+    fn from(res: Result<T, HypersyncError>) -> Self {
+        Self(res)
+    }
+}
+
+impl<T> From<anyhow::Result<T>> for ClientResult<T> {
+    fn from(res: anyhow::Result<T>) -> Self {
+        Self(res.map_err(HypersyncError::from))
+    }
+}
+
+impl<T> ClientResult<T> {
+    /// Convert the result of receiving from a stream into an item for `AsyncGenerator::next`,
+    /// so `for await` loops reject with a `HypersyncError` as well
+    pub fn stream_item(res: anyhow::Result<Option<T>>) -> napi::Result<Option<Self>> {
+        Ok(res.map_err(HypersyncError::from).transpose().map(Self))
+    }
+}
+
+impl<T: ToNapiValue> ToNapiValue for ClientResult<T> {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        match val.0 {
+            Ok(val) => T::to_napi_value(env, val),
+            Err(e) => Err(e.into_napi_error(&Env::from_raw(env))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    fn classify(e: anyhow::Error) -> (ErrorCode, Option<u16>) {
+        let e = HypersyncError::from(e);
+        (e.code, e.http_status)
+    }
+
+    #[test]
+    fn test_classify_http_status() {
+        let e = anyhow!("http response status code 401 Unauthorized, err body: invalid token")
+            .context("run inner query");
+        assert_eq!(classify(e), (ErrorCode::Unauthorized, Some(401)));
+
+        let e = anyhow!("http response status code 400 Bad Request, err body: bad address");
+        assert_eq!(classify(e), (ErrorCode::InvalidQuery, Some(400)));
+
+        let e = anyhow!("http response status code 503 Service Unavailable, err body: ");
+        assert_eq!(classify(e), (ErrorCode::ServerError, Some(503)));
+    }
+
+    #[test]
+    fn test_classify_message() {
+        let e = anyhow!("rate limited by server. To increase your rate limits, upgrade your plan");
+        assert_eq!(classify(e), (ErrorCode::RateLimited, None));
+
+        let e = anyhow!("operation timed out").context("execute http req");
+        assert_eq!(classify(e), (ErrorCode::Timeout, None));
+
+        let e = anyhow!("something else");
+        assert_eq!(classify(e), (ErrorCode::Unknown, None));
+    }
+
+    #[test]
+    fn test_coded_keeps_message() {
+        let e = coded(ErrorCode::InvalidQuery, "invalid address")
+            .context("parse query")
+            .context("outer");
+        let e = HypersyncError::from(e);
+
+        assert_eq!(e.code, ErrorCode::InvalidQuery);
+        assert!(e.message.starts_with("outer"));
+        assert!(e.message.contains("parse query"));
+        assert!(e.message.contains("invalid address"));
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use arrow::array::RecordBatch;
+use flate2::write::GzEncoder;
+use hypersync_client::{net_types, Client};
+
+/// Compression of the files written by `collectCsv` and `collectNdjson`
+#[napi(string_enum)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Options for `collectCsv` and `collectNdjson`
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct FlatFileOptions {
+    /// Default: None
+    pub compression: Option<Compression>,
+}
+
+/// Format of the files written by `collect_flat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Ndjson,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Stream the query and write one file per table into the directory at `path`, e.g.
+/// `logs.csv.gz`. Files are only created for tables that have data.
+pub async fn collect_flat(
+    client: &Client,
+    path: &str,
+    query: net_types::Query,
+    config: hypersync_client::StreamConfig,
+    format: Format,
+    compression: Compression,
+) -> Result<()> {
+    let dir = PathBuf::from(path);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .context("create output dir")?;
+
+    let mut rx = client
+        .clone()
+        .stream_arrow(query, config)
+        .await
+        .context("start stream")?;
+
+    let mut tables = Tables::new(dir, format, compression);
+    while let Some(resp) = rx.recv().await {
+        let resp = resp.context("get query response")?;
+        log::trace!("got data up to block {}", resp.next_block);
+
+        // file writes block, so they are moved off the async runtime
+        tables = tokio::task::spawn_blocking(move || {
+            tables.write(resp.data)?;
+            Ok::<_, anyhow::Error>(tables)
+        })
+        .await
+        .context("join writer task")??;
+    }
+
+    tokio::task::spawn_blocking(move || tables.finish())
+        .await
+        .context("join writer task")?
+}
+
+struct Tables {
+    dir: PathBuf,
+    format: Format,
+    compression: Compression,
+    blocks: Option<TableWriter>,
+    transactions: Option<TableWriter>,
+    logs: Option<TableWriter>,
+    traces: Option<TableWriter>,
+    decoded_logs: Option<TableWriter>,
+}
+
+impl Tables {
+    fn new(dir: PathBuf, format: Format, compression: Compression) -> Self {
+        Self {
+            dir,
+            format,
+            compression,
+            blocks: None,
+            transactions: None,
+            logs: None,
+            traces: None,
+            decoded_logs: None,
+        }
+    }
+
+    fn write(&mut self, data: hypersync_client::ArrowResponseData) -> Result<()> {
+        for (writer, name, batches) in [
+            (&mut self.blocks, "blocks", data.blocks),
+            (&mut self.transactions, "transactions", data.transactions),
+            (&mut self.logs, "logs", data.logs),
+            (&mut self.traces, "traces", data.traces),
+            (&mut self.decoded_logs, "decoded_logs", data.decoded_logs),
+        ] {
+            for batch in batches.iter().filter(|batch| batch.num_rows() > 0) {
+                let writer = match writer {
+                    Some(writer) => writer,
+                    None => writer.insert(
+                        TableWriter::create(&self.dir, name, self.format, self.compression)
+                            .with_context(|| format!("create {} file", name))?,
+                    ),
+                };
+                writer
+                    .write(batch)
+                    .with_context(|| format!("write {}", name))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        for (writer, name) in [
+            (self.blocks, "blocks"),
+            (self.transactions, "transactions"),
+            (self.logs, "logs"),
+            (self.traces, "traces"),
+            (self.decoded_logs, "decoded_logs"),
+        ] {
+            if let Some(writer) = writer {
+                writer
+                    .finish()
+                    .with_context(|| format!("finish {} file", name))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+enum TableWriter {
+    Csv(Box<arrow::csv::Writer<Sink>>),
+    Ndjson(Box<arrow::json::LineDelimitedWriter<Sink>>),
+}
+
+impl TableWriter {
+    fn create(dir: &Path, name: &str, format: Format, compression: Compression) -> Result<Self> {
+        let mut file_name = format!("{}.{}", name, format.extension());
+        match compression {
+            Compression::None => (),
+            Compression::Gzip => file_name.push_str(".gz"),
+            Compression::Zstd => file_name.push_str(".zst"),
+        }
+        let file =
+            File::create(dir.join(&file_name)).with_context(|| format!("create {}", file_name))?;
+        let sink = Sink::new(BufWriter::new(file), compression)?;
+
+        Ok(match format {
+            Format::Csv => TableWriter::Csv(Box::new(arrow::csv::Writer::new(sink))),
+            Format::Ndjson => {
+                TableWriter::Ndjson(Box::new(arrow::json::LineDelimitedWriter::new(sink)))
+            }
+        })
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match self {
+            TableWriter::Csv(writer) => writer.write(batch),
+            TableWriter::Ndjson(writer) => writer.write(batch),
+        }
+        .context("encode batch")
+    }
+
+    fn finish(self) -> Result<()> {
+        let sink = match self {
+            TableWriter::Csv(writer) => writer.into_inner(),
+            TableWriter::Ndjson(mut writer) => {
+                writer.finish().context("finish ndjson")?;
+                writer.into_inner()
+            }
+        };
+        sink.finish()
+    }
+}
+
+/// File with optional compression, compressed streams need `finish` to write their trailer
+enum Sink {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Sink {
+    fn new(file: BufWriter<File>, compression: Compression) -> Result<Self> {
+        Ok(match compression {
+            Compression::None => Sink::Plain(file),
+            Compression::Gzip => Sink::Gzip(GzEncoder::new(file, flate2::Compression::default())),
+            Compression::Zstd => {
+                Sink::Zstd(zstd::Encoder::new(file, 0).context("create zstd encoder")?)
+            }
+        })
+    }
+
+    fn finish(self) -> Result<()> {
+        let mut file = match self {
+            Sink::Plain(file) => file,
+            Sink::Gzip(encoder) => encoder.finish().context("finish gzip stream")?,
+            Sink::Zstd(encoder) => encoder.finish().context("finish zstd stream")?,
+        };
+        file.flush().context("flush file")
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Sink::Plain(w) => w.write(buf),
+            Sink::Gzip(w) => w.write(buf),
+            Sink::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Sink::Plain(w) => w.flush(),
+            Sink::Gzip(w) => w.flush(),
+            Sink::Zstd(w) => w.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, sync::Arc};
+
+    use arrow::{
+        array::{BinaryArray, UInt64Array},
+        datatypes::{DataType, Field, Schema},
+    };
+
+    use super::*;
+
+    fn logs(numbers: Vec<u64>) -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("block_number", DataType::UInt64, false),
+            Field::new("data", DataType::Binary, true),
+        ]);
+        let data = numbers
+            .iter()
+            .map(|n| Some(vec![*n as u8, 0xff]))
+            .collect::<Vec<_>>();
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(UInt64Array::from(numbers)),
+                Arc::new(BinaryArray::from_iter(data)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn write_tables(dir: &Path, format: Format, compression: Compression) {
+        let mut tables = Tables::new(dir.to_owned(), format, compression);
+        for numbers in [vec![1, 2], vec![3]] {
+            tables
+                .write(hypersync_client::ArrowResponseData {
+                    logs: vec![logs(numbers)],
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        tables.finish().unwrap();
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("flat_out_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const CSV: &str = "block_number,data\n1,01ff\n2,02ff\n3,03ff\n";
+
+    #[test]
+    fn test_write_csv() {
+        let dir = temp_dir("csv");
+        write_tables(&dir, Format::Csv, Compression::None);
+
+        assert_eq!(std::fs::read_to_string(dir.join("logs.csv")).unwrap(), CSV);
+        // tables without data get no file
+        assert!(!dir.join("blocks.csv").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_compressed() {
+        let dir = temp_dir("compressed");
+        write_tables(&dir, Format::Csv, Compression::Gzip);
+        write_tables(&dir, Format::Ndjson, Compression::Zstd);
+
+        let mut csv = String::new();
+        flate2::read::GzDecoder::new(File::open(dir.join("logs.csv.gz")).unwrap())
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert_eq!(csv, CSV);
+
+        let ndjson = zstd::decode_all(File::open(dir.join("logs.ndjson.zst")).unwrap()).unwrap();
+        assert_eq!(
+            String::from_utf8(ndjson).unwrap(),
+            "{\"block_number\":1,\"data\":\"01ff\"}\n\
+             {\"block_number\":2,\"data\":\"02ff\"}\n\
+             {\"block_number\":3,\"data\":\"03ff\"}\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,769 @@
+use alloy_primitives::U256;
+use anyhow::{anyhow, Context, Result};
+use arrow::{
+    array::{Array, AsArray, RecordBatch},
+    datatypes::{DataType, UInt64Type, UInt8Type},
+};
+use hypersync_client::{
+    arrow_reader::{BlockReader, TraceReader, TransactionReader},
+    format::{FixedSizeData, Hex, Quantity},
+};
+use napi::{
+    bindgen_prelude::{Either, Object, ToNapiValue},
+    sys, Env,
+};
+
+use crate::{
+    error::{js_error, ErrorCode, WithCode},
+    types::{
+        fraction, AccessList, Authorization, Encoding, Numeric, NumericKind, RollbackGuard,
+        StringOrBuffer, Withdrawal,
+    },
+};
+
+/// Tables of a lazy response
+#[napi(object, object_from_js = false)]
+pub struct LazyResponseData {
+    pub blocks: LazyTable,
+    pub transactions: LazyTable,
+    pub logs: LazyTable,
+    pub traces: LazyTable,
+}
+
+/// Response from a blockchain query where rows are kept in Arrow batches and only converted
+/// to JS values when they are accessed
+#[napi(object, object_from_js = false)]
+pub struct LazyQueryResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Next block to query for, the responses are paginated so,
+    ///  the caller should continue the query from this block if they
+    ///  didn't get responses up to the to_block they specified in the Query.
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the query.
+    pub total_execution_time: i64,
+    /// Response data
+    pub data: LazyResponseData,
+    /// Rollback guard, supposed to be used to detect rollbacks
+    pub rollback_guard: Option<RollbackGuard>,
+}
+
+pub fn convert_lazy_response(
+    resp: hypersync_client::ArrowResponse,
+    encoding: Encoding,
+) -> Result<LazyQueryResponse> {
+    let data = resp.data;
+    Ok(LazyQueryResponse {
+        archive_height: resp
+            .archive_height
+            .map(|h| h.try_into())
+            .transpose()
+            .context("convert height")?,
+        next_block: resp.next_block.try_into().context("convert next_block")?,
+        total_execution_time: resp
+            .total_execution_time
+            .try_into()
+            .context("convert total_execution_time")?,
+        data: LazyResponseData {
+            blocks: LazyTable::new(Table::Blocks, data.blocks, encoding),
+            transactions: LazyTable::new(Table::Transactions, data.transactions, encoding),
+            logs: LazyTable::new(Table::Logs, data.logs, encoding),
+            traces: LazyTable::new(Table::Traces, data.traces, encoding),
+        },
+        rollback_guard: resp
+            .rollback_guard
+            .map(RollbackGuard::try_from)
+            .transpose()
+            .context("convert rollback guard")?,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Table {
+    Blocks,
+    Transactions,
+    Logs,
+    Traces,
+}
+
+/// How the values of a column are converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    /// Big endian quantity, converted according to `numericMode`
+    Quantity(NumericKind),
+    /// Quantity that is always a hex string, like the signature fields
+    HexQuantity,
+    Address,
+    /// Lists that are stored serialized, read with the row readers of the client
+    Nested,
+    /// `topic0` to `topic3` of logs, read into a single `topics` array
+    Topics,
+    /// Converted by the arrow type of the column
+    Plain,
+}
+
+impl ColumnKind {
+    /// Kind of a column, kept in line with the `from_simple` conversions of the types by
+    /// `test_columns_match_from_simple`
+    fn of(table: Table, name: &str) -> Self {
+        use NumericKind::{BigInt, Hex, Number};
+        match (table, name) {
+            (Table::Blocks, "timestamp") => Self::Quantity(Number),
+            (Table::Blocks, "send_count") => Self::Quantity(Hex),
+            (
+                Table::Blocks,
+                "nonce" | "difficulty" | "total_difficulty" | "size" | "gas_limit" | "gas_used"
+                | "base_fee_per_gas" | "blob_gas_used" | "excess_blob_gas",
+            ) => Self::Quantity(BigInt),
+            (Table::Blocks, "miner") => Self::Address,
+            (Table::Blocks, "uncles" | "withdrawals") => Self::Nested,
+            (Table::Transactions, "chain_id" | "l1_block_number") => Self::Quantity(Number),
+            (
+                Table::Transactions,
+                "gas"
+                | "gas_price"
+                | "nonce"
+                | "value"
+                | "max_priority_fee_per_gas"
+                | "max_fee_per_gas"
+                | "max_fee_per_blob_gas"
+                | "cumulative_gas_used"
+                | "effective_gas_price"
+                | "gas_used"
+                | "l1_fee"
+                | "l1_gas_price"
+                | "l1_gas_used"
+                | "gas_used_for_l1"
+                | "blob_gas_price"
+                | "blob_gas_used"
+                | "deposit_nonce"
+                | "deposit_receipt_version"
+                | "l1_base_fee_scalar"
+                | "l1_blob_base_fee"
+                | "l1_blob_base_fee_scalar"
+                | "mint",
+            ) => Self::Quantity(BigInt),
+            (Table::Transactions, "v" | "r" | "s" | "y_parity") => Self::HexQuantity,
+            (Table::Transactions, "from" | "to" | "contract_address") => Self::Address,
+            (
+                Table::Transactions,
+                "access_list" | "authorization_list" | "blob_versioned_hashes" | "l1_fee_scalar",
+            ) => Self::Nested,
+            (Table::Logs, "address") => Self::Address,
+            (Table::Logs, "topic0" | "topic1" | "topic2" | "topic3") => Self::Topics,
+            (Table::Traces, "gas" | "value" | "gas_used" | "balance") => Self::Quantity(BigInt),
+            (
+                Table::Traces,
+                "from" | "to" | "author" | "address" | "action_address" | "refund_address",
+            ) => Self::Address,
+            (Table::Traces, "trace_address") => Self::Nested,
+            _ => Self::Plain,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    /// Name of the property on JS rows, e.g. `blockNumber`
+    js_name: String,
+    kind: ColumnKind,
+}
+
+impl Column {
+    fn new(table: Table, name: &str) -> Self {
+        let kind = ColumnKind::of(table, name);
+        let name = if kind == ColumnKind::Topics {
+            "topics"
+        } else {
+            name
+        };
+        Self {
+            name: name.to_owned(),
+            js_name: js_name(name),
+            kind,
+        }
+    }
+}
+
+/// Camel case name of a column, the way napi names the fields of the response types
+fn js_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Converted value of a single cell
+#[derive(Debug)]
+pub enum Value {
+    Bool(bool),
+    Numeric(Numeric),
+    Fraction(Either<f64, String>),
+    String(String),
+    Binary(StringOrBuffer),
+    Binaries(Vec<StringOrBuffer>),
+    Topics(Vec<Option<StringOrBuffer>>),
+    Numbers(Vec<i64>),
+    Withdrawals(Vec<Withdrawal>),
+    AccessList(Vec<AccessList>),
+    Authorizations(Vec<Authorization>),
+}
+
+impl ToNapiValue for Value {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        match val {
+            Self::Bool(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Numeric(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Fraction(v) => ToNapiValue::to_napi_value(env, v),
+            Self::String(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Binary(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Binaries(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Topics(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Numbers(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Withdrawals(v) => ToNapiValue::to_napi_value(env, v),
+            Self::AccessList(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Authorizations(v) => ToNapiValue::to_napi_value(env, v),
+        }
+    }
+}
+
+/// Rows of one table of a lazy response.
+///
+/// Rows stay in Arrow batches on the Rust side and are only converted to JS values when they
+/// are accessed. Columns that are null in every row are skipped entirely.
+/// Values are represented the same way as in `collect`, so `binaryFields`, `numericMode` and
+/// checksumming apply. Rows have a property per column, except for the topics of logs which are
+/// in a `topics` array like in `Log`, so log rows can be passed to `Decoder.decodeLogs`.
+#[napi]
+pub struct LazyTable {
+    table: Table,
+    batches: Vec<RecordBatch>,
+    /// Index of the first row of each batch
+    offsets: Vec<usize>,
+    /// Columns that have a value in at least one row
+    columns: Vec<Column>,
+    len: usize,
+    encoding: Encoding,
+}
+
+impl LazyTable {
+    fn new(table: Table, batches: Vec<RecordBatch>, encoding: Encoding) -> Self {
+        let mut offsets = Vec::with_capacity(batches.len());
+        let mut len = 0;
+        for batch in batches.iter() {
+            offsets.push(len);
+            len += batch.num_rows();
+        }
+
+        let mut columns: Vec<Column> = match batches.first() {
+            Some(first) => first
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| Column::new(table, field.name()))
+                .filter(|column| {
+                    // logs always have topics, null if they weren't selected
+                    column.kind == ColumnKind::Topics
+                        || batches.iter().any(|batch| {
+                            batch
+                                .column_by_name(&column.name)
+                                .is_some_and(|col| col.null_count() < col.len())
+                        })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        columns.dedup_by(|a, b| a.name == b.name);
+
+        Self {
+            table,
+            batches,
+            offsets,
+            columns,
+            len,
+            encoding,
+        }
+    }
+
+    /// Batch and row within the batch of the row at `index`
+    fn locate(&self, index: u32) -> Option<(&RecordBatch, usize)> {
+        let index = usize::try_from(index).ok()?;
+        if index >= self.len {
+            return None;
+        }
+        let batch = self.offsets.partition_point(|&offset| offset <= index) - 1;
+        Some((&self.batches[batch], index - self.offsets[batch]))
+    }
+
+    fn read_row(&self, index: u32) -> Result<Option<Vec<(&str, Value)>>> {
+        let Some((batch, row)) = self.locate(index) else {
+            return Ok(None);
+        };
+
+        let mut values = Vec::with_capacity(self.columns.len());
+        for column in self.columns.iter() {
+            let value = self
+                .read_value(batch, row, column)
+                .with_context(|| format!("read column {}", column.name))?;
+            if let Some(value) = value {
+                values.push((column.js_name.as_str(), value));
+            }
+        }
+
+        Ok(Some(values))
+    }
+
+    fn read_value(
+        &self,
+        batch: &RecordBatch,
+        row: usize,
+        column: &Column,
+    ) -> Result<Option<Value>> {
+        if column.kind == ColumnKind::Topics {
+            return self.read_topics(batch, row).map(Some);
+        }
+        let Some(array) = batch.column_by_name(&column.name) else {
+            return Ok(None);
+        };
+        if array.is_null(row) {
+            return Ok(None);
+        }
+
+        let encoding = self.encoding;
+        let binary = || -> Result<&[u8]> {
+            let array = array
+                .as_binary_opt::<i32>()
+                .context("expected a binary column")?;
+            Ok(array.value(row))
+        };
+
+        let value = match column.kind {
+            ColumnKind::Quantity(kind) => {
+                let v = U256::try_from_be_slice(binary()?).context("quantity exceeds 256 bits")?;
+                Value::Numeric(Numeric::new(v, kind, encoding.numeric)?)
+            }
+            ColumnKind::HexQuantity => Value::String(Quantity::from(binary()?).encode_hex()),
+            ColumnKind::Address => {
+                let address = FixedSizeData::<20>::try_from(binary()?).context("read address")?;
+                Value::Binary(StringOrBuffer::address(&address, encoding))
+            }
+            ColumnKind::Nested => return self.read_nested(batch, row, &column.name),
+            ColumnKind::Topics => unreachable!("topics are read by read_topics"),
+            ColumnKind::Plain => match array.data_type() {
+                DataType::Boolean => Value::Bool(array.as_boolean().value(row)),
+                DataType::UInt8 => {
+                    let v = array.as_primitive::<UInt8Type>().value(row);
+                    Value::Numeric(Numeric::new(
+                        U256::from(v),
+                        NumericKind::Number,
+                        encoding.numeric,
+                    )?)
+                }
+                DataType::UInt64 => {
+                    let v = array.as_primitive::<UInt64Type>().value(row);
+                    Value::Numeric(Numeric::new(
+                        U256::from(v),
+                        NumericKind::Number,
+                        encoding.numeric,
+                    )?)
+                }
+                DataType::Utf8 => Value::String(array.as_string::<i32>().value(row).to_owned()),
+                DataType::Binary => Value::Binary(StringOrBuffer::from_slice(binary()?, encoding)),
+                data_type => return Err(anyhow!("unsupported column type {data_type}")),
+            },
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Topics of a log, always four of them with nulls for missing topics like in `collect`
+    fn read_topics(&self, batch: &RecordBatch, row: usize) -> Result<Value> {
+        let topics = ["topic0", "topic1", "topic2", "topic3"]
+            .into_iter()
+            .map(|name| {
+                let Some(array) = batch.column_by_name(name) else {
+                    return Ok(None);
+                };
+                if array.is_null(row) {
+                    return Ok(None);
+                }
+                let bytes = array
+                    .as_binary_opt::<i32>()
+                    .context("expected a binary column")?
+                    .value(row);
+                let topic =
+                    FixedSizeData::<32>::try_from(bytes).with_context(|| format!("read {name}"))?;
+                Ok(Some(StringOrBuffer::new(&topic, self.encoding)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Value::Topics(topics))
+    }
+
+    fn read_nested(&self, batch: &RecordBatch, row: usize, name: &str) -> Result<Option<Value>> {
+        let encoding = self.encoding;
+        let hashes = |hashes: Vec<FixedSizeData<32>>| {
+            Value::Binaries(
+                hashes
+                    .iter()
+                    .map(|h| StringOrBuffer::new(h, encoding))
+                    .collect(),
+            )
+        };
+
+        let value = match (self.table, name) {
+            (Table::Blocks, "uncles") => BlockReader::new(batch, row)?.uncles()?.map(hashes),
+            (Table::Blocks, "withdrawals") => BlockReader::new(batch, row)?
+                .withdrawals()?
+                .map(|w| w.iter().map(|w| Withdrawal::new(w, encoding)).collect())
+                .transpose()?
+                .map(Value::Withdrawals),
+            (Table::Transactions, "access_list") => TransactionReader::new(batch, row)?
+                .access_list()?
+                .map(|arr| {
+                    Value::AccessList(arr.iter().map(|a| AccessList::new(a, encoding)).collect())
+                }),
+            (Table::Transactions, "authorization_list") => TransactionReader::new(batch, row)?
+                .authorization_list()?
+                .map(|al| al.iter().map(|a| Authorization::new(a, encoding)).collect())
+                .transpose()?
+                .map(Value::Authorizations),
+            (Table::Transactions, "blob_versioned_hashes") => TransactionReader::new(batch, row)?
+                .blob_versioned_hashes()?
+                .map(hashes),
+            (Table::Transactions, "l1_fee_scalar") => TransactionReader::new(batch, row)?
+                .l1_fee_scalar()?
+                .map(|v| Value::Fraction(fraction(v, encoding.numeric))),
+            (Table::Traces, "trace_address") => TraceReader::new(batch, row)?
+                .trace_address()?
+                .map(|arr| arr.into_iter().map(i64::try_from).collect())
+                .transpose()?
+                .map(Value::Numbers),
+            _ => return Err(anyhow!("unknown nested column {name}")),
+        };
+
+        Ok(value)
+    }
+}
+
+#[napi]
+impl LazyTable {
+    /// Number of rows
+    #[napi(getter)]
+    pub fn length(&self) -> u32 {
+        self.len.try_into().unwrap_or(u32::MAX)
+    }
+
+    /// Names of the properties rows can have, columns that are null in every row are skipped
+    #[napi(getter)]
+    pub fn columns(&self) -> Vec<String> {
+        self.columns.iter().map(|c| c.js_name.clone()).collect()
+    }
+
+    /// Row at `index` as an object, null if the index is out of range.
+    ///
+    /// Only properties of columns that have a value in this row are set.
+    #[napi(ts_return_type = "Record<string, unknown> | null")]
+    pub fn get(&self, env: Env, index: u32) -> napi::Result<Option<Object<'_>>> {
+        let Some(values) = self
+            .read_row(index)
+            .with_code(ErrorCode::DecodeError)
+            .map_err(js_error(&env))?
+        else {
+            return Ok(None);
+        };
+
+        let mut obj = Object::new(&env)?;
+        for (name, value) in values {
+            obj.set(name, value)?;
+        }
+        Ok(Some(obj))
+    }
+
+    /// Value of `column` in the row at `index`, e.g. `getValue(0, "blockNumber")`.
+    ///
+    /// Null if the index is out of range, the value is null or the column is not in `columns`.
+    #[napi(ts_return_type = "unknown")]
+    pub fn get_value(&self, env: Env, index: u32, column: String) -> napi::Result<Option<Value>> {
+        let Some((batch, row)) = self.locate(index) else {
+            return Ok(None);
+        };
+        let Some(column) = self.columns.iter().find(|c| c.js_name == column) else {
+            return Ok(None);
+        };
+
+        self.read_value(batch, row, column)
+            .with_context(|| format!("read column {}", column.name))
+            .with_code(ErrorCode::DecodeError)
+            .map_err(js_error(&env))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{ArrayRef, BinaryArray, BooleanArray, StringArray, UInt64Array, UInt8Array},
+        datatypes::{Field, Schema},
+    };
+    use hypersync_client::{arrow_reader::LogReader, schema, simple_types};
+
+    use super::*;
+    use crate::{
+        config::NumericMode,
+        types::{Block, Log, Trace, Transaction},
+    };
+
+    fn blocks(numbers: Vec<u64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("number", DataType::UInt64, false),
+            Field::new("miner", DataType::Binary, false),
+            Field::new("gas_used", DataType::Binary, false),
+            Field::new("base_fee_per_gas", DataType::Binary, true),
+        ]));
+        let len = numbers.len();
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(UInt64Array::from(numbers.clone())),
+                Arc::new(BinaryArray::from_iter_values(
+                    numbers.iter().map(|_| [0xab; 20]),
+                )),
+                Arc::new(BinaryArray::from_iter_values(
+                    numbers.iter().map(|n| (n * 1000).to_be_bytes()),
+                )),
+                Arc::new(BinaryArray::from_iter(std::iter::repeat_n(
+                    None::<&[u8]>,
+                    len,
+                ))),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn value(table: &LazyTable, index: u32, column: &str) -> Option<Value> {
+        let (batch, row) = table.locate(index)?;
+        let column = table.columns.iter().find(|c| c.js_name == column)?;
+        table.read_value(batch, row, column).unwrap()
+    }
+
+    #[test]
+    fn test_lazy_table() {
+        let table = LazyTable::new(
+            Table::Blocks,
+            vec![blocks(vec![1, 2]), blocks(vec![3])],
+            Encoding::default(),
+        );
+
+        assert_eq!(table.length(), 3);
+        // null-only columns are skipped
+        assert_eq!(table.columns(), vec!["number", "miner", "gasUsed"]);
+        assert!(table.locate(3).is_none());
+
+        let Some(Value::Numeric(number)) = value(&table, 2, "number") else {
+            panic!("expected a number");
+        };
+        assert_eq!(number, Numeric::Number(3));
+        let Some(Value::Numeric(gas_used)) = value(&table, 1, "gasUsed") else {
+            panic!("expected a quantity");
+        };
+        assert_eq!(gas_used, Numeric::BigInt(2000u64.into()));
+        assert!(value(&table, 0, "baseFeePerGas").is_none());
+
+        let row = table.read_row(0).unwrap().unwrap();
+        let names = row.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["number", "miner", "gasUsed"]);
+        assert!(table.read_row(3).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_lazy_table_encoding() {
+        let encoding = Encoding {
+            checksum_addresses: true,
+            numeric: Some(NumericMode::DecimalString),
+            ..Default::default()
+        };
+        let table = LazyTable::new(Table::Blocks, vec![blocks(vec![1])], encoding);
+
+        let Some(Value::Numeric(gas_used)) = value(&table, 0, "gasUsed") else {
+            panic!("expected a quantity");
+        };
+        assert_eq!(gas_used, Numeric::String("1000".into()));
+        let Some(Value::Binary(miner)) = value(&table, 0, "miner") else {
+            panic!("expected an address");
+        };
+        assert_eq!(
+            miner.as_str(),
+            Some("0xABaBaBaBABabABabAbAbABAbABabababaBaBABaB")
+        );
+    }
+
+    /// Batch with a single row and only the column of `field`, as if only it was selected
+    fn single_value_batch(field: &Field, value: &[u8]) -> RecordBatch {
+        let column: ArrayRef = match field.data_type() {
+            DataType::Boolean => Arc::new(BooleanArray::from(vec![true])),
+            DataType::UInt8 => Arc::new(UInt8Array::from(vec![1])),
+            DataType::UInt64 => Arc::new(UInt64Array::from(vec![7])),
+            DataType::Utf8 => Arc::new(StringArray::from(vec!["call"])),
+            DataType::Binary => Arc::new(BinaryArray::from_iter_values([value])),
+            data_type => panic!("unexpected column type {data_type}"),
+        };
+        let schema = Arc::new(Schema::new(vec![field.clone()]));
+        RecordBatch::try_new(schema, vec![column]).unwrap()
+    }
+
+    /// First row of `batch` converted the way `collect` converts it
+    fn eager_row(table: Table, batch: &RecordBatch, encoding: Encoding) -> Result<String> {
+        Ok(match table {
+            Table::Blocks => {
+                let block = simple_types::Block::try_from(BlockReader::new(batch, 0)?)?;
+                format!("{:?}", Block::from_simple(&block, encoding)?)
+            }
+            Table::Transactions => {
+                let tx = simple_types::Transaction::try_from(TransactionReader::new(batch, 0)?)?;
+                format!("{:?}", Transaction::from_simple(&tx, encoding)?)
+            }
+            Table::Logs => {
+                let log = simple_types::Log::try_from(LogReader::new(batch, 0)?)?;
+                format!("{:?}", Log::from_simple(&log, encoding)?)
+            }
+            Table::Traces => {
+                let trace = simple_types::Trace::try_from(TraceReader::new(batch, 0)?)?;
+                format!("{:?}", Trace::from_simple(&trace, encoding)?)
+            }
+        })
+    }
+
+    /// Debug output of the value in a cell, the way it shows up in the fields of the types
+    fn debug_inner(value: &Value) -> String {
+        match value {
+            Value::Bool(v) => format!("{v:?}"),
+            Value::Numeric(v) => format!("{v:?}"),
+            Value::Fraction(v) => format!("{v:?}"),
+            Value::String(v) => format!("{v:?}"),
+            Value::Binary(v) => format!("{v:?}"),
+            Value::Binaries(v) => format!("{v:?}"),
+            Value::Topics(v) => format!("{v:?}"),
+            Value::Numbers(v) => format!("{v:?}"),
+            Value::Withdrawals(v) => format!("{v:?}"),
+            Value::AccessList(v) => format!("{v:?}"),
+            Value::Authorizations(v) => format!("{v:?}"),
+        }
+    }
+
+    #[test]
+    fn test_columns_match_from_simple() {
+        // checksumming and the default numeric kinds tell the column kinds apart
+        let encoding = Encoding {
+            checksum_addresses: true,
+            ..Default::default()
+        };
+        // binary columns get the first of these that the eager conversion accepts, e.g. a
+        // quantity, an address, a hash, the l1 fee scalar or an empty serialized list
+        let samples: [&[u8]; 5] = [&[1, 2], &[0xab; 20], &[0xab; 32], b"0.5", &[0; 8]];
+
+        for (table, schema) in [
+            (Table::Blocks, schema::block_header()),
+            (Table::Transactions, schema::transaction()),
+            (Table::Logs, schema::log()),
+            (Table::Traces, schema::trace()),
+        ] {
+            for field in schema.fields() {
+                let name = field.name();
+                let (batch, eager) = samples
+                    .iter()
+                    .find_map(|sample| {
+                        let batch = single_value_batch(field, sample);
+                        let eager = eager_row(table, &batch, encoding).ok()?;
+                        Some((batch, eager))
+                    })
+                    .unwrap_or_else(|| panic!("no sample value for {table:?} {name}"));
+
+                let lazy = LazyTable::new(table, vec![batch.clone()], encoding);
+                let column = Column::new(table, name);
+                assert!(lazy.columns.iter().any(|c| c.name == column.name));
+                let value = lazy
+                    .read_value(&batch, 0, &column)
+                    .unwrap()
+                    .unwrap_or_else(|| panic!("no value for {table:?} {name}"));
+
+                let expected = match column.kind {
+                    ColumnKind::Topics => format!(" topics: {}", debug_inner(&value)),
+                    _ => {
+                        let field = if name == "type" { "type_" } else { name };
+                        format!(" {field}: Some({})", debug_inner(&value))
+                    }
+                };
+                assert!(
+                    eager.contains(&expected),
+                    "{table:?} {name}: `{expected}` not in {eager}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_log_topics() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("address", DataType::Binary, false),
+            Field::new("topic0", DataType::Binary, true),
+            Field::new("topic1", DataType::Binary, true),
+            Field::new("topic2", DataType::Binary, true),
+            Field::new("topic3", DataType::Binary, true),
+        ]));
+        let null = || {
+            Arc::new(BinaryArray::from_iter(std::iter::repeat_n(
+                None::<&[u8]>,
+                1,
+            )))
+        };
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(BinaryArray::from_iter_values([[0xab; 20]])),
+                Arc::new(BinaryArray::from_iter_values([[0x11; 32]])),
+                Arc::new(BinaryArray::from_iter_values([[0x22; 32]])),
+                null(),
+                null(),
+            ],
+        )
+        .unwrap();
+        let table = LazyTable::new(Table::Logs, vec![batch], Encoding::default());
+
+        assert_eq!(table.columns(), vec!["address", "topics"]);
+        let Some(Value::Topics(topics)) = value(&table, 0, "topics") else {
+            panic!("expected topics");
+        };
+        let topics = topics
+            .iter()
+            .map(|t| t.as_ref().and_then(|t| t.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            topics,
+            vec![
+                Some(format!("0x{}", "11".repeat(32)).as_str()),
+                Some(format!("0x{}", "22".repeat(32)).as_str()),
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_js_name() {
+        assert_eq!(js_name("block_number"), "blockNumber");
+        assert_eq!(js_name("sha3_uncles"), "sha3Uncles");
+        assert_eq!(js_name("l1_block_number"), "l1BlockNumber");
+        assert_eq!(js_name("topic0"), "topic0");
+    }
+}
@@ -1,22 +1,57 @@
 #[macro_use]
 extern crate napi_derive;
 
-use std::sync::Once;
+use std::sync::{Arc, Once};
 
 use anyhow::{Context, Result};
-use napi::bindgen_prelude::Either3;
+use napi::{
+    bindgen_prelude::{AsyncGenerator, Either, Either3},
+    Env,
+};
 use tokio::sync::mpsc;
+use tokio_util::sync::{CancellationToken, DropGuard};
 
+mod abi;
+mod arrow_ipc;
+mod cancel;
+mod checkpoint;
+mod chunked;
 mod config;
 mod decode;
 mod decode_call;
+mod error;
+mod flat_out;
+mod lazy;
+mod live;
+mod lookup;
 pub mod preset_query;
 mod query;
+mod query_builder;
+pub mod query_codec;
+mod reorg;
+mod sqlite_out;
+mod timestamp;
 mod types;
+pub mod validate;
 
+use arrow_ipc::{convert_arrow_response, ArrowResponse};
+use cancel::{close_on_cancel, recv_cancellable, run_cancellable, CancellationHandle};
+use checkpoint::{resume_query, Checkpoint, CheckpointStore, CheckpointWriter};
+use chunked::{Chunked, PendingRows};
 use config::{ClientConfig, StreamConfig};
-use query::Query;
-use types::{Block, Event, Log, RollbackGuard, Trace, Transaction};
+use decode::event_signature_log_fields;
+use error::{js_error, ClientResult, ErrorCode, HypersyncError, WithCode};
+use flat_out::{collect_flat, FlatFileOptions, Format};
+use lazy::{convert_lazy_response, LazyQueryResponse};
+use lookup::BlockHint;
+use query::{LogField, Query};
+use reorg::ReorgStream;
+use sqlite_out::SqliteStream;
+use timestamp::{
+    find_first_block, probe_block_timestamp, BlockByTimestampOptions, BlockRange, Boundary,
+    TimestampCache, TimestampDirection,
+};
+use types::{Block, Encoding, Event, Log, RollbackGuard, Trace, Transaction};
 
 static LOGGER_INIT: Once = Once::new();
 
@@ -75,20 +110,21 @@ pub struct QueryResponseWithRateLimit {
     pub rate_limit: RateLimitInfo,
 }
 
-
 /// HyperSync client for querying blockchain data
 #[napi]
 pub struct HypersyncClient {
     inner: hypersync_client::Client,
-    enable_checksum_addresses: bool,
+    encoding: Encoding,
+    debug: bool,
+    block_timestamps: TimestampCache,
 }
 
 #[napi]
 impl HypersyncClient {
     /// Create a new client with given config
     #[napi(constructor)]
-    pub fn new(cfg: ClientConfig) -> napi::Result<HypersyncClient> {
-        Self::new_with_agent(cfg, format!("hscn/{}", env!("CARGO_PKG_VERSION")))
+    pub fn new(env: Env, cfg: ClientConfig) -> napi::Result<HypersyncClient> {
+        Self::new_with_agent(env, cfg, format!("hscn/{}", env!("CARGO_PKG_VERSION")))
     }
 
     /// Create a new client with custom user agent
@@ -99,125 +135,511 @@ impl HypersyncClient {
     /// @internal
     #[doc(hidden)]
     #[napi]
-    pub fn new_with_agent(cfg: ClientConfig, user_agent: String) -> napi::Result<HypersyncClient> {
+    pub fn new_with_agent(
+        env: Env,
+        cfg: ClientConfig,
+        user_agent: String,
+    ) -> napi::Result<HypersyncClient> {
         init_logger(Some("info"));
 
-        let enable_checksum_addresses = cfg.enable_checksum_addresses.unwrap_or_default();
+        let encoding = Encoding {
+            checksum_addresses: cfg.enable_checksum_addresses.unwrap_or_default(),
+            binary: cfg.binary_fields.unwrap_or_default(),
+            numeric: cfg.numeric_mode,
+        };
+        let debug = cfg.debug.unwrap_or_default();
 
         let inner = hypersync_client::Client::new_with_agent(cfg.into(), user_agent)
             .context("build client")
-            .map_err(map_err)?;
+            .with_code(ErrorCode::InvalidQuery)
+            .map_err(js_error(&env))?;
 
         Ok(HypersyncClient {
             inner,
-            enable_checksum_addresses,
+            encoding,
+            debug,
+            block_timestamps: TimestampCache::default(),
         })
     }
 
     /// Get the height of the source hypersync instance
-    #[napi]
-    pub async fn get_height(&self) -> napi::Result<i64> {
-        let height = self.inner.get_height().await.map_err(map_err)?;
-
-        Ok(height.try_into().unwrap())
+    #[napi(ts_return_type = "Promise<number>")]
+    pub async fn get_height(&self) -> ClientResult<i64> {
+        let res = async {
+            let height = self.inner.get_height().await.context("get height")?;
+            height.try_into().context("convert height")
+        };
+        self.finish(res.await, None)
     }
 
     /// Get the chain_id of the source hypersync instance
-    #[napi]
-    pub async fn get_chain_id(&self) -> napi::Result<i64> {
-        let chain_id = self.inner.get_chain_id().await.map_err(map_err)?;
+    #[napi(ts_return_type = "Promise<number>")]
+    pub async fn get_chain_id(&self) -> ClientResult<i64> {
+        let res = async {
+            let chain_id = self.inner.get_chain_id().await.context("get chain id")?;
+            chain_id.try_into().context("convert chain id")
+        };
+        self.finish(res.await, None)
+    }
 
-        Ok(chain_id.try_into().unwrap())
+    /// Get the number of the block at a unix timestamp in seconds.
+    ///
+    /// Returns the first block at or after the timestamp, or the last block at or before it
+    /// with `direction: "Before"`. Returns null if there is no such block.
+    /// Timestamps of probed blocks are cached, so lookups of nearby timestamps are cheaper.
+    #[napi(ts_return_type = "Promise<number | null>")]
+    pub async fn get_block_number_by_timestamp(
+        &self,
+        timestamp: i64,
+        options: Option<BlockByTimestampOptions>,
+    ) -> ClientResult<Option<i64>> {
+        let res = async {
+            let timestamp = u64::try_from(timestamp)
+                .context("convert timestamp")
+                .with_code(ErrorCode::InvalidQuery)?;
+            let direction = options.and_then(|o| o.direction).unwrap_or_default();
+
+            let block = match direction {
+                TimestampDirection::After => {
+                    match self.find_block_by_timestamp(timestamp, true).await? {
+                        Boundary::Block(block) => Some(block),
+                        Boundary::AfterHead(_) => None,
+                    }
+                }
+                TimestampDirection::Before => {
+                    match self.find_block_by_timestamp(timestamp, false).await? {
+                        Boundary::Block(block) => block.checked_sub(1),
+                        Boundary::AfterHead(head) => Some(head),
+                    }
+                }
+            };
+            Ok(block.map(|block| block as i64))
+        };
+        self.finish(res.await, None)
     }
 
-    /// Collect blockchain data from the given query
-    #[napi]
-    pub async fn collect(&self, query: Query, config: StreamConfig) -> napi::Result<QueryResponse> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
+    /// Get the block range of a time range `[startTimestamp, endTimestamp)` given as unix
+    /// timestamps in seconds, to use as `fromBlock` and `toBlock` of a query.
+    ///
+    /// Returns null if the time range starts after the last block.
+    #[napi(ts_return_type = "Promise<BlockRange | null>")]
+    pub async fn get_block_range_by_timestamp(
+        &self,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    ) -> ClientResult<Option<BlockRange>> {
+        let res = async {
+            let start = u64::try_from(start_timestamp)
+                .context("convert start timestamp")
+                .with_code(ErrorCode::InvalidQuery)?;
+            let end = u64::try_from(end_timestamp)
+                .context("convert end timestamp")
+                .with_code(ErrorCode::InvalidQuery)?;
+            if end <= start {
+                return Err(anyhow::anyhow!(
+                    "end timestamp ({}) must be greater than start timestamp ({})",
+                    end,
+                    start
+                ))
+                .with_code(ErrorCode::InvalidQuery);
+            }
+
+            let from_block = match self.find_block_by_timestamp(start, true).await? {
+                Boundary::Block(block) => block,
+                Boundary::AfterHead(_) => return Ok(None),
+            };
+            let to_block = match self.find_block_by_timestamp(end, true).await? {
+                Boundary::Block(block) => Some(block as i64),
+                Boundary::AfterHead(_) => None,
+            };
+            Ok(Some(BlockRange {
+                from_block: from_block as i64,
+                to_block,
+            }))
+        };
+        self.finish(res.await, None)
+    }
 
-        let resp = self
-            .inner
-            .clone()
-            .collect(query, config.into())
+    /// Collect blockchain data from the given query
+    ///
+    /// Rows are added to the result a chunk at a time, so the event loop keeps running while a
+    /// large result is created.
+    ///
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<QueryResponse>")]
+    pub async fn collect(
+        &self,
+        query: Query,
+        config: StreamConfig,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<Chunked<QueryResponse>> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+
+            run_cancellable(cancel.map(CancellationHandle::token), async {
+                let rx = self
+                    .inner
+                    .clone()
+                    .stream(net_query, config.into())
+                    .await
+                    .context("start inner stream")?;
+                collect_responses(rx, encoding).await
+            })
             .await
-            .context("run inner collect")
-            .map_err(map_err)?;
-
-        convert_response(resp, self.enable_checksum_addresses)
-            .context("convert response")
-            .map_err(map_err)
+        };
+        self.finish(res.await.map(Chunked::from), Some(&query))
     }
 
     /// Collect blockchain events from the given query
-    #[napi]
+    ///
+    /// Rows are added to the result a chunk at a time, so the event loop keeps running while a
+    /// large result is created.
+    ///
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<EventResponse>")]
     pub async fn collect_events(
         &self,
         query: Query,
         config: StreamConfig,
-    ) -> napi::Result<EventResponse> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
-        let config = config.into();
-
-        let resp = self
-            .inner
-            .clone()
-            .collect_events(query, config)
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<Chunked<EventResponse>> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            let config = config.into();
+
+            run_cancellable(cancel.map(CancellationHandle::token), async {
+                let rx = self
+                    .inner
+                    .clone()
+                    .stream_events(net_query, config)
+                    .await
+                    .context("start inner stream")?;
+                collect_event_responses(rx, encoding).await
+            })
             .await
-            .context("run inner collect")
-            .map_err(map_err)?;
-
-        convert_event_response(resp, self.enable_checksum_addresses)
-            .context("convert response")
-            .map_err(map_err)
+        };
+        self.finish(res.await.map(Chunked::from), Some(&query))
     }
 
     /// Collect blockchain data and save to parquet format
-    #[napi]
+    ///
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<void>")]
     pub async fn collect_parquet(
         &self,
         path: String,
         query: Query,
         config: StreamConfig,
-    ) -> napi::Result<()> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
-        let config = config.into();
-
-        self.inner
-            .clone()
-            .collect_parquet(&path, query, config)
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<()> {
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            let config = config.into();
+
+            run_cancellable(cancel.map(CancellationHandle::token), async {
+                self.inner
+                    .clone()
+                    .collect_parquet(&path, net_query, config)
+                    .await
+            })
             .await
-            .map_err(map_err)
+        };
+        self.finish(res.await, Some(&query))
     }
 
-    /// Get blockchain data for a single query
-    #[napi]
-    pub async fn get(&self, query: Query) -> napi::Result<QueryResponse> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
+    /// Collect blockchain data into CSV files, one per table in the directory at `path`
+    ///
+    /// Decoded logs are written to `decoded_logs.csv` when `config.eventSignature` is set.
+    /// Binary columns are written as hex without prefix unless `config.hexOutput` is set.
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async fn collect_csv(
+        &self,
+        path: String,
+        query: Query,
+        config: StreamConfig,
+        options: Option<FlatFileOptions>,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<()> {
         let res = self
-            .inner
-            .get(&query)
-            .await
-            .context("run inner query")
-            .map_err(map_err)?;
-        convert_response(res, self.enable_checksum_addresses)
-            .context("convert response")
-            .map_err(map_err)
+            .collect_flat_files(&path, &query, config, options, cancel, Format::Csv)
+            .await;
+        self.finish(res, Some(&query))
     }
 
-    /// Get blockchain events for a single query
-    #[napi]
-    pub async fn get_events(&self, query: Query) -> napi::Result<EventResponse> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
+    /// Collect blockchain data into newline delimited JSON files, one per table in the
+    /// directory at `path`
+    ///
+    /// Decoded logs are written to `decoded_logs.ndjson` when `config.eventSignature` is set.
+    /// Binary columns are written as hex without prefix unless `config.hexOutput` is set.
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async fn collect_ndjson(
+        &self,
+        path: String,
+        query: Query,
+        config: StreamConfig,
+        options: Option<FlatFileOptions>,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<()> {
         let res = self
-            .inner
-            .get_events(query)
+            .collect_flat_files(&path, &query, config, options, cancel, Format::Ndjson)
+            .await;
+        self.finish(res, Some(&query))
+    }
+
+    /// Collect blockchain data into the SQLite database at `path`, with a table per data type
+    ///
+    /// Tables are created from the selected fields, decoded logs are written to `decoded_logs`
+    /// when `config.eventSignature` is set. Each response is inserted in one transaction along
+    /// with the block to continue from, so calling this again resumes where the last call
+    /// stopped. Rows of blocks that are rolled back are deleted.
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async fn collect_sqlite(
+        &self,
+        path: String,
+        query: Query,
+        config: StreamConfig,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<()> {
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+
+            run_cancellable(cancel.map(CancellationHandle::token), async {
+                sqlite_out::run(&self.inner, path, net_query, config, None).await
+            })
             .await
-            .context("run inner query")
-            .map_err(map_err)?;
-        let r = convert_event_response(res, self.enable_checksum_addresses)
-            .context("convert response")
-            .map_err(map_err)?;
-        Ok(r)
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Stream blockchain data into the SQLite database at `path`, see `collectSqlite`
+    ///
+    /// The returned stream yields the progress after every committed response or rollback.
+    #[napi(ts_return_type = "Promise<SqliteStream>")]
+    pub async fn stream_sqlite(
+        &self,
+        path: String,
+        query: Query,
+        config: StreamConfig,
+    ) -> ClientResult<SqliteStream> {
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            SqliteStream::start(&self.inner, path, net_query, config)
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Collect blockchain data as Arrow IPC buffers, one per table
+    ///
+    /// `config.columnMapping` and `config.hexOutput` are applied to the columns.
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<ArrowResponse>")]
+    pub async fn collect_arrow(
+        &self,
+        query: Query,
+        config: StreamConfig,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<ArrowResponse> {
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+
+            let resp = run_cancellable(cancel.map(CancellationHandle::token), async {
+                self.inner
+                    .clone()
+                    .collect_arrow(net_query, config.into())
+                    .await
+                    .context("run inner collect")
+            })
+            .await?;
+
+            convert_arrow_response(resp)
+                .context("convert response")
+                .with_code(ErrorCode::DecodeError)
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Collect blockchain data without converting it up front, rows are converted to JS
+    /// values only when they are accessed.
+    ///
+    /// Prefer this over `collect` for large results where only some rows or fields are used.
+    /// `config.columnMapping` and `config.hexOutput` are ignored.
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<LazyQueryResponse>")]
+    pub async fn collect_lazy(
+        &self,
+        query: Query,
+        config: StreamConfig,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<LazyQueryResponse> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            let mut config = hypersync_client::StreamConfig::from(config);
+            // values are converted from the raw columns on access
+            config.column_mapping = None;
+            config.hex_output = hypersync_client::HexOutput::NoEncode;
+
+            let resp = run_cancellable(cancel.map(CancellationHandle::token), async {
+                self.inner
+                    .clone()
+                    .collect_arrow(net_query, config)
+                    .await
+                    .context("run inner collect")
+            })
+            .await?;
+
+            convert_lazy_response(resp, encoding)
+                .context("convert response")
+                .with_code(ErrorCode::DecodeError)
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Get blockchain data for a single query
+    ///
+    /// Pass a `CancellationHandle` to be able to abort the call.
+    #[napi(ts_return_type = "Promise<QueryResponse>")]
+    pub async fn get(
+        &self,
+        query: Query,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<QueryResponse> {
+        let res = async {
+            let net_query = self.parse_query(&query)?;
+            let res = run_cancellable(cancel.map(CancellationHandle::token), async {
+                self.inner.get(&net_query).await.context("run inner query")
+            })
+            .await?;
+            convert_response(res, self.encoding)
+                .await
+                .context("convert response")
+                .with_code(ErrorCode::DecodeError)
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Get blockchain events for a single query
+    #[napi(ts_return_type = "Promise<EventResponse>")]
+    pub async fn get_events(&self, query: Query) -> ClientResult<EventResponse> {
+        let res = async {
+            let net_query = self.parse_query(&query)?;
+            let res = self
+                .inner
+                .get_events(net_query)
+                .await
+                .context("run inner query")?;
+            convert_event_response(res, self.encoding)
+                .await
+                .context("convert response")
+                .with_code(ErrorCode::DecodeError)
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Get a block by number or hash, null if it isn't found.
+    ///
+    /// Lookups by hash scan the chain, pass `hint` to narrow down the blocks to search in.
+    /// Pass a `CancellationHandle` to be able to abort the lookup.
+    #[napi(ts_return_type = "Promise<Block | null>")]
+    pub async fn get_block(
+        &self,
+        number_or_hash: Either<i64, String>,
+        hint: Option<BlockHint>,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<Option<Block>> {
+        let query = match number_or_hash {
+            Either::A(number) => lookup::block_by_number_query(number),
+            Either::B(hash) => lookup::block_by_hash_query(hash, hint),
+        };
+        let res = async {
+            let Some(resp) = self.get_first_match(&query, cancel).await? else {
+                return Ok(None);
+            };
+            Ok(resp.data.blocks.into_iter().next())
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Get a transaction by hash, null if it isn't found.
+    ///
+    /// Pass `hint` to narrow down the blocks to search in.
+    /// Pass a `CancellationHandle` to be able to abort the lookup.
+    #[napi(ts_return_type = "Promise<Transaction | null>")]
+    pub async fn get_transaction(
+        &self,
+        hash: String,
+        hint: Option<BlockHint>,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<Option<Transaction>> {
+        let query = lookup::transaction_query(hash.clone(), hint, lookup::all_transaction_fields());
+        let res = async {
+            let Some(resp) = self.get_first_match(&query, cancel).await? else {
+                return Ok(None);
+            };
+            Ok(resp
+                .data
+                .transactions
+                .into_iter()
+                .find(|tx| lookup::same_hash(tx.hash.as_ref(), &hash)))
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Get the logs emitted by a transaction, empty if the transaction isn't found.
+    ///
+    /// Pass `hint` to narrow down the blocks to search in.
+    /// Pass a `CancellationHandle` to be able to abort the lookup.
+    #[napi(ts_return_type = "Promise<Array<Log>>")]
+    pub async fn get_transaction_receipt_logs(
+        &self,
+        hash: String,
+        hint: Option<BlockHint>,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<Vec<Log>> {
+        let query = lookup::transaction_query(hash.clone(), hint, lookup::all_log_fields());
+        let res = async {
+            let Some(resp) = self.get_first_match(&query, cancel).await? else {
+                return Ok(Vec::new());
+            };
+            Ok(resp
+                .data
+                .logs
+                .into_iter()
+                .filter(|log| lookup::same_hash(log.transaction_hash.as_ref(), &hash))
+                .collect())
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Get the traces of a transaction, empty if the transaction isn't found.
+    ///
+    /// Pass `hint` to narrow down the blocks to search in.
+    /// Pass a `CancellationHandle` to be able to abort the lookup.
+    #[napi(ts_return_type = "Promise<Array<Trace>>")]
+    pub async fn get_traces(
+        &self,
+        tx_hash: String,
+        hint: Option<BlockHint>,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<Vec<Trace>> {
+        let query = lookup::transaction_query(tx_hash.clone(), hint, lookup::all_trace_fields());
+        let res = async {
+            let Some(resp) = self.get_first_match(&query, cancel).await? else {
+                return Ok(Vec::new());
+            };
+            Ok(resp
+                .data
+                .traces
+                .into_iter()
+                .filter(|trace| lookup::same_hash(trace.transaction_hash.as_ref(), &tx_hash))
+                .collect())
+        };
+        self.finish(res.await, Some(&query))
     }
 
     /// Stream chain height events
@@ -227,53 +649,178 @@ impl HypersyncClient {
         let inner = self.inner.clone().stream_height();
 
         HeightStream {
-            inner: tokio::sync::Mutex::new(inner),
+            inner: Arc::new(tokio::sync::Mutex::new(inner)),
         }
     }
     /// Stream blockchain data from the given query
-    #[napi]
+    ///
+    /// Pass a `CancellationHandle` to be able to abort the stream, cancelling it stops the
+    /// background tasks and makes pending and later `recv()` calls reject.
+    #[napi(ts_return_type = "Promise<QueryResponseStream>")]
     pub async fn stream(
         &self,
         query: Query,
         config: StreamConfig,
-    ) -> napi::Result<QueryResponseStream> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
-        let config = config.into();
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<QueryResponseStream> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            let cancel = cancel.map(CancellationHandle::token);
+
+            let inner = run_cancellable(cancel.clone(), async {
+                live::stream(&self.inner, net_query, config)
+                    .await
+                    .context("start stream")
+            })
+            .await?;
+            let inner = Arc::new(tokio::sync::Mutex::new(inner));
+
+            Ok(QueryResponseStream {
+                _close_on_cancel: cancel.as_ref().map(|token| close_on_cancel(token, &inner)),
+                inner,
+                encoding,
+                cancel,
+                checkpoint: None,
+            })
+        };
+        self.finish(res.await, Some(&query))
+    }
 
-        let inner = self
-            .inner
-            .clone()
-            .stream(query, config)
-            .await
-            .context("start stream")
-            .map_err(map_err)?;
+    /// Stream blockchain data as Arrow IPC buffers, one per table
+    ///
+    /// `config.columnMapping` and `config.hexOutput` are applied to the columns,
+    /// `config.followHead` is not supported.
+    /// Pass a `CancellationHandle` to be able to abort the stream.
+    #[napi(ts_return_type = "Promise<ArrowResponseStream>")]
+    pub async fn stream_arrow(
+        &self,
+        query: Query,
+        config: StreamConfig,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<ArrowResponseStream> {
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            let cancel = cancel.map(CancellationHandle::token);
+
+            let inner = run_cancellable(cancel.clone(), async {
+                live::stream_arrow(&self.inner, net_query, config)
+                    .await
+                    .context("start stream")
+            })
+            .await?;
+            let inner = Arc::new(tokio::sync::Mutex::new(inner));
 
-        Ok(QueryResponseStream {
-            inner: tokio::sync::Mutex::new(inner),
-            enable_checksum_addresses: self.enable_checksum_addresses,
-        })
+            Ok(ArrowResponseStream {
+                _close_on_cancel: cancel.as_ref().map(|token| close_on_cancel(token, &inner)),
+                inner,
+                cancel,
+            })
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Stream blockchain data from the given query, resuming from the checkpoint in `store`
+    ///
+    /// If the store holds a checkpoint, the stream starts from its `nextBlock` instead of
+    /// `query.fromBlock`, and ends right away if that is at or past `query.toBlock`. Before
+    /// resuming, the block hash recorded in the checkpoint is compared against the chain and the
+    /// call fails with `Rollback` if the chain was rolled back since.
+    ///
+    /// The checkpoint of a response is saved when the next response is requested, so a
+    /// response only counts as processed once the consumer comes back for more.
+    /// Pass a `CancellationHandle` to be able to abort the stream.
+    #[napi(ts_return_type = "Promise<QueryResponseStream>")]
+    pub async fn stream_with_checkpoint(
+        &self,
+        query: Query,
+        config: StreamConfig,
+        store: &CheckpointStore,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<QueryResponseStream> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            check_checkpoint_config(&config)?;
+            let cancel = cancel.map(CancellationHandle::token);
+
+            let inner = run_cancellable(cancel.clone(), async {
+                let Some(net_query) = resume_query(&self.inner, net_query, store)
+                    .await
+                    .context("resume from checkpoint")?
+                else {
+                    return Ok(finished_stream());
+                };
+
+                live::stream(&self.inner, net_query, config)
+                    .await
+                    .context("start stream")
+            })
+            .await?;
+            let inner = Arc::new(tokio::sync::Mutex::new(inner));
+
+            Ok(QueryResponseStream {
+                _close_on_cancel: cancel.as_ref().map(|token| close_on_cancel(token, &inner)),
+                inner,
+                encoding,
+                cancel,
+                checkpoint: Some(CheckpointWriter::new(store.clone())),
+            })
+        };
+        self.finish(res.await, Some(&query))
+    }
+
+    /// Stream blockchain data from the given query, detecting chain reorganizations
+    ///
+    /// Each response is checked against the block hashes delivered before using its rollback
+    /// guard. When a response doesn't chain onto them, a `Rollback` event is emitted and the
+    /// stream continues with the new data from the first rolled back block.
+    /// Pass a `CancellationHandle` to be able to abort the stream.
+    #[napi(ts_return_type = "Promise<ReorgStream>")]
+    pub async fn stream_with_reorg_detection(
+        &self,
+        query: Query,
+        config: StreamConfig,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<ReorgStream> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            let cancel = cancel.map(CancellationHandle::token);
+
+            run_cancellable(cancel.clone(), async {
+                ReorgStream::start(&self.inner, net_query, config, encoding, cancel)
+                    .await
+                    .context("start stream")
+            })
+            .await
+        };
+        self.finish(res.await, Some(&query))
     }
 
     /// Get blockchain data for a single query, with rate limit info
-    #[napi]
+    #[napi(ts_return_type = "Promise<QueryResponseWithRateLimit>")]
     pub async fn get_with_rate_limit(
         &self,
         query: Query,
-    ) -> napi::Result<QueryResponseWithRateLimit> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
-        let res = self
-            .inner
-            .get_with_rate_limit(&query)
-            .await
-            .context("run inner query")
-            .map_err(map_err)?;
-        let response = convert_response(res.response, self.enable_checksum_addresses)
-            .context("convert response")
-            .map_err(map_err)?;
-        Ok(QueryResponseWithRateLimit {
-            response,
-            rate_limit: res.rate_limit.into(),
-        })
+    ) -> ClientResult<QueryResponseWithRateLimit> {
+        let res = async {
+            let net_query = self.parse_query(&query)?;
+            let res = self
+                .inner
+                .get_with_rate_limit(&net_query)
+                .await
+                .context("run inner query")?;
+            let response = convert_response(res.response, self.encoding)
+                .await
+                .context("convert response")
+                .with_code(ErrorCode::DecodeError)?;
+            Ok(QueryResponseWithRateLimit {
+                response,
+                rate_limit: res.rate_limit.into(),
+            })
+        };
+        self.finish(res.await, Some(&query))
     }
 
     /// Get the most recently observed rate limit information.
@@ -291,35 +838,258 @@ impl HypersyncClient {
     }
 
     /// Stream blockchain events from the given query
-    #[napi]
+    ///
+    /// Pass a `CancellationHandle` to be able to abort the stream.
+    #[napi(ts_return_type = "Promise<EventStream>")]
     pub async fn stream_events(
         &self,
         query: Query,
         config: StreamConfig,
-    ) -> napi::Result<EventStream> {
-        let query = query.try_into().context("parse query").map_err(map_err)?;
-        let config = config.into();
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<EventStream> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            let cancel = cancel.map(CancellationHandle::token);
+
+            let inner = run_cancellable(cancel.clone(), async {
+                live::stream_events(&self.inner, net_query, config)
+                    .await
+                    .context("start stream")
+            })
+            .await?;
+            let inner = Arc::new(tokio::sync::Mutex::new(inner));
+
+            Ok(EventStream {
+                _close_on_cancel: cancel.as_ref().map(|token| close_on_cancel(token, &inner)),
+                inner,
+                encoding,
+                cancel,
+                checkpoint: None,
+            })
+        };
+        self.finish(res.await, Some(&query))
+    }
 
-        let inner = self
-            .inner
-            .clone()
-            .stream_events(query, config)
-            .await
-            .context("start stream")
-            .map_err(map_err)?;
+    /// Stream blockchain events from the given query, resuming from the checkpoint in `store`
+    ///
+    /// Works the same way as `streamWithCheckpoint`.
+    #[napi(ts_return_type = "Promise<EventStream>")]
+    pub async fn stream_events_with_checkpoint(
+        &self,
+        query: Query,
+        config: StreamConfig,
+        store: &CheckpointStore,
+        cancel: Option<&CancellationHandle>,
+    ) -> ClientResult<EventStream> {
+        let encoding = self.encoding.for_stream(&config);
+        let res = async {
+            let net_query = self.parse_stream_query(&query, &config)?;
+            check_checkpoint_config(&config)?;
+            let cancel = cancel.map(CancellationHandle::token);
+
+            let inner = run_cancellable(cancel.clone(), async {
+                let Some(net_query) = resume_query(&self.inner, net_query, store)
+                    .await
+                    .context("resume from checkpoint")?
+                else {
+                    return Ok(finished_stream());
+                };
+
+                live::stream_events(&self.inner, net_query, config)
+                    .await
+                    .context("start stream")
+            })
+            .await?;
+            let inner = Arc::new(tokio::sync::Mutex::new(inner));
+
+            Ok(EventStream {
+                _close_on_cancel: cancel.as_ref().map(|token| close_on_cancel(token, &inner)),
+                inner,
+                encoding,
+                cancel,
+                checkpoint: Some(CheckpointWriter::new(store.clone())),
+            })
+        };
+        self.finish(res.await, Some(&query))
+    }
+}
+
+impl HypersyncClient {
+    /// Turn the result of a call into its JS result, adding rate limit info and the query to errors
+    fn finish<T>(&self, res: Result<T>, query: Option<&Query>) -> ClientResult<T> {
+        let res = res.map_err(|e| {
+            let mut e = HypersyncError::from(e);
+            if e.code == ErrorCode::RateLimited && e.retry_after_secs.is_none() {
+                e.retry_after_secs = self
+                    .inner
+                    .rate_limit_info()
+                    .and_then(|info| info.reset_secs);
+            }
+            e.query = query.cloned();
+            e
+        });
+        res.into()
+    }
 
-        Ok(EventStream {
-            inner: tokio::sync::Mutex::new(inner),
-            enable_checksum_addresses: self.enable_checksum_addresses,
+    async fn collect_flat_files(
+        &self,
+        path: &str,
+        query: &Query,
+        config: StreamConfig,
+        options: Option<FlatFileOptions>,
+        cancel: Option<&CancellationHandle>,
+        format: Format,
+    ) -> Result<()> {
+        let net_query = self.parse_stream_query(query, &config)?;
+        let compression = options.and_then(|o| o.compression).unwrap_or_default();
+
+        run_cancellable(cancel.map(CancellationHandle::token), async {
+            collect_flat(
+                &self.inner,
+                path,
+                net_query,
+                config.into(),
+                format,
+                compression,
+            )
+            .await
         })
+        .await
+    }
+
+    /// Run the query page by page until a page has data, None if the range is exhausted first
+    async fn get_first_match(
+        &self,
+        query: &Query,
+        cancel: Option<&CancellationHandle>,
+    ) -> Result<Option<QueryResponse>> {
+        let mut net_query = self.parse_query(query)?;
+        for _ in 0..lookup::MAX_LOOKUP_PAGES {
+            let resp = run_cancellable(cancel.map(CancellationHandle::token), async {
+                self.inner.get(&net_query).await.context("run inner query")
+            })
+            .await?;
+            let data = &resp.data;
+            let found = [
+                data.blocks.iter().any(|b| !b.is_empty()),
+                data.transactions.iter().any(|b| !b.is_empty()),
+                data.logs.iter().any(|b| !b.is_empty()),
+                data.traces.iter().any(|b| !b.is_empty()),
+            ]
+            .contains(&true);
+            let past_end = net_query
+                .to_block
+                .is_some_and(|to_block| resp.next_block >= to_block)
+                || resp
+                    .archive_height
+                    .is_some_and(|height| resp.next_block > height)
+                || resp.next_block <= net_query.from_block;
+            if found || past_end {
+                let resp = convert_response(resp, self.encoding)
+                    .await
+                    .context("convert response")
+                    .with_code(ErrorCode::DecodeError)?;
+                return Ok(found.then_some(resp));
+            }
+            net_query.from_block = resp.next_block;
+        }
+
+        anyhow::bail!(
+            "no match after {} requests, stopped at block {}. Pass a hint to narrow down the blocks to search in",
+            lookup::MAX_LOOKUP_PAGES,
+            net_query.from_block
+        )
     }
+
+    /// First block with a timestamp after `timestamp`, or at or after it if `inclusive`
+    async fn find_block_by_timestamp(&self, timestamp: u64, inclusive: bool) -> Result<Boundary> {
+        let height = self.inner.get_height().await.context("get height")?;
+        find_first_block(
+            &self.block_timestamps,
+            height,
+            timestamp,
+            inclusive,
+            |block| probe_block_timestamp(&self.inner, block),
+        )
+        .await
+        .context("find block by timestamp")
+    }
+
+    /// Convert the query for the inner client, validating it first in debug mode
+    fn parse_query(&self, query: &Query) -> Result<hypersync_client::net_types::Query> {
+        self.parse_query_with(query, None)
+    }
+
+    /// Like `parse_query`, also checking in debug mode that the log fields needed to decode
+    /// `config.eventSignature` are selected
+    fn parse_stream_query(
+        &self,
+        query: &Query,
+        config: &StreamConfig,
+    ) -> Result<hypersync_client::net_types::Query> {
+        let decoder_fields = match (&config.event_signature, self.debug) {
+            (Some(signature), true) => Some(event_signature_log_fields(signature)?),
+            _ => None,
+        };
+        self.parse_query_with(query, decoder_fields.as_deref())
+    }
+
+    fn parse_query_with(
+        &self,
+        query: &Query,
+        decoder_fields: Option<&[LogField]>,
+    ) -> Result<hypersync_client::net_types::Query> {
+        if self.debug {
+            let validation = validate::validate(query, decoder_fields);
+            for issue in &validation.warnings {
+                log::warn!("query {}: {}", issue.path, issue.message);
+            }
+            if !validation.errors.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "invalid query: {}",
+                    validation.error_summary()
+                ))
+                .with_code(ErrorCode::InvalidQuery);
+            }
+        }
+
+        query
+            .clone()
+            .try_into()
+            .context("parse query")
+            .with_code(ErrorCode::InvalidQuery)
+    }
+}
+
+fn check_checkpoint_config(config: &StreamConfig) -> Result<()> {
+    if config.reverse.unwrap_or_default() {
+        return Err(anyhow::anyhow!(
+            "checkpoints are not supported for reverse streams"
+        ))
+        .with_code(ErrorCode::InvalidQuery);
+    }
+    Ok(())
+}
+
+/// Receiver of a stream that was resumed past the end of its query, it ends right away
+fn finished_stream<T>() -> mpsc::Receiver<T> {
+    mpsc::channel(1).1
 }
 
 /// Stream for receiving query responses
-#[napi]
+///
+/// Can be consumed with `for await (const res of stream)`, breaking out of the
+/// loop closes the stream. `recv()` adds the rows of a response in chunks, while
+/// `for await` creates each response in one turn of the event loop.
+#[napi(async_iterator)]
 pub struct QueryResponseStream {
-    inner: tokio::sync::Mutex<mpsc::Receiver<Result<hypersync_client::QueryResponse>>>,
-    enable_checksum_addresses: bool,
+    inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<hypersync_client::QueryResponse>>>>,
+    encoding: Encoding,
+    cancel: Option<CancellationToken>,
+    /// Stops the cancellation watcher when the stream is dropped
+    _close_on_cancel: Option<DropGuard>,
+    checkpoint: Option<CheckpointWriter>,
 }
 
 #[napi]
@@ -331,25 +1101,171 @@ impl QueryResponseStream {
     }
 
     /// Receive the next query response from the stream
+    #[napi(ts_return_type = "Promise<QueryResponse | null>")]
+    pub async fn recv(&self) -> ClientResult<Option<Chunked<QueryResponse>>> {
+        Self::recv_impl(
+            self.inner.clone(),
+            self.encoding,
+            self.cancel.clone(),
+            self.checkpoint.clone(),
+        )
+        .await
+        .map(|resp| resp.map(Chunked::from))
+        .into()
+    }
+
+    async fn recv_impl(
+        inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<hypersync_client::QueryResponse>>>>,
+        encoding: Encoding,
+        cancel: Option<CancellationToken>,
+        checkpoint: Option<CheckpointWriter>,
+    ) -> Result<Option<QueryResponse>> {
+        let mut rx = inner.lock().await;
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.commit().await.context("save checkpoint")?;
+        }
+
+        let resp = match recv_cancellable(&mut rx, cancel).await? {
+            Some(resp) => resp?,
+            None => return Ok(None),
+        };
+
+        let next_checkpoint = checkpoint
+            .as_ref()
+            .map(|_| Checkpoint::from_response(&resp))
+            .transpose()
+            .context("build checkpoint")?;
+
+        let resp = convert_response(resp, encoding)
+            .await
+            .context("convert response")
+            .with_code(ErrorCode::DecodeError)?;
+
+        // Only stage once the response can be delivered, otherwise resuming would skip it
+        if let (Some(checkpoint), Some(next)) = (&checkpoint, next_checkpoint) {
+            checkpoint.stage(next).await;
+        }
+        Ok(Some(resp))
+    }
+}
+
+#[napi]
+impl AsyncGenerator for QueryResponseStream {
+    type Yield = ClientResult<QueryResponse>;
+    type Next = ();
+    type Return = ();
+
+    fn next(
+        &mut self,
+        _value: Option<Self::Next>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let recv = Self::recv_impl(
+            self.inner.clone(),
+            self.encoding,
+            self.cancel.clone(),
+            self.checkpoint.clone(),
+        );
+        async move { ClientResult::stream_item(recv.await) }
+    }
+
+    fn complete(
+        &mut self,
+        _value: Option<Self::Return>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move {
+            inner.lock().await.close();
+            Ok(None)
+        }
+    }
+}
+
+/// Stream for receiving arrow responses
+///
+/// Can be consumed with `for await (const res of stream)`, breaking out of the
+/// loop closes the stream.
+#[napi(async_iterator)]
+pub struct ArrowResponseStream {
+    inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<hypersync_client::ArrowResponse>>>>,
+    cancel: Option<CancellationToken>,
+    /// Stops the cancellation watcher when the stream is dropped
+    _close_on_cancel: Option<DropGuard>,
+}
+
+#[napi]
+impl ArrowResponseStream {
+    /// Close the response stream
     #[napi]
-    pub async fn recv(&self) -> napi::Result<Option<QueryResponse>> {
-        let resp = self.inner.lock().await.recv().await;
+    pub async fn close(&self) {
+        self.inner.lock().await.close();
+    }
 
-        resp.map(|r| {
-            convert_response(r?, self.enable_checksum_addresses).context("convert response")
-        })
-        .transpose()
-        .map_err(map_err)
+    /// Receive the next arrow response from the stream
+    #[napi(ts_return_type = "Promise<ArrowResponse | null>")]
+    pub async fn recv(&self) -> ClientResult<Option<ArrowResponse>> {
+        Self::recv_impl(self.inner.clone(), self.cancel.clone())
+            .await
+            .into()
+    }
+
+    async fn recv_impl(
+        inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<hypersync_client::ArrowResponse>>>>,
+        cancel: Option<CancellationToken>,
+    ) -> Result<Option<ArrowResponse>> {
+        let mut rx = inner.lock().await;
+        let resp = match recv_cancellable(&mut rx, cancel).await? {
+            Some(resp) => resp?,
+            None => return Ok(None),
+        };
+
+        convert_arrow_response(resp)
+            .context("convert response")
+            .with_code(ErrorCode::DecodeError)
+            .map(Some)
+    }
+}
+
+#[napi]
+impl AsyncGenerator for ArrowResponseStream {
+    type Yield = ClientResult<ArrowResponse>;
+    type Next = ();
+    type Return = ();
+
+    fn next(
+        &mut self,
+        _value: Option<Self::Next>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let recv = Self::recv_impl(self.inner.clone(), self.cancel.clone());
+        async move { ClientResult::stream_item(recv.await) }
+    }
+
+    fn complete(
+        &mut self,
+        _value: Option<Self::Return>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move {
+            inner.lock().await.close();
+            Ok(None)
+        }
     }
 }
 
 type HSEventResponse = hypersync_client::QueryResponse<Vec<hypersync_client::simple_types::Event>>;
 
 /// Stream for receiving event responses
-#[napi]
+///
+/// Can be consumed with `for await (const res of stream)`, breaking out of the
+/// loop closes the stream. `recv()` adds the rows of a response in chunks, while
+/// `for await` creates each response in one turn of the event loop.
+#[napi(async_iterator)]
 pub struct EventStream {
-    inner: tokio::sync::Mutex<mpsc::Receiver<Result<HSEventResponse>>>,
-    enable_checksum_addresses: bool,
+    inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<HSEventResponse>>>>,
+    encoding: Encoding,
+    cancel: Option<CancellationToken>,
+    /// Stops the cancellation watcher when the stream is dropped
+    _close_on_cancel: Option<DropGuard>,
+    checkpoint: Option<CheckpointWriter>,
 }
 
 #[napi]
@@ -361,15 +1277,82 @@ impl EventStream {
     }
 
     /// Receive the next event response from the stream
-    #[napi]
-    pub async fn recv(&self) -> napi::Result<Option<EventResponse>> {
-        let resp = self.inner.lock().await.recv().await;
+    #[napi(ts_return_type = "Promise<EventResponse | null>")]
+    pub async fn recv(&self) -> ClientResult<Option<Chunked<EventResponse>>> {
+        Self::recv_impl(
+            self.inner.clone(),
+            self.encoding,
+            self.cancel.clone(),
+            self.checkpoint.clone(),
+        )
+        .await
+        .map(|resp| resp.map(Chunked::from))
+        .into()
+    }
 
-        resp.map(|r| {
-            convert_event_response(r?, self.enable_checksum_addresses).context("convert response")
-        })
-        .transpose()
-        .map_err(map_err)
+    async fn recv_impl(
+        inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<HSEventResponse>>>>,
+        encoding: Encoding,
+        cancel: Option<CancellationToken>,
+        checkpoint: Option<CheckpointWriter>,
+    ) -> Result<Option<EventResponse>> {
+        let mut rx = inner.lock().await;
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.commit().await.context("save checkpoint")?;
+        }
+
+        let resp = match recv_cancellable(&mut rx, cancel).await? {
+            Some(resp) => resp?,
+            None => return Ok(None),
+        };
+
+        let next_checkpoint = checkpoint
+            .as_ref()
+            .map(|_| Checkpoint::from_response(&resp))
+            .transpose()
+            .context("build checkpoint")?;
+
+        let resp = convert_event_response(resp, encoding)
+            .await
+            .context("convert response")
+            .with_code(ErrorCode::DecodeError)?;
+
+        // Only stage once the response can be delivered, otherwise resuming would skip it
+        if let (Some(checkpoint), Some(next)) = (&checkpoint, next_checkpoint) {
+            checkpoint.stage(next).await;
+        }
+        Ok(Some(resp))
+    }
+}
+
+#[napi]
+impl AsyncGenerator for EventStream {
+    type Yield = ClientResult<EventResponse>;
+    type Next = ();
+    type Return = ();
+
+    fn next(
+        &mut self,
+        _value: Option<Self::Next>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let recv = Self::recv_impl(
+            self.inner.clone(),
+            self.encoding,
+            self.cancel.clone(),
+            self.checkpoint.clone(),
+        );
+        async move { ClientResult::stream_item(recv.await) }
+    }
+
+    fn complete(
+        &mut self,
+        _value: Option<Self::Return>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move {
+            inner.lock().await.close();
+            Ok(None)
+        }
     }
 }
 
@@ -451,9 +1434,12 @@ fn try_into_height_stream_event(
 /// Stream for receiving height stream events
 /// yields the immediate height of the chain and then
 /// continues to yield height updates as they are received
-#[napi]
+///
+/// Can be consumed with `for await (const event of stream)`, breaking out of the
+/// loop closes the stream.
+#[napi(async_iterator)]
 pub struct HeightStream {
-    inner: tokio::sync::Mutex<mpsc::Receiver<hypersync_client::HeightStreamEvent>>,
+    inner: Arc<tokio::sync::Mutex<mpsc::Receiver<hypersync_client::HeightStreamEvent>>>,
 }
 
 #[napi]
@@ -465,11 +1451,42 @@ impl HeightStream {
     }
 
     /// Receive the next height stream event from the stream
-    #[napi]
-    pub async fn recv(&self) -> napi::Result<Option<HeightStreamEvent>> {
-        let resp = self.inner.lock().await.recv().await;
-        resp.map(|hs_height_event| try_into_height_stream_event(hs_height_event).map_err(map_err))
-            .transpose()
+    #[napi(ts_return_type = "Promise<HeightStreamEvent | null>")]
+    pub async fn recv(&self) -> ClientResult<Option<HeightStreamEvent>> {
+        Self::recv_impl(self.inner.clone()).await.into()
+    }
+
+    async fn recv_impl(
+        inner: Arc<tokio::sync::Mutex<mpsc::Receiver<hypersync_client::HeightStreamEvent>>>,
+    ) -> Result<Option<HeightStreamEvent>> {
+        let resp = inner.lock().await.recv().await;
+        resp.map(try_into_height_stream_event).transpose()
+    }
+}
+
+#[napi]
+impl AsyncGenerator for HeightStream {
+    type Yield = ClientResult<HeightStreamEvent>;
+    type Next = ();
+    type Return = ();
+
+    fn next(
+        &mut self,
+        _value: Option<Self::Next>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let recv = Self::recv_impl(self.inner.clone());
+        async move { ClientResult::stream_item(recv.await) }
+    }
+
+    fn complete(
+        &mut self,
+        _value: Option<Self::Return>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move {
+            inner.lock().await.close();
+            Ok(None)
+        }
     }
 }
 
@@ -537,15 +1554,129 @@ pub struct Events {
     pub rollback_guard: Option<RollbackGuard>,
 }
 
-fn convert_response(
+impl From<QueryResponse> for Chunked<QueryResponse> {
+    fn from(mut res: QueryResponse) -> Self {
+        let data = &mut res.data;
+        let rows = vec![
+            PendingRows::boxed(&["data", "blocks"], std::mem::take(&mut data.blocks)),
+            PendingRows::boxed(
+                &["data", "transactions"],
+                std::mem::take(&mut data.transactions),
+            ),
+            PendingRows::boxed(&["data", "logs"], std::mem::take(&mut data.logs)),
+            PendingRows::boxed(&["data", "traces"], std::mem::take(&mut data.traces)),
+        ];
+        Chunked::new(res, rows)
+    }
+}
+
+impl From<EventResponse> for Chunked<EventResponse> {
+    fn from(mut resp: EventResponse) -> Self {
+        let rows = vec![PendingRows::boxed(
+            &["data"],
+            std::mem::take(&mut resp.data),
+        )];
+        Chunked::new(resp, rows)
+    }
+}
+
+/// Convert a response on the blocking thread pool, so building the hex strings, checksums and
+/// bigints of large responses doesn't hold up the async runtime
+async fn convert_response(
+    res: hypersync_client::QueryResponse,
+    encoding: Encoding,
+) -> Result<QueryResponse> {
+    tokio::task::spawn_blocking(move || convert_response_sync(res, encoding))
+        .await
+        .context("join convert task")?
+}
+
+async fn convert_event_response(
+    resp: HSEventResponse,
+    encoding: Encoding,
+) -> Result<EventResponse> {
+    tokio::task::spawn_blocking(move || convert_event_response_sync(resp, encoding))
+        .await
+        .context("join convert task")?
+}
+
+/// Collect all responses of a stream, each response is converted while the stream fetches the
+/// next ones
+async fn collect_responses(
+    mut rx: mpsc::Receiver<Result<hypersync_client::QueryResponse>>,
+    encoding: Encoding,
+) -> Result<QueryResponse> {
+    let mut collected = QueryResponse {
+        archive_height: None,
+        next_block: 0,
+        total_execution_time: 0,
+        data: QueryResponseData {
+            blocks: Vec::new(),
+            transactions: Vec::new(),
+            logs: Vec::new(),
+            traces: Vec::new(),
+        },
+        rollback_guard: None,
+    };
+
+    while let Some(resp) = rx.recv().await {
+        let resp = resp.context("run inner collect")?;
+        let resp = convert_response(resp, encoding)
+            .await
+            .context("convert response")
+            .with_code(ErrorCode::DecodeError)?;
+
+        collected.archive_height = resp.archive_height;
+        collected.next_block = resp.next_block;
+        collected.total_execution_time += resp.total_execution_time;
+        collected.data.blocks.extend(resp.data.blocks);
+        collected.data.transactions.extend(resp.data.transactions);
+        collected.data.logs.extend(resp.data.logs);
+        collected.data.traces.extend(resp.data.traces);
+    }
+
+    Ok(collected)
+}
+
+/// Collect all responses of an event stream, each response is converted while the stream
+/// fetches the next ones
+async fn collect_event_responses(
+    mut rx: mpsc::Receiver<Result<HSEventResponse>>,
+    encoding: Encoding,
+) -> Result<EventResponse> {
+    let mut collected = EventResponse {
+        archive_height: None,
+        next_block: 0,
+        total_execution_time: 0,
+        data: Vec::new(),
+        rollback_guard: None,
+    };
+
+    while let Some(resp) = rx.recv().await {
+        let resp = resp.context("run inner collect")?;
+        let resp = convert_event_response(resp, encoding)
+            .await
+            .context("convert response")
+            .with_code(ErrorCode::DecodeError)?;
+
+        collected.archive_height = resp.archive_height;
+        collected.next_block = resp.next_block;
+        collected.total_execution_time += resp.total_execution_time;
+        collected.data.extend(resp.data);
+    }
+
+    Ok(collected)
+}
+
+fn convert_response_sync(
     res: hypersync_client::QueryResponse,
-    should_checksum: bool,
+    encoding: Encoding,
 ) -> Result<QueryResponse> {
     let blocks = res
         .data
         .blocks
         .iter()
-        .flat_map(|b| b.iter().map(|b| Block::from_simple(b, should_checksum)))
+        .flat_map(|b| b.iter().map(|b| Block::from_simple(b, encoding)))
         .collect::<Result<Vec<_>>>()
         .context("mapping blocks")?;
 
@@ -553,10 +1684,7 @@ fn convert_response(
         .data
         .transactions
         .iter()
-        .flat_map(|b| {
-            b.iter()
-                .map(|tx| Transaction::from_simple(tx, should_checksum))
-        })
+        .flat_map(|b| b.iter().map(|tx| Transaction::from_simple(tx, encoding)))
         .collect::<Result<Vec<_>>>()
         .context("mapping transactions")?;
 
@@ -564,7 +1692,7 @@ fn convert_response(
         .data
         .logs
         .iter()
-        .flat_map(|b| b.iter().map(|l| Log::from_simple(l, should_checksum)))
+        .flat_map(|b| b.iter().map(|l| Log::from_simple(l, encoding)))
         .collect::<Result<Vec<_>>>()
         .context("mapping logs")?;
 
@@ -572,7 +1700,7 @@ fn convert_response(
         .data
         .traces
         .iter()
-        .flat_map(|b| b.iter().map(|tr| Trace::from_simple(tr, should_checksum)))
+        .flat_map(|b| b.iter().map(|tr| Trace::from_simple(tr, encoding)))
         .collect::<Result<Vec<_>>>()
         .context("mapping traces")?;
 
@@ -601,10 +1729,7 @@ fn convert_response(
     })
 }
 
-fn convert_event_response(
-    resp: hypersync_client::QueryResponse<Vec<hypersync_client::simple_types::Event>>,
-    should_checksum: bool,
-) -> Result<EventResponse> {
+fn convert_event_response_sync(resp: HSEventResponse, encoding: Encoding) -> Result<EventResponse> {
     let data = resp
         .data
         .into_iter()
@@ -612,15 +1737,15 @@ fn convert_event_response(
             Ok(Event {
                 transaction: event
                     .transaction
-                    .map(|v| Transaction::from_simple(&v, should_checksum))
+                    .map(|v| Transaction::from_simple(&v, encoding))
                     .transpose()
                     .context("mapping transaction")?,
                 block: event
                     .block
-                    .map(|v| Block::from_simple(&v, should_checksum))
+                    .map(|v| Block::from_simple(&v, encoding))
                     .transpose()
                     .context("mapping block")?,
-                log: Log::from_simple(&event.log, should_checksum).context("mapping log")?,
+                log: Log::from_simple(&event.log, encoding).context("mapping log")?,
             })
         })
         .collect::<Result<Vec<_>>>()
@@ -644,7 +1769,3 @@ fn convert_event_response(
             .transpose()?,
     })
 }
-
-fn map_err(e: anyhow::Error) -> napi::Error {
-    napi::Error::from_reason(format!("{:?}", e))
-}
@@ -0,0 +1,175 @@
+use std::future::Future;
+
+use anyhow::{Context, Result};
+use hypersync_client::{net_types, Client, HeightStreamEvent};
+use tokio::sync::mpsc;
+
+use crate::{
+    config::StreamConfig,
+    error::{ErrorCode, WithCode},
+};
+
+/// Start a stream, following the chain head after catching up if `config.followHead` is set
+pub async fn stream(
+    client: &Client,
+    query: net_types::Query,
+    config: StreamConfig,
+) -> Result<mpsc::Receiver<Result<hypersync_client::QueryResponse>>> {
+    let follow_head = check_follow_head(&config)?;
+    let config = config.into();
+
+    if !follow_head {
+        return client.clone().stream(query, config).await;
+    }
+
+    let historical = client.clone().stream(query.clone(), config).await?;
+    Ok(follow(
+        client.clone(),
+        query,
+        historical,
+        |client, query| async move { client.get(&query).await },
+    ))
+}
+
+/// Start an event stream, following the chain head after catching up if `config.followHead` is set
+pub async fn stream_events(
+    client: &Client,
+    query: net_types::Query,
+    config: StreamConfig,
+) -> Result<mpsc::Receiver<Result<hypersync_client::EventResponse>>> {
+    let follow_head = check_follow_head(&config)?;
+    let config = config.into();
+
+    if !follow_head {
+        return client.clone().stream_events(query, config).await;
+    }
+
+    let historical = client.clone().stream_events(query.clone(), config).await?;
+    Ok(follow(
+        client.clone(),
+        query,
+        historical,
+        |client, query| async move { client.get_events(query).await },
+    ))
+}
+
+/// Start an arrow stream, `config.followHead` is not supported since the column mapping and hex
+/// output are only applied to the historical stream
+pub async fn stream_arrow(
+    client: &Client,
+    query: net_types::Query,
+    config: StreamConfig,
+) -> Result<mpsc::Receiver<Result<hypersync_client::ArrowResponse>>> {
+    if check_follow_head(&config)? {
+        return Err(anyhow::anyhow!(
+            "followHead is not supported for arrow streams"
+        ))
+        .with_code(ErrorCode::InvalidQuery);
+    }
+    client.clone().stream_arrow(query, config.into()).await
+}
+
+fn check_follow_head(config: &StreamConfig) -> Result<bool> {
+    let follow_head = config.follow_head.unwrap_or_default();
+    if follow_head && config.reverse.unwrap_or_default() {
+        return Err(anyhow::anyhow!(
+            "followHead is not supported for reverse streams"
+        ))
+        .with_code(ErrorCode::InvalidQuery);
+    }
+    Ok(follow_head)
+}
+
+/// Forward the historical stream, then keep querying new blocks whenever the height stream
+/// reports that the chain advanced.
+///
+/// Every query made at the head is forwarded, so consumers get a response without data as a
+/// heartbeat when the new blocks don't contain anything matching the query.
+fn follow<T, F, Fut>(
+    client: Client,
+    query: net_types::Query,
+    historical: mpsc::Receiver<Result<hypersync_client::QueryResponse<T>>>,
+    get: F,
+) -> mpsc::Receiver<Result<hypersync_client::QueryResponse<T>>>
+where
+    T: Send + 'static,
+    F: Fn(Client, net_types::Query) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<hypersync_client::QueryResponse<T>>> + Send,
+{
+    let (tx, rx) = mpsc::channel(historical.max_capacity());
+
+    tokio::spawn(async move {
+        if let Err(e) = run_follow(client, query, historical, get, &tx).await {
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    rx
+}
+
+async fn run_follow<T, F, Fut>(
+    client: Client,
+    mut query: net_types::Query,
+    mut historical: mpsc::Receiver<Result<hypersync_client::QueryResponse<T>>>,
+    get: F,
+    tx: &mpsc::Sender<Result<hypersync_client::QueryResponse<T>>>,
+) -> Result<()>
+where
+    F: Fn(Client, net_types::Query) -> Fut,
+    Fut: Future<Output = Result<hypersync_client::QueryResponse<T>>>,
+{
+    let mut next_block = query.from_block;
+
+    while let Some(resp) = historical.recv().await {
+        let resp = resp?;
+        next_block = resp.next_block;
+        if tx.send(Ok(resp)).await.is_err() {
+            return Ok(());
+        }
+    }
+
+    if query
+        .to_block
+        .is_some_and(|to_block| next_block >= to_block)
+    {
+        return Ok(());
+    }
+
+    let mut heights = client.stream_height();
+
+    loop {
+        let event = tokio::select! {
+            event = heights.recv() => event,
+            // Stop when the consumer is gone, otherwise we'd wait for the next block to notice
+            _ = tx.closed() => return Ok(()),
+        };
+        let height = match event.context("height stream stopped")? {
+            HeightStreamEvent::Height(height) => height,
+            HeightStreamEvent::Connected | HeightStreamEvent::Reconnecting { .. } => continue,
+        };
+
+        while next_block <= height {
+            query.from_block = next_block;
+            let resp = get(client.clone(), query.clone())
+                .await
+                .context("query new blocks")?;
+
+            // The server might not have indexed the announced height yet
+            let advanced = resp.next_block > next_block;
+            next_block = resp.next_block;
+            if tx.send(Ok(resp)).await.is_err() {
+                return Ok(());
+            }
+
+            if query
+                .to_block
+                .is_some_and(|to_block| next_block >= to_block)
+            {
+                return Ok(());
+            }
+            if !advanced {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use hypersync_client::net_types;
+use napi::bindgen_prelude::Either;
+
+use crate::{
+    query::{
+        BlockField, BlockFilter, FieldSelection, JoinMode, LogField, Query, TraceField,
+        TransactionField, TransactionFilter,
+    },
+    types::StringOrBuffer,
+};
+
+/// Number of requests a lookup makes before giving up on finding a match
+pub const MAX_LOOKUP_PAGES: usize = 100;
+
+/// Blocks to search in for single-entity lookups. Lookups by hash scan from `fromBlock` to
+/// `toBlock` until they find a match, so narrowing the range makes them a lot faster.
+/// Lookups fail if they don't find a match within 100 requests.
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct BlockHint {
+    /// Block to start searching from, inclusive. Default: 0
+    pub from_block: Option<i64>,
+    /// Block to stop searching at, exclusive. Default: the chain head
+    pub to_block: Option<i64>,
+}
+
+/// Query for the block with the given number
+pub fn block_by_number_query(number: i64) -> Query {
+    Query {
+        from_block: number,
+        to_block: Some(number.saturating_add(1)),
+        include_all_blocks: Some(true),
+        field_selection: FieldSelection {
+            block: Some(all_block_fields()),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Query for the block with the given hash
+pub fn block_by_hash_query(hash: String, hint: Option<BlockHint>) -> Query {
+    Query {
+        blocks: Some(vec![Either::B(BlockFilter {
+            hash: Some(vec![hash]),
+            miner: None,
+        })]),
+        field_selection: FieldSelection {
+            block: Some(all_block_fields()),
+            ..Default::default()
+        },
+        ..hinted_query(hint)
+    }
+}
+
+/// Query for the transaction with the given hash, `field_selection` picks the data returned
+/// along with it
+pub fn transaction_query(
+    hash: String,
+    hint: Option<BlockHint>,
+    field_selection: FieldSelection,
+) -> Query {
+    Query {
+        transactions: Some(vec![Either::B(TransactionFilter {
+            hash: Some(vec![hash]),
+            ..Default::default()
+        })]),
+        field_selection,
+        // joins the logs and traces of the transaction
+        join_mode: Some(JoinMode::JoinAll),
+        ..hinted_query(hint)
+    }
+}
+
+pub fn all_transaction_fields() -> FieldSelection {
+    FieldSelection {
+        transaction: Some(
+            net_types::TransactionField::all()
+                .into_iter()
+                .map(TransactionField::from)
+                .collect(),
+        ),
+        ..Default::default()
+    }
+}
+
+pub fn all_log_fields() -> FieldSelection {
+    FieldSelection {
+        log: Some(
+            net_types::LogField::all()
+                .into_iter()
+                .map(LogField::from)
+                .collect(),
+        ),
+        // matched by hash, so the query selects the transaction table
+        transaction: Some(vec![TransactionField::Hash]),
+        ..Default::default()
+    }
+}
+
+pub fn all_trace_fields() -> FieldSelection {
+    FieldSelection {
+        trace: Some(
+            net_types::TraceField::all()
+                .into_iter()
+                .map(TraceField::from)
+                .collect(),
+        ),
+        transaction: Some(vec![TransactionField::Hash]),
+        ..Default::default()
+    }
+}
+
+/// Whether a hash of a response matches the hex encoded hash, ignoring case
+pub fn same_hash(a: Option<&StringOrBuffer>, b: &str) -> bool {
+    a.is_some_and(|a| a.matches_hex(b))
+}
+
+fn all_block_fields() -> Vec<BlockField> {
+    net_types::BlockField::all()
+        .into_iter()
+        .map(BlockField::from)
+        .collect()
+}
+
+fn hinted_query(hint: Option<BlockHint>) -> Query {
+    let hint = hint.unwrap_or_default();
+    Query {
+        from_block: hint.from_block.unwrap_or_default(),
+        to_block: hint.to_block,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hypersync_client::format::{self, Hex};
+
+    use super::*;
+
+    const HASH: &str = "0x410eec15e380c6f23c2294ad714487b2300dd88a7eaa051835e0da07f16fc282";
+
+    #[test]
+    fn test_lookup_queries_convert() {
+        let hint = BlockHint {
+            from_block: Some(100),
+            to_block: Some(200),
+        };
+        let query = net_types::Query::try_from(transaction_query(
+            HASH.into(),
+            Some(hint),
+            all_log_fields(),
+        ))
+        .unwrap();
+        assert_eq!((query.from_block, query.to_block), (100, Some(200)));
+        assert_eq!(query.join_mode, net_types::JoinMode::JoinAll);
+        assert_eq!(query.field_selection.log, net_types::LogField::all());
+
+        let query = net_types::Query::try_from(block_by_hash_query(HASH.into(), None)).unwrap();
+        assert_eq!((query.from_block, query.to_block), (0, None));
+        assert_eq!(query.field_selection.block, net_types::BlockField::all());
+
+        let query = net_types::Query::try_from(block_by_number_query(5)).unwrap();
+        assert_eq!((query.from_block, query.to_block), (5, Some(6)));
+        assert!(query.include_all_blocks);
+
+        assert!(net_types::Query::try_from(block_by_hash_query("0x12".into(), None)).is_err());
+    }
+
+    #[test]
+    fn test_same_hash() {
+        let upper = StringOrBuffer::String(HASH.to_uppercase().replace("0X", "0x"));
+        assert!(same_hash(Some(&upper), HASH));
+        assert!(!same_hash(None, HASH));
+
+        let bytes = format::Data::decode_hex(HASH).unwrap();
+        let buffer = StringOrBuffer::Buffer(bytes.to_vec());
+        assert!(same_hash(Some(&buffer), HASH));
+        assert!(!same_hash(Some(&buffer), &HASH.replace("0x41", "0x42")));
+    }
+}
@@ -1,26 +1,39 @@
-use anyhow::Context;
+use std::collections::HashMap;
+
+use alloy_dyn_abi::{DynSolValue, Specifier};
+use alloy_json_abi::{Event, EventParam};
+use alloy_primitives::{keccak256, B256};
+use anyhow::{Context, Result};
 use hypersync_client::{
     format::{Address, Hex, LogArgument},
-    preset_query,
+    net_types, preset_query,
 };
+use napi::{bindgen_prelude::Either, Env};
 
-use crate::{map_err, query::Query};
+use crate::{
+    abi::parse_events,
+    error::{js_error, ErrorCode, WithCode},
+    query::{FieldSelection, LogField, LogFilter, Query},
+};
 
 /// Returns a query for all Blocks and Transactions within the block range (from_block, to_block]
 /// If to_block is None then query runs to the head of the chain.
 #[napi]
 pub fn preset_query_blocks_and_transactions(
+    env: Env,
     from_block: i64,
     to_block: Option<i64>,
 ) -> napi::Result<Query> {
     let from_block = from_block
         .try_into()
         .context("convert from_block")
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
     let to_block = to_block
         .map(|t| t.try_into().context("convert to_block"))
         .transpose()
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
 
     let query: Query = preset_query::blocks_and_transactions(from_block, to_block).into();
 
@@ -32,17 +45,20 @@ pub fn preset_query_blocks_and_transactions(
 /// so it can be mapped to a block.  If to_block is None then query runs to the head of the chain.
 #[napi]
 pub fn preset_query_blocks_and_transaction_hashes(
+    env: Env,
     from_block: i64,
     to_block: Option<i64>,
 ) -> napi::Result<Query> {
     let from_block = from_block
         .try_into()
         .context("convert from_block")
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
     let to_block = to_block
         .map(|t| t.try_into().context("convert to_block"))
         .transpose()
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
 
     let query: Query = preset_query::blocks_and_transaction_hashes(from_block, to_block).into();
 
@@ -53,22 +69,26 @@ pub fn preset_query_blocks_and_transaction_hashes(
 /// If to_block is None then query runs to the head of the chain.
 #[napi]
 pub fn preset_query_logs(
+    env: Env,
     contract_address: String,
     from_block: i64,
     to_block: Option<i64>,
 ) -> napi::Result<Query> {
     let address = Address::decode_hex(&contract_address)
         .context("parse address")
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
 
     let from_block = from_block
         .try_into()
         .context("convert from_block")
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
     let to_block = to_block
         .map(|t| t.try_into().context("convert to_block"))
         .transpose()
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
 
     Ok(preset_query::logs(from_block, to_block, address).into())
 }
@@ -78,6 +98,7 @@ pub fn preset_query_logs(
 /// If to_block is None then query runs to the head of the chain.
 #[napi]
 pub fn preset_query_logs_of_event(
+    env: Env,
     contract_address: String,
     topic0: String,
     from_block: i64,
@@ -85,20 +106,295 @@ pub fn preset_query_logs_of_event(
 ) -> napi::Result<Query> {
     let address = Address::decode_hex(&contract_address)
         .context("parse address")
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
     let topic0 = LogArgument::decode_hex(&topic0)
         .context("parse topic0")
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
 
     let from_block = from_block
         .try_into()
         .context("convert from_block")
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
     let to_block = to_block
         .map(|t| t.try_into().context("convert to_block"))
         .transpose()
-        .map_err(map_err)?;
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
 
     let query = preset_query::logs_of_event(from_block, to_block, topic0, address).into();
     Ok(query)
 }
+
+/// Events to query the logs of
+#[napi(object)]
+#[derive(Default)]
+pub struct EventQueryOptions {
+    /// Event signatures, e.g. `Transfer(address indexed from, address indexed to, uint256 value)`
+    pub signatures: Option<Vec<String>>,
+    /// JSON ABI as a JSON string or parsed JSON, either the plain ABI array or a
+    /// Hardhat/Foundry artifact that contains it
+    pub abi: Option<serde_json::Value>,
+    /// Addresses of the contracts, empty means match all
+    pub addresses: Option<Vec<String>>,
+    /// Values of indexed params keyed by param name, a log matches if the param equals any of
+    /// the values. Values are given as strings, e.g. `"0xab..."` for addresses or `"1000"` for
+    /// numbers. Every event has to have an indexed param with each of the names.
+    #[napi(ts_type = "Record<string, Array<string>>")]
+    pub indexed_args: Option<HashMap<String, Vec<String>>>,
+}
+
+/// Returns a query for the logs of the given events within the block range, with the topic
+/// filters computed from the event signatures and the fields needed to decode the logs selected.
+/// If to_block is None then query runs to the head of the chain.
+#[napi]
+pub fn preset_query_events(
+    env: Env,
+    events: EventQueryOptions,
+    from_block: i64,
+    to_block: Option<i64>,
+) -> napi::Result<Query> {
+    u64::try_from(from_block)
+        .context("convert from_block")
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
+    to_block
+        .map(|t| u64::try_from(t).context("convert to_block"))
+        .transpose()
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
+    let (logs, fields) = event_log_filters(events)
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
+
+    Ok(Query {
+        from_block,
+        to_block,
+        logs: Some(logs.into_iter().map(Either::B).collect()),
+        field_selection: FieldSelection {
+            log: Some(fields),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+/// Log filters matching the events and the log fields needed to decode them
+pub(crate) fn event_log_filters(
+    options: EventQueryOptions,
+) -> Result<(Vec<LogFilter>, Vec<LogField>)> {
+    let events = parse_events(&options.signatures.unwrap_or_default(), options.abi)?;
+    if events.is_empty() {
+        anyhow::bail!("no events to query");
+    }
+    let indexed_args = options.indexed_args.unwrap_or_default();
+    // an event without the param would match any value, so every event needs to have it
+    for name in indexed_args.keys() {
+        for event in &events {
+            let is_indexed = event
+                .inputs
+                .iter()
+                .any(|input| input.indexed && &input.name == name);
+            if !is_indexed {
+                anyhow::bail!("event {} has no indexed param named {}", event.name, name);
+            }
+        }
+    }
+
+    let filters = if indexed_args.is_empty() {
+        vec![LogFilter {
+            address: options.addresses.clone(),
+            topics: Some(vec![events
+                .iter()
+                .map(|event| event.selector().to_string())
+                .collect()]),
+        }]
+    } else {
+        events
+            .iter()
+            .map(|event| {
+                Ok(LogFilter {
+                    address: options.addresses.clone(),
+                    topics: Some(event_topics(event, &indexed_args)?),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    for filter in &filters {
+        net_types::LogFilter::try_from(filter.clone())?;
+    }
+
+    let num_topics = events.iter().map(Event::num_topics).max().unwrap_or(1);
+    let mut fields = vec![LogField::Data];
+    if options.addresses.is_some() {
+        fields.push(LogField::Address);
+    }
+    fields.extend(
+        [
+            LogField::Topic0,
+            LogField::Topic1,
+            LogField::Topic2,
+            LogField::Topic3,
+        ]
+        .into_iter()
+        .take(num_topics),
+    );
+
+    Ok((filters, fields))
+}
+
+/// Topic filters of the event, trailing topics that match anything are left out
+fn event_topics(
+    event: &Event,
+    indexed_args: &HashMap<String, Vec<String>>,
+) -> Result<Vec<Vec<String>>> {
+    let mut topics = vec![vec![event.selector().to_string()]];
+    for input in event.inputs.iter().filter(|input| input.indexed) {
+        let values = match indexed_args.get(&input.name) {
+            Some(values) => values
+                .iter()
+                .map(|value| {
+                    encode_topic(input, value)
+                        .map(|topic| topic.to_string())
+                        .with_context(|| format!("encode {} of {}", input.name, event.name))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        topics.push(values);
+    }
+
+    while topics.last().is_some_and(Vec::is_empty) {
+        topics.pop();
+    }
+    Ok(topics)
+}
+
+/// Topic of an indexed param, dynamic values are stored as their hash
+fn encode_topic(param: &EventParam, value: &str) -> Result<B256> {
+    let ty = param.resolve().context("resolve param type")?;
+    let value = ty
+        .coerce_str(value)
+        .with_context(|| format!("parse {} as {}", value, ty))?;
+
+    match value {
+        DynSolValue::String(value) => Ok(keccak256(value)),
+        DynSolValue::Bytes(value) => Ok(keccak256(value)),
+        value => value.as_word().with_context(|| {
+            format!(
+                "filtering on indexed params of type {} is not supported",
+                ty
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER: &str = "Transfer(address indexed from, address indexed to, uint256 value)";
+    const TRANSFER_TOPIC0: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    #[test]
+    fn test_event_log_filters() {
+        let (filters, fields) = event_log_filters(EventQueryOptions {
+            signatures: Some(vec![TRANSFER.into()]),
+            addresses: Some(vec!["0x6B175474E89094C44Da98b954EedeAC495271d0F".into()]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(filters.len(), 1);
+        assert_eq!(
+            filters[0].topics,
+            Some(vec![vec![TRANSFER_TOPIC0.to_owned()]])
+        );
+        assert_eq!(
+            fields,
+            vec![
+                LogField::Data,
+                LogField::Address,
+                LogField::Topic0,
+                LogField::Topic1,
+                LogField::Topic2
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_log_filters_indexed_args() {
+        let to = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        let mint = "Mint(address indexed to, uint256 amount)";
+        let (filters, _) = event_log_filters(EventQueryOptions {
+            signatures: Some(vec![TRANSFER.into(), mint.into()]),
+            indexed_args: Some(HashMap::from([("to".to_owned(), vec![to.to_owned()])])),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let to_topic = format!("0x{}{}", "0".repeat(24), &to[2..]);
+        assert_eq!(
+            filters[0].topics,
+            Some(vec![
+                vec![TRANSFER_TOPIC0.to_owned()],
+                vec![],
+                vec![to_topic.clone()],
+            ])
+        );
+        assert_eq!(
+            filters[1].topics,
+            Some(vec![
+                vec![keccak256("Mint(address,uint256)").to_string()],
+                vec![to_topic],
+            ])
+        );
+
+        let (filters, _) = event_log_filters(EventQueryOptions {
+            signatures: Some(vec!["Named(string indexed name)".into()]),
+            indexed_args: Some(HashMap::from([(
+                "name".to_owned(),
+                vec!["alice".to_owned()],
+            )])),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            filters[0].topics.as_ref().unwrap()[1],
+            vec![keccak256("alice").to_string()]
+        );
+    }
+
+    #[test]
+    fn test_event_log_filters_rejects_bad_args() {
+        let options = |name: &str, value: &str| EventQueryOptions {
+            signatures: Some(vec![TRANSFER.into()]),
+            indexed_args: Some(HashMap::from([(name.to_owned(), vec![value.to_owned()])])),
+            ..Default::default()
+        };
+
+        assert!(event_log_filters(options("value", "1")).is_err());
+        assert!(event_log_filters(options("to", "not an address")).is_err());
+        assert!(event_log_filters(EventQueryOptions::default()).is_err());
+
+        // Approval has no `to`, so its logs would all match
+        let err = event_log_filters(EventQueryOptions {
+            signatures: Some(vec![
+                TRANSFER.into(),
+                "Approval(address indexed owner, address indexed spender, uint256 value)".into(),
+            ]),
+            indexed_args: Some(HashMap::from([(
+                "to".to_owned(),
+                vec!["0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_owned()],
+            )])),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Approval has no indexed param named to"));
+    }
+}
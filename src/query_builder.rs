@@ -0,0 +1,282 @@
+use anyhow::{Context, Result};
+use hypersync_client::net_types;
+use napi::{
+    bindgen_prelude::{Either, This},
+    Env,
+};
+
+use crate::{
+    error::{js_error, ErrorCode, WithCode},
+    preset_query::{event_log_filters, EventQueryOptions},
+    query::{
+        FieldSelection, JoinMode, LogField, LogFilter, Query, TraceFilter, TraceSelection,
+        TransactionFilter, TransactionSelection,
+    },
+};
+
+/// Log filter with a field per topic, missing topics match anything
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct LogFilterArgs {
+    /// Addresses of the contracts, empty means match all
+    pub address: Option<Vec<String>>,
+    pub topic0: Option<Vec<String>>,
+    pub topic1: Option<Vec<String>>,
+    pub topic2: Option<Vec<String>>,
+    pub topic3: Option<Vec<String>>,
+}
+
+impl From<LogFilterArgs> for LogFilter {
+    fn from(args: LogFilterArgs) -> Self {
+        let mut topics = vec![args.topic0, args.topic1, args.topic2, args.topic3];
+        // the server matches topics by position, so only trailing topics can be left out
+        while topics.last().is_some_and(Option::is_none) {
+            topics.pop();
+        }
+
+        LogFilter {
+            address: args.address,
+            topics: Some(topics.into_iter().map(Option::unwrap_or_default).collect())
+                .filter(|topics: &Vec<_>| !topics.is_empty()),
+        }
+    }
+}
+
+/// Builder for queries, each method validates its input right away and throws an
+/// `InvalidQuery` error if it is invalid
+#[napi]
+#[derive(Default)]
+pub struct QueryBuilder {
+    query: Query,
+}
+
+#[napi]
+impl QueryBuilder {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block to start the query from, inclusive
+    #[napi(ts_return_type = "this")]
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_block<'a>(
+        &mut self,
+        env: Env,
+        this: This<'a>,
+        block: i64,
+    ) -> napi::Result<This<'a>> {
+        check_range(block, self.query.to_block)
+            .with_code(ErrorCode::InvalidQuery)
+            .map_err(js_error(&env))?;
+        self.query.from_block = block;
+        Ok(this)
+    }
+
+    /// Block to end the query at, exclusive
+    #[napi(ts_return_type = "this")]
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_block<'a>(&mut self, env: Env, this: This<'a>, block: i64) -> napi::Result<This<'a>> {
+        check_range(self.query.from_block, Some(block))
+            .with_code(ErrorCode::InvalidQuery)
+            .map_err(js_error(&env))?;
+        self.query.to_block = Some(block);
+        Ok(this)
+    }
+
+    /// Select logs matching the filter. Filters added with separate calls are or'ed together.
+    ///
+    /// If no log fields are selected when the query is built, the fields needed to decode
+    /// the logs are selected.
+    #[napi(ts_return_type = "this")]
+    pub fn add_log_filter<'a>(
+        &mut self,
+        env: Env,
+        this: This<'a>,
+        filter: LogFilterArgs,
+    ) -> napi::Result<This<'a>> {
+        let filter = LogFilter::from(filter);
+        net_types::LogFilter::try_from(filter.clone())
+            .context("invalid log filter")
+            .with_code(ErrorCode::InvalidQuery)
+            .map_err(js_error(&env))?;
+        self.query
+            .logs
+            .get_or_insert_with(Vec::new)
+            .push(Either::B(filter));
+        Ok(this)
+    }
+
+    /// Select the logs of events, see `presetQueryEvents`. Also selects the log fields needed
+    /// to decode them.
+    #[napi(ts_return_type = "this")]
+    pub fn add_event_filter<'a>(
+        &mut self,
+        env: Env,
+        this: This<'a>,
+        events: EventQueryOptions,
+    ) -> napi::Result<This<'a>> {
+        let (filters, fields) = event_log_filters(events)
+            .context("invalid event filter")
+            .with_code(ErrorCode::InvalidQuery)
+            .map_err(js_error(&env))?;
+        self.query
+            .logs
+            .get_or_insert_with(Vec::new)
+            .extend(filters.into_iter().map(Either::B));
+        merge_fields(&mut self.query.field_selection.log, Some(fields));
+        Ok(this)
+    }
+
+    /// Select transactions matching the filter or selection
+    #[napi(ts_return_type = "this")]
+    pub fn add_transaction_filter<'a>(
+        &mut self,
+        env: Env,
+        this: This<'a>,
+        filter: Either<TransactionSelection, TransactionFilter>,
+    ) -> napi::Result<This<'a>> {
+        match &filter {
+            Either::A(selection) => {
+                net_types::TransactionSelection::try_from(selection.clone()).map(drop)
+            }
+            Either::B(filter) => net_types::TransactionFilter::try_from(filter.clone()).map(drop),
+        }
+        .context("invalid transaction filter")
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
+        self.query
+            .transactions
+            .get_or_insert_with(Vec::new)
+            .push(filter);
+        Ok(this)
+    }
+
+    /// Select traces matching the filter or selection
+    #[napi(ts_return_type = "this")]
+    pub fn add_trace_filter<'a>(
+        &mut self,
+        env: Env,
+        this: This<'a>,
+        filter: Either<TraceSelection, TraceFilter>,
+    ) -> napi::Result<This<'a>> {
+        match &filter {
+            Either::A(selection) => {
+                net_types::TraceSelection::try_from(selection.clone()).map(drop)
+            }
+            Either::B(filter) => net_types::TraceFilter::try_from(filter.clone()).map(drop),
+        }
+        .context("invalid trace filter")
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
+        self.query.traces.get_or_insert_with(Vec::new).push(filter);
+        Ok(this)
+    }
+
+    /// Add fields to the field selection, fields selected by earlier calls are kept
+    #[napi(ts_return_type = "this")]
+    pub fn select_fields<'a>(&mut self, this: This<'a>, fields: FieldSelection) -> This<'a> {
+        let selection = &mut self.query.field_selection;
+        merge_fields(&mut selection.block, fields.block);
+        merge_fields(&mut selection.transaction, fields.transaction);
+        merge_fields(&mut selection.log, fields.log);
+        merge_fields(&mut selection.trace, fields.trace);
+        this
+    }
+
+    /// Select how the server joins related data, see `Query.joinMode`
+    #[napi(ts_return_type = "this")]
+    pub fn join_mode<'a>(&mut self, this: This<'a>, mode: JoinMode) -> This<'a> {
+        self.query.join_mode = Some(mode);
+        this
+    }
+
+    /// Build the query, the builder can be used again afterwards
+    #[napi]
+    pub fn build(&self, env: Env) -> napi::Result<Query> {
+        let mut query = self.query.clone();
+        if query.logs.is_some() && query.field_selection.log.is_none() {
+            query.field_selection.log = Some(DECODE_LOG_FIELDS.to_vec());
+        }
+
+        net_types::Query::try_from(query.clone())
+            .context("invalid query")
+            .with_code(ErrorCode::InvalidQuery)
+            .map_err(js_error(&env))?;
+        Ok(query)
+    }
+}
+
+/// Log fields needed to decode logs
+const DECODE_LOG_FIELDS: [LogField; 6] = [
+    LogField::Address,
+    LogField::Data,
+    LogField::Topic0,
+    LogField::Topic1,
+    LogField::Topic2,
+    LogField::Topic3,
+];
+
+fn check_range(from_block: i64, to_block: Option<i64>) -> Result<()> {
+    if from_block < 0 {
+        anyhow::bail!("from_block must not be negative, got {}", from_block);
+    }
+    if let Some(to_block) = to_block {
+        if to_block <= from_block {
+            anyhow::bail!(
+                "to_block ({}) must be greater than from_block ({})",
+                to_block,
+                from_block
+            );
+        }
+    }
+    Ok(())
+}
+
+fn merge_fields<T: Ord>(selected: &mut Option<Vec<T>>, fields: Option<Vec<T>>) {
+    let Some(fields) = fields else {
+        return;
+    };
+    let selected = selected.get_or_insert_with(Vec::new);
+    selected.extend(fields);
+    selected.sort();
+    selected.dedup();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_filter_args_pads_topics() {
+        let filter = LogFilter::from(LogFilterArgs {
+            topic2: Some(vec!["0x01".into()]),
+            ..Default::default()
+        });
+        assert_eq!(
+            filter.topics,
+            Some(vec![vec![], vec![], vec!["0x01".to_owned()]])
+        );
+
+        let filter = LogFilter::from(LogFilterArgs::default());
+        assert_eq!(filter.topics, None);
+    }
+
+    #[test]
+    fn test_check_range() {
+        assert!(check_range(0, None).is_ok());
+        assert!(check_range(10, Some(11)).is_ok());
+        assert!(check_range(-1, None).is_err());
+        assert!(check_range(10, Some(10)).is_err());
+    }
+
+    #[test]
+    fn test_merge_fields() {
+        let mut selected = Some(vec![LogField::Data]);
+        merge_fields(&mut selected, Some(vec![LogField::Topic0, LogField::Data]));
+        assert_eq!(selected, Some(vec![LogField::Data, LogField::Topic0]));
+
+        merge_fields(&mut selected, None);
+        assert_eq!(selected, Some(vec![LogField::Data, LogField::Topic0]));
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use hypersync_client::net_types::{self, hypersync_net_types_capnp, request::Request};
+use napi::{bindgen_prelude::Buffer, Env};
+
+use crate::{
+    error::{js_error, ErrorCode, WithCode},
+    query::Query,
+};
+
+/// Serialize a query to the JSON the client sends to the server
+#[napi]
+pub fn query_to_json(env: Env, query: Query, pretty: Option<bool>) -> napi::Result<String> {
+    let query = net_types::Query::try_from(query)
+        .context("parse query")
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))?;
+
+    let json = if pretty.unwrap_or_default() {
+        serde_json::to_string_pretty(&query)
+    } else {
+        serde_json::to_string(&query)
+    };
+    json.context("serialize query")
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))
+}
+
+/// Parse a query from the JSON the client sends to the server
+#[napi]
+pub fn query_from_json(env: Env, json: String) -> napi::Result<Query> {
+    serde_json::from_str::<net_types::Query>(&json)
+        .map(Query::from)
+        .context("parse query json")
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))
+}
+
+/// Serialize a query to the Cap'n Proto message the client sends to the server
+#[napi]
+pub fn query_to_capnp(env: Env, query: Query) -> napi::Result<Buffer> {
+    net_types::Query::try_from(query)
+        .context("parse query")
+        .and_then(|query| to_capnp(&query))
+        .map(Buffer::from)
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))
+}
+
+/// Parse a query from the Cap'n Proto message the client sends to the server
+#[napi]
+pub fn query_from_capnp(env: Env, bytes: Buffer) -> napi::Result<Query> {
+    from_capnp(&bytes)
+        .map(Query::from)
+        .with_code(ErrorCode::InvalidQuery)
+        .map_err(js_error(&env))
+}
+
+fn to_capnp(query: &net_types::Query) -> Result<Vec<u8>> {
+    let mut message = capnp::message::Builder::new_default();
+    let mut request = message.init_root::<hypersync_net_types_capnp::request::Builder>();
+    request
+        .build_full_query_from_query(query, false)
+        .context("build capnp query")?;
+
+    let mut bytes = Vec::new();
+    capnp::serialize_packed::write_message(&mut bytes, &message).context("write capnp message")?;
+    Ok(bytes)
+}
+
+fn from_capnp(bytes: &[u8]) -> Result<net_types::Query> {
+    match Request::from_capnp_bytes(bytes).context("read capnp message")? {
+        Request::QueryBody { query, .. } => Ok(*query),
+        Request::QueryId { .. } => {
+            anyhow::bail!("message only holds the id of a cached query, not the query itself")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = r#"{
+        "from_block": 100,
+        "to_block": 200,
+        "logs": [{
+            "address": ["0xdac17f958d2ee523a2206206994597c13d831ec7"],
+            "topics": [["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"]]
+        }],
+        "field_selection": {"log": ["data", "topic0"]}
+    }"#;
+
+    #[test]
+    fn test_capnp_roundtrip() {
+        let query: net_types::Query = serde_json::from_str(QUERY).unwrap();
+        let bytes = to_capnp(&query).unwrap();
+        assert_eq!(from_capnp(&bytes).unwrap(), query);
+        assert!(from_capnp(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_json_roundtrip_through_js_query() {
+        let query: net_types::Query = serde_json::from_str(QUERY).unwrap();
+        let converted = net_types::Query::try_from(Query::from(query.clone())).unwrap();
+        assert_eq!(converted, query);
+    }
+}
@@ -0,0 +1,423 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::{Context, Result};
+use hypersync_client::{
+    format::{Hash, Hex},
+    net_types, Client,
+};
+use napi::bindgen_prelude::{AsyncGenerator, Either};
+use tokio::sync::mpsc;
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use crate::{
+    cancel::{close_on_cancel, recv_cancellable},
+    config::StreamConfig,
+    convert_response,
+    error::{ClientResult, ErrorCode, WithCode},
+    live,
+    types::Encoding,
+    QueryResponse,
+};
+
+/// Number of block hashes kept around to find the fork point of a rollback
+pub const MAX_TRACKED_BLOCKS: usize = 256;
+
+#[napi(string_enum)]
+pub enum DataTag {
+    Data,
+}
+
+#[napi(object)]
+pub struct ReorgStreamDataEvent {
+    #[napi(js_name = "type")]
+    pub type_: DataTag,
+    pub response: QueryResponse,
+}
+
+#[napi(string_enum)]
+pub enum RollbackTag {
+    Rollback,
+}
+
+#[napi(object)]
+pub struct ReorgStreamRollbackEvent {
+    #[napi(js_name = "type")]
+    pub type_: RollbackTag,
+    /// First block that was rolled back, the stream continues from this block
+    pub from_block: i64,
+    /// Last block that was delivered before the rollback was detected
+    pub to_block: i64,
+}
+
+#[napi]
+/// Reorg stream event, switch on 'event.type' to get different payload options
+///
+/// switch (event.type) {
+///   case "Data":
+///     console.log("Next block:", event.response.nextBlock);
+///     break;
+///   case "Rollback":
+///     console.log("Revert blocks", event.fromBlock, "to", event.toBlock);
+///     break;
+/// }
+pub type ReorgStreamEvent = Either<ReorgStreamDataEvent, ReorgStreamRollbackEvent>;
+
+enum ReorgEvent {
+    Data(hypersync_client::QueryResponse),
+    Rollback { from_block: u64, to_block: u64 },
+}
+
+async fn convert_reorg_event(event: ReorgEvent, encoding: Encoding) -> Result<ReorgStreamEvent> {
+    let event = match event {
+        ReorgEvent::Data(resp) => Either::A(ReorgStreamDataEvent {
+            type_: DataTag::Data,
+            response: convert_response(resp, encoding)
+                .await
+                .context("convert response")
+                .with_code(ErrorCode::DecodeError)?,
+        }),
+        ReorgEvent::Rollback {
+            from_block,
+            to_block,
+        } => Either::B(ReorgStreamRollbackEvent {
+            type_: RollbackTag::Rollback,
+            from_block: from_block.try_into().context("convert from_block")?,
+            to_block: to_block.try_into().context("convert to_block")?,
+        }),
+    };
+    Ok(event)
+}
+
+/// Stream of data and rollback events
+///
+/// A `Rollback` event means the blocks in `fromBlock..=toBlock` that were delivered before are no
+/// longer part of the chain. State derived from them should be reverted, the stream then
+/// continues with the new data starting at `fromBlock`.
+///
+/// Can be consumed with `for await (const event of stream)`, breaking out of the
+/// loop closes the stream.
+#[napi(async_iterator)]
+pub struct ReorgStream {
+    inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<ReorgEvent>>>>,
+    encoding: Encoding,
+    cancel: Option<CancellationToken>,
+    /// Stops the cancellation watcher when the stream is dropped
+    _close_on_cancel: Option<DropGuard>,
+}
+
+#[napi]
+impl ReorgStream {
+    /// Close the stream
+    #[napi]
+    pub async fn close(&self) {
+        self.inner.lock().await.close();
+    }
+
+    /// Receive the next event, returns null when the stream is finished
+    #[napi(ts_return_type = "Promise<ReorgStreamEvent | null>")]
+    pub async fn recv(&self) -> ClientResult<Option<ReorgStreamEvent>> {
+        Self::recv_impl(self.inner.clone(), self.encoding, self.cancel.clone())
+            .await
+            .into()
+    }
+
+    async fn recv_impl(
+        inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<ReorgEvent>>>>,
+        encoding: Encoding,
+        cancel: Option<CancellationToken>,
+    ) -> Result<Option<ReorgStreamEvent>> {
+        let event = recv_cancellable(&mut *inner.lock().await, cancel).await?;
+
+        match event {
+            Some(event) => convert_reorg_event(event?, encoding).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[napi]
+impl AsyncGenerator for ReorgStream {
+    type Yield = ClientResult<ReorgStreamEvent>;
+    type Next = ();
+    type Return = ();
+
+    fn next(
+        &mut self,
+        _value: Option<Self::Next>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let recv = Self::recv_impl(self.inner.clone(), self.encoding, self.cancel.clone());
+        async move { ClientResult::stream_item(recv.await) }
+    }
+
+    fn complete(
+        &mut self,
+        _value: Option<Self::Return>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move {
+            inner.lock().await.close();
+            Ok(None)
+        }
+    }
+}
+
+impl ReorgStream {
+    pub async fn start(
+        client: &Client,
+        query: net_types::Query,
+        config: StreamConfig,
+        encoding: Encoding,
+        cancel: Option<CancellationToken>,
+    ) -> Result<Self> {
+        if config.reverse.unwrap_or_default() {
+            return Err(anyhow::anyhow!(
+                "reorg detection is not supported for reverse streams"
+            ))
+            .with_code(ErrorCode::InvalidQuery);
+        }
+
+        let rx = live::stream(client, query.clone(), config.clone()).await?;
+        let (tx, event_rx) = mpsc::channel(rx.max_capacity());
+        let client = client.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = run(client, query, config, rx, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        let inner = Arc::new(tokio::sync::Mutex::new(event_rx));
+
+        Ok(Self {
+            _close_on_cancel: cancel.as_ref().map(|token| close_on_cancel(token, &inner)),
+            inner,
+            encoding,
+            cancel,
+        })
+    }
+}
+
+/// Forward responses while checking that each one chains onto the blocks delivered before.
+///
+/// On a mismatch, the last tracked block that is still part of the chain is looked up, a rollback
+/// event is sent and the stream is restarted right after that block.
+async fn run(
+    client: Client,
+    mut query: net_types::Query,
+    config: StreamConfig,
+    mut rx: mpsc::Receiver<Result<hypersync_client::QueryResponse>>,
+    tx: &mpsc::Sender<Result<ReorgEvent>>,
+) -> Result<()> {
+    let mut tracker = HashTracker::default();
+
+    while let Some(resp) = rx.recv().await {
+        let resp = resp?;
+
+        if let Some(guard) = &resp.rollback_guard {
+            if !tracker.chains_onto(guard) {
+                let from_block = tracker
+                    .find_fork_point(&client)
+                    .await
+                    .context("find fork point")?;
+                let to_block = tracker.last_block().unwrap_or(from_block);
+
+                if tx
+                    .send(Ok(ReorgEvent::Rollback {
+                        from_block,
+                        to_block,
+                    }))
+                    .await
+                    .is_err()
+                {
+                    return Ok(());
+                }
+
+                tracker.truncate(from_block);
+                query.from_block = from_block;
+                rx = live::stream(&client, query.clone(), config.clone())
+                    .await
+                    .context("restart stream after rollback")?;
+                continue;
+            }
+
+            tracker.insert(guard.block_number, guard.hash.encode_hex());
+        }
+
+        if tx.send(Ok(ReorgEvent::Data(resp))).await.is_err() {
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+/// Hashes of recently delivered blocks, keyed by block number
+#[derive(Default)]
+pub struct HashTracker {
+    hashes: BTreeMap<u64, String>,
+}
+
+impl HashTracker {
+    pub fn insert(&mut self, block_number: u64, hash: String) {
+        self.hashes.insert(block_number, hash);
+        while self.hashes.len() > MAX_TRACKED_BLOCKS {
+            self.hashes.pop_first();
+        }
+    }
+
+    pub fn last_block(&self) -> Option<u64> {
+        self.hashes.last_key_value().map(|(&number, _)| number)
+    }
+
+    /// Whether the parent of the first block in the guard matches what was delivered before.
+    /// Returns true if the parent block wasn't tracked, since there is nothing to compare.
+    pub fn chains_onto(&self, guard: &net_types::RollbackGuard) -> bool {
+        let parent = match guard.first_block_number.checked_sub(1) {
+            Some(parent) => parent,
+            None => return true,
+        };
+
+        match self.hashes.get(&parent) {
+            Some(hash) => *hash == guard.first_parent_hash.encode_hex(),
+            None => true,
+        }
+    }
+
+    /// Find the first block that has to be rolled back, the one after the last tracked block that
+    /// is still part of the chain.
+    pub async fn find_fork_point(&self, client: &Client) -> Result<u64> {
+        let canonical = self
+            .get_canonical(client)
+            .await
+            .context("get tracked blocks from chain")?;
+        self.fork_point(&canonical)
+    }
+
+    /// Get the tracked blocks that are still part of the chain, using a single query over the
+    /// tracked range that filters by the tracked hashes.
+    async fn get_canonical(&self, client: &Client) -> Result<BTreeMap<u64, String>> {
+        let (Some(&first), Some(last)) = (self.hashes.keys().next(), self.last_block()) else {
+            return Ok(BTreeMap::new());
+        };
+
+        let hashes = self
+            .hashes
+            .values()
+            .map(|hash| Hash::decode_hex(hash).context("decode tracked hash"))
+            .collect::<Result<Vec<_>>>()?;
+        let mut query = net_types::Query {
+            from_block: first,
+            to_block: Some(last + 1),
+            blocks: vec![net_types::BlockFilter {
+                hash: hashes,
+                miner: Vec::new(),
+            }
+            .into()],
+            field_selection: net_types::FieldSelection {
+                block: [net_types::BlockField::Number, net_types::BlockField::Hash]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut canonical = BTreeMap::new();
+        loop {
+            let res = client.get(&query).await.context("run block query")?;
+            for block in res.data.blocks.iter().flatten() {
+                if let (Some(number), Some(hash)) = (block.number, &block.hash) {
+                    canonical.insert(number, hash.encode_hex());
+                }
+            }
+
+            // the server can stop short of the range, blocks past its head aren't canonical
+            if res.next_block > last || res.next_block <= query.from_block {
+                return Ok(canonical);
+            }
+            query.from_block = res.next_block;
+        }
+    }
+
+    /// First block to roll back given the tracked blocks that are still part of the chain
+    fn fork_point(&self, canonical: &BTreeMap<u64, String>) -> Result<u64> {
+        self.hashes
+            .iter()
+            .rev()
+            .find(|&(number, hash)| canonical.get(number) == Some(hash))
+            .map(|(&number, _)| number + 1)
+            .with_context(|| {
+                format!(
+                    "rollback is deeper than the last {} tracked blocks",
+                    self.hashes.len()
+                )
+            })
+    }
+
+    /// Forget blocks starting from `from_block`
+    pub fn truncate(&mut self, from_block: u64) {
+        self.hashes.split_off(&from_block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(byte: u8) -> String {
+        hypersync_client::format::Hash::from([byte; 32]).encode_hex()
+    }
+
+    fn guard(first_block_number: u64, first_parent: u8) -> net_types::RollbackGuard {
+        net_types::RollbackGuard {
+            block_number: first_block_number + 10,
+            timestamp: 0,
+            hash: [0xff; 32].into(),
+            first_block_number,
+            first_parent_hash: [first_parent; 32].into(),
+        }
+    }
+
+    #[test]
+    fn test_hash_tracker_chains_onto() {
+        let mut tracker = HashTracker::default();
+        assert!(tracker.chains_onto(&guard(100, 1)));
+
+        tracker.insert(99, hash(1));
+        assert!(tracker.chains_onto(&guard(100, 1)));
+        assert!(!tracker.chains_onto(&guard(100, 2)));
+        // parent not tracked
+        assert!(tracker.chains_onto(&guard(200, 2)));
+    }
+
+    #[test]
+    fn test_hash_tracker_truncate_and_limit() {
+        let mut tracker = HashTracker::default();
+        for number in 0..(MAX_TRACKED_BLOCKS as u64 + 10) {
+            tracker.insert(number, hash(1));
+        }
+        assert_eq!(tracker.hashes.len(), MAX_TRACKED_BLOCKS);
+        assert_eq!(tracker.hashes.first_key_value().map(|(&n, _)| n), Some(10));
+
+        tracker.truncate(100);
+        assert_eq!(tracker.last_block(), Some(99));
+    }
+
+    #[test]
+    fn test_hash_tracker_fork_point() {
+        let mut tracker = HashTracker::default();
+        tracker.insert(10, hash(1));
+        tracker.insert(20, hash(2));
+        tracker.insert(30, hash(3));
+
+        let canonical = [(10, hash(1)), (20, hash(2))].into_iter().collect();
+        assert_eq!(tracker.fork_point(&canonical).unwrap(), 21);
+
+        // a block with the same number but a different hash was replaced
+        let canonical = [(10, hash(1)), (20, hash(9))].into_iter().collect();
+        assert_eq!(tracker.fork_point(&canonical).unwrap(), 11);
+
+        let err = tracker.fork_point(&BTreeMap::new()).unwrap_err();
+        assert!(err.to_string().contains("deeper than the last 3"));
+    }
+}
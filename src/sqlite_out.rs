@@ -0,0 +1,702 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context, Result};
+use arrow::{
+    array::{Array, ArrayRef, AsArray, PrimitiveArray, RecordBatch},
+    datatypes::{
+        ArrowNativeType, ArrowPrimitiveType, DataType, Field, Float32Type, Float64Type, Int16Type,
+        Int32Type, Int64Type, Int8Type, Schema, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+    },
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use hypersync_client::{format::Hex, net_types, Client};
+use napi::bindgen_prelude::AsyncGenerator;
+use rusqlite::{params_from_iter, types::Value, Connection, OptionalExtension, Transaction};
+use tokio::sync::mpsc;
+
+use crate::{
+    config::StreamConfig,
+    error::{ClientResult, ErrorCode, WithCode},
+    live,
+    reorg::{HashTracker, MAX_TRACKED_BLOCKS},
+};
+
+/// Data tables and the column holding the block number of their rows
+const TABLES: [(&str, &str); 5] = [
+    ("blocks", "number"),
+    ("transactions", "block_number"),
+    ("logs", "block_number"),
+    ("traces", "block_number"),
+    ("decoded_logs", "block_number"),
+];
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS _hypersync_progress (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        next_block INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS _hypersync_block_hashes (
+        number INTEGER PRIMARY KEY,
+        hash TEXT NOT NULL
+    );
+";
+
+/// Progress of a `streamSqlite` stream
+#[napi(object)]
+pub struct SqliteProgress {
+    /// Block the database holds data up to, exclusive
+    pub next_block: i64,
+    /// Set if rows starting from this block were deleted because the chain was rolled back
+    pub rolled_back_from: Option<i64>,
+}
+
+/// Stream of progress events of writing to a SQLite database, see `streamSqlite`
+///
+/// Every event is sent after its changes are committed. Breaking out of the loop stops
+/// writing after the batch in progress.
+#[napi(async_iterator)]
+pub struct SqliteStream {
+    inner: Arc<tokio::sync::Mutex<mpsc::Receiver<Result<SqliteProgress>>>>,
+}
+
+#[napi]
+impl SqliteStream {
+    /// Close the stream
+    #[napi]
+    pub async fn close(&self) {
+        self.inner.lock().await.close();
+    }
+
+    /// Receive the next progress event, returns null when the stream is finished
+    #[napi(ts_return_type = "Promise<SqliteProgress | null>")]
+    pub async fn recv(&self) -> ClientResult<Option<SqliteProgress>> {
+        self.inner.lock().await.recv().await.transpose().into()
+    }
+}
+
+#[napi]
+impl AsyncGenerator for SqliteStream {
+    type Yield = ClientResult<SqliteProgress>;
+    type Next = ();
+    type Return = ();
+
+    fn next(
+        &mut self,
+        _value: Option<Self::Next>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move { ClientResult::stream_item(inner.lock().await.recv().await.transpose()) }
+    }
+
+    fn complete(
+        &mut self,
+        _value: Option<Self::Return>,
+    ) -> impl std::future::Future<Output = napi::Result<Option<Self::Yield>>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move {
+            inner.lock().await.close();
+            Ok(None)
+        }
+    }
+}
+
+impl SqliteStream {
+    pub fn start(
+        client: &Client,
+        path: String,
+        query: net_types::Query,
+        config: StreamConfig,
+    ) -> Result<Self> {
+        check_config(&config)?;
+        let (tx, rx) = mpsc::channel(1);
+        let client = client.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = run(&client, path, query, config, Some(&tx)).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        Ok(Self {
+            inner: Arc::new(tokio::sync::Mutex::new(rx)),
+        })
+    }
+}
+
+/// Stream the query into the SQLite database at `path`, resuming from where the last run
+/// stopped. Rows of blocks that are no longer part of the chain are deleted, both when
+/// resuming and when a response doesn't chain onto the blocks written before.
+pub async fn run(
+    client: &Client,
+    path: String,
+    mut query: net_types::Query,
+    config: StreamConfig,
+    progress: Option<&mpsc::Sender<Result<SqliteProgress>>>,
+) -> Result<()> {
+    check_config(&config)?;
+    select_block_numbers(&mut query);
+
+    let mut sink = blocking(move || SqliteSink::open(PathBuf::from(path))).await?;
+
+    // the chain might have been rolled back since the last run
+    if let Some(last_block) = sink.tracker.last_block() {
+        let fork = sink
+            .tracker
+            .find_fork_point(client)
+            .await
+            .context("check stored blocks against the chain")?;
+        if fork <= last_block {
+            sink = blocking(move || sink.rollback(fork).map(|()| sink)).await?;
+            if !send(progress, fork, Some(fork)).await? {
+                return Ok(());
+            }
+        }
+    }
+    if let Some(next_block) = sink.next_block()? {
+        query.from_block = query.from_block.max(next_block);
+    }
+
+    let mut rx = live::stream_arrow(client, query.clone(), config.clone())
+        .await
+        .context("start stream")?;
+    while let Some(resp) = rx.recv().await {
+        let resp = resp.context("get query response")?;
+
+        if let Some(guard) = &resp.rollback_guard {
+            if !sink.tracker.chains_onto(guard) {
+                let fork = sink
+                    .tracker
+                    .find_fork_point(client)
+                    .await
+                    .context("find fork point")?;
+                sink = blocking(move || sink.rollback(fork).map(|()| sink)).await?;
+                if !send(progress, fork, Some(fork)).await? {
+                    return Ok(());
+                }
+
+                query.from_block = fork;
+                rx = live::stream_arrow(client, query.clone(), config.clone())
+                    .await
+                    .context("restart stream after rollback")?;
+                continue;
+            }
+        }
+
+        let next_block = resp.next_block;
+        sink = blocking(move || sink.write(resp).map(|()| sink)).await?;
+        if !send(progress, next_block, None).await? {
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+fn check_config(config: &StreamConfig) -> Result<()> {
+    if config.reverse.unwrap_or_default() {
+        return Err(anyhow::anyhow!(
+            "writing to sqlite is not supported for reverse streams"
+        ))
+        .with_code(ErrorCode::InvalidQuery);
+    }
+    if config.follow_head.unwrap_or_default() {
+        return Err(anyhow::anyhow!(
+            "followHead is not supported when writing to sqlite"
+        ))
+        .with_code(ErrorCode::InvalidQuery);
+    }
+    Ok(())
+}
+
+/// Send a progress event, returns false if the receiver is gone
+async fn send(
+    progress: Option<&mpsc::Sender<Result<SqliteProgress>>>,
+    next_block: u64,
+    rolled_back_from: Option<u64>,
+) -> Result<bool> {
+    let Some(progress) = progress else {
+        return Ok(true);
+    };
+    let event = SqliteProgress {
+        next_block: next_block.try_into().context("convert next_block")?,
+        rolled_back_from: rolled_back_from
+            .map(i64::try_from)
+            .transpose()
+            .context("convert rolled_back_from")?,
+    };
+    Ok(progress.send(Ok(event)).await.is_ok())
+}
+
+/// Run database work off the async runtime
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .context("join sqlite task")?
+}
+
+/// Select the block number of every selected table, rows are rolled back by block number.
+/// The log index is selected as well to join decoded logs with logs.
+fn select_block_numbers(query: &mut net_types::Query) {
+    let fields = &mut query.field_selection;
+    if !fields.block.is_empty() {
+        fields.block.insert(net_types::BlockField::Number);
+    }
+    if !fields.transaction.is_empty() {
+        fields
+            .transaction
+            .insert(net_types::TransactionField::BlockNumber);
+    }
+    if !fields.log.is_empty() {
+        fields.log.insert(net_types::LogField::BlockNumber);
+        fields.log.insert(net_types::LogField::LogIndex);
+    }
+    if !fields.trace.is_empty() {
+        fields.trace.insert(net_types::TraceField::BlockNumber);
+    }
+}
+
+struct SqliteSink {
+    conn: Connection,
+    /// Hashes of the last written blocks, also stored in the database
+    tracker: HashTracker,
+}
+
+impl SqliteSink {
+    fn open(path: PathBuf) -> Result<Self> {
+        let conn = Connection::open(&path)
+            .with_context(|| format!("open sqlite database {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .context("create progress tables")?;
+
+        let mut tracker = HashTracker::default();
+        let mut stmt = conn
+            .prepare("SELECT number, hash FROM _hypersync_block_hashes ORDER BY number")
+            .context("prepare block hash query")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .context("read block hashes")?;
+        for row in rows {
+            let (number, hash) = row.context("read block hash")?;
+            tracker.insert(number.try_into().context("convert block number")?, hash);
+        }
+        drop(stmt);
+
+        Ok(Self { conn, tracker })
+    }
+
+    fn next_block(&self) -> Result<Option<u64>> {
+        self.conn
+            .query_row(
+                "SELECT next_block FROM _hypersync_progress WHERE id = 0",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()
+            .context("read next block")?
+            .map(|n| u64::try_from(n).context("convert next block"))
+            .transpose()
+    }
+
+    /// Insert the data of a response and advance the progress, in one transaction
+    fn write(&mut self, resp: hypersync_client::ArrowResponse) -> Result<()> {
+        let data = resp.data;
+        let decoded_logs = data
+            .decoded_logs
+            .iter()
+            .zip(&data.logs)
+            .map(|(decoded, logs)| with_log_position(decoded, logs))
+            .collect::<Result<Vec<_>>>()
+            .context("add log position to decoded logs")?;
+
+        let tx = self.conn.transaction().context("begin transaction")?;
+        for ((table, _), batches) in TABLES.iter().zip([
+            &data.blocks,
+            &data.transactions,
+            &data.logs,
+            &data.traces,
+            &decoded_logs,
+        ]) {
+            for batch in batches.iter().filter(|batch| batch.num_rows() > 0) {
+                insert_batch(&tx, table, batch).with_context(|| format!("insert {}", table))?;
+            }
+        }
+
+        set_next_block(&tx, resp.next_block)?;
+        let guard = resp.rollback_guard.map(|guard| {
+            let hash = guard.hash.encode_hex();
+            (guard.block_number, hash)
+        });
+        if let Some((number, hash)) = &guard {
+            let number = i64::try_from(*number).context("convert block number")?;
+            tx.execute(
+                "INSERT OR REPLACE INTO _hypersync_block_hashes (number, hash) VALUES (?1, ?2)",
+                (number, hash),
+            )
+            .context("save block hash")?;
+            tx.execute(
+                "DELETE FROM _hypersync_block_hashes WHERE number <= ?1",
+                [number - MAX_TRACKED_BLOCKS as i64],
+            )
+            .context("prune block hashes")?;
+        }
+        tx.commit().context("commit transaction")?;
+
+        if let Some((number, hash)) = guard {
+            self.tracker.insert(number, hash);
+        }
+        Ok(())
+    }
+
+    /// Delete rows starting from `from_block` and continue from there, in one transaction
+    fn rollback(&mut self, from_block: u64) -> Result<()> {
+        let from = i64::try_from(from_block).context("convert from_block")?;
+
+        let tx = self.conn.transaction().context("begin transaction")?;
+        for (table, column) in TABLES {
+            if !table_exists(&tx, table)? {
+                continue;
+            }
+            tx.execute(
+                &format!("DELETE FROM {} WHERE {} >= ?1", quote(table), quote(column)),
+                [from],
+            )
+            .with_context(|| format!("delete rolled back {}", table))?;
+        }
+        tx.execute(
+            "DELETE FROM _hypersync_block_hashes WHERE number >= ?1",
+            [from],
+        )
+        .context("delete rolled back block hashes")?;
+        set_next_block(&tx, from_block)?;
+        tx.commit().context("commit transaction")?;
+
+        self.tracker.truncate(from_block);
+        Ok(())
+    }
+}
+
+fn set_next_block(tx: &Transaction, next_block: u64) -> Result<()> {
+    let next_block = i64::try_from(next_block).context("convert next_block")?;
+    tx.execute(
+        "INSERT INTO _hypersync_progress (id, next_block) VALUES (0, ?1)
+        ON CONFLICT (id) DO UPDATE SET next_block = excluded.next_block",
+        [next_block],
+    )
+    .context("save next block")?;
+    Ok(())
+}
+
+fn table_exists(tx: &Transaction, table: &str) -> Result<bool> {
+    tx.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|row| row.is_some())
+    .context("check if table exists")
+}
+
+/// Insert the rows of the batch, creating the table from its schema if needed
+fn insert_batch(tx: &Transaction, table: &str, batch: &RecordBatch) -> Result<()> {
+    let schema = batch.schema();
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| format!("{} {}", quote(field.name()), sql_type(field.data_type())))
+        .collect::<Vec<_>>();
+    tx.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            quote(table),
+            columns.join(", ")
+        ),
+        [],
+    )
+    .context("create table")?;
+    if let Some((_, column)) = TABLES.iter().find(|(name, _)| *name == table) {
+        if schema.column_with_name(column).is_some() {
+            tx.execute(
+                &format!(
+                    "CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+                    quote(&format!("{}_{}", table, column)),
+                    quote(table),
+                    quote(column)
+                ),
+                [],
+            )
+            .context("create block number index")?;
+        }
+    }
+
+    let names = schema
+        .fields()
+        .iter()
+        .map(|field| quote(field.name()))
+        .collect::<Vec<_>>();
+    let placeholders = vec!["?"; names.len()];
+    let mut stmt = tx
+        .prepare_cached(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote(table),
+            names.join(", "),
+            placeholders.join(", ")
+        ))
+        .context("prepare insert")?;
+
+    let values = batch
+        .columns()
+        .iter()
+        .map(sql_values)
+        .collect::<Result<Vec<_>>>()?;
+    for row in 0..batch.num_rows() {
+        stmt.execute(params_from_iter(values.iter().map(|column| &column[row])))
+            .context("insert row")?;
+    }
+    Ok(())
+}
+
+/// Prepend the block number and log index of the logs to the decoded logs, so they can be
+/// rolled back and joined with the logs
+fn with_log_position(decoded: &RecordBatch, logs: &RecordBatch) -> Result<RecordBatch> {
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    for name in ["block_number", "log_index"] {
+        let Some(column) = logs.column_by_name(name) else {
+            continue;
+        };
+        if decoded.schema().column_with_name(name).is_some() || column.len() != decoded.num_rows() {
+            continue;
+        }
+        fields.push(Field::new(name, column.data_type().clone(), true));
+        columns.push(column.clone());
+    }
+    if columns.is_empty() {
+        return Ok(decoded.clone());
+    }
+
+    let schema = decoded.schema();
+    fields.extend(schema.fields().iter().map(|field| field.as_ref().clone()));
+    columns.extend(decoded.columns().iter().cloned());
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).context("build batch")
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn sql_type(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => "INTEGER",
+        DataType::Float32 | DataType::Float64 => "REAL",
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => "BLOB",
+        _ => "TEXT",
+    }
+}
+
+fn sql_values(array: &ArrayRef) -> Result<Vec<Value>> {
+    let values = match array.data_type() {
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|v| v.map_or(Value::Null, |v| Value::Integer(v.into())))
+            .collect(),
+        DataType::Int8 => integers(array.as_primitive::<Int8Type>()),
+        DataType::Int16 => integers(array.as_primitive::<Int16Type>()),
+        DataType::Int32 => integers(array.as_primitive::<Int32Type>()),
+        DataType::Int64 => integers(array.as_primitive::<Int64Type>()),
+        DataType::UInt8 => integers(array.as_primitive::<UInt8Type>()),
+        DataType::UInt16 => integers(array.as_primitive::<UInt16Type>()),
+        DataType::UInt32 => integers(array.as_primitive::<UInt32Type>()),
+        DataType::UInt64 => integers(array.as_primitive::<UInt64Type>()),
+        DataType::Float32 => array
+            .as_primitive::<Float32Type>()
+            .iter()
+            .map(|v| v.map_or(Value::Null, |v| Value::Real(v.into())))
+            .collect(),
+        DataType::Float64 => array
+            .as_primitive::<Float64Type>()
+            .iter()
+            .map(|v| v.map_or(Value::Null, Value::Real))
+            .collect(),
+        DataType::Utf8 => array
+            .as_string::<i32>()
+            .iter()
+            .map(|v| v.map_or(Value::Null, |v| Value::Text(v.to_owned())))
+            .collect(),
+        DataType::Binary => array
+            .as_binary::<i32>()
+            .iter()
+            .map(|v| v.map_or(Value::Null, |v| Value::Blob(v.to_vec())))
+            .collect(),
+        DataType::LargeBinary => array
+            .as_binary::<i64>()
+            .iter()
+            .map(|v| v.map_or(Value::Null, |v| Value::Blob(v.to_vec())))
+            .collect(),
+        DataType::FixedSizeBinary(_) => array
+            .as_fixed_size_binary()
+            .iter()
+            .map(|v| v.map_or(Value::Null, |v| Value::Blob(v.to_vec())))
+            .collect(),
+        _ => {
+            let options = FormatOptions::default();
+            let formatter =
+                ArrayFormatter::try_new(array.as_ref(), &options).context("format column")?;
+            (0..array.len())
+                .map(|i| {
+                    if array.is_null(i) {
+                        Value::Null
+                    } else {
+                        Value::Text(formatter.value(i).to_string())
+                    }
+                })
+                .collect()
+        }
+    };
+    Ok(values)
+}
+
+/// Integers that don't fit in SQLite's 64 bit integers are stored as text
+fn integers<T: ArrowPrimitiveType>(array: &PrimitiveArray<T>) -> Vec<Value> {
+    array
+        .iter()
+        .map(|v| match v {
+            None => Value::Null,
+            Some(v) => v
+                .to_i64()
+                .map_or_else(|| Value::Text(format!("{:?}", v)), Value::Integer),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{BinaryArray, StringArray, UInt64Array};
+
+    use super::*;
+
+    fn logs(numbers: &[u64]) -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("block_number", DataType::UInt64, false),
+            Field::new("log_index", DataType::UInt64, false),
+            Field::new("data", DataType::Binary, true),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(UInt64Array::from(numbers.to_vec())),
+                Arc::new(UInt64Array::from(vec![0; numbers.len()])),
+                Arc::new(BinaryArray::from_iter(
+                    numbers.iter().map(|n| Some(vec![*n as u8])),
+                )),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn decoded(numbers: &[u64]) -> RecordBatch {
+        let schema = Schema::new(vec![Field::new("value", DataType::Utf8, true)]);
+        let values = numbers.iter().map(|n| Some(n.to_string()));
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(StringArray::from_iter(values))],
+        )
+        .unwrap()
+    }
+
+    fn response(numbers: &[u64], next_block: u64) -> hypersync_client::ArrowResponse {
+        let last = next_block - 1;
+        hypersync_client::ArrowResponse {
+            archive_height: None,
+            next_block,
+            total_execution_time: 0,
+            data: hypersync_client::ArrowResponseData {
+                logs: vec![logs(numbers)],
+                decoded_logs: vec![decoded(numbers)],
+                ..Default::default()
+            },
+            rollback_guard: Some(net_types::RollbackGuard {
+                block_number: last,
+                timestamp: 0,
+                hash: [last as u8; 32].into(),
+                first_block_number: 0,
+                first_parent_hash: [0; 32].into(),
+            }),
+        }
+    }
+
+    fn count(sink: &SqliteSink, table: &str) -> i64 {
+        sink.conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_write_and_rollback() {
+        let path = std::env::temp_dir().join(format!("sqlite_out_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut sink = SqliteSink::open(path.clone()).unwrap();
+        assert_eq!(sink.next_block().unwrap(), None);
+        sink.write(response(&[1, 2, 2], 3)).unwrap();
+        sink.write(response(&[3, 4], 5)).unwrap();
+        assert_eq!(count(&sink, "logs"), 5);
+        assert_eq!(count(&sink, "decoded_logs"), 5);
+        let (number, value): (i64, String) = sink
+            .conn
+            .query_row(
+                "SELECT block_number, value FROM decoded_logs WHERE block_number = 4",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((number, value.as_str()), (4, "4"));
+
+        sink.rollback(2).unwrap();
+        assert_eq!(count(&sink, "logs"), 1);
+        assert_eq!(count(&sink, "decoded_logs"), 1);
+        assert_eq!(sink.next_block().unwrap(), Some(2));
+        drop(sink);
+
+        // progress and block hashes are kept between runs
+        let sink = SqliteSink::open(path.clone()).unwrap();
+        assert_eq!(sink.next_block().unwrap(), Some(2));
+        assert_eq!(sink.tracker.last_block(), None);
+        drop(sink);
+
+        let mut sink = SqliteSink::open(path.clone()).unwrap();
+        sink.write(response(&[2], 3)).unwrap();
+        drop(sink);
+        let sink = SqliteSink::open(path.clone()).unwrap();
+        assert_eq!(sink.tracker.last_block(), Some(2));
+        drop(sink);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sql_values() {
+        let array: ArrayRef = Arc::new(UInt64Array::from(vec![Some(1), None, Some(u64::MAX)]));
+        assert_eq!(
+            sql_values(&array).unwrap(),
+            vec![
+                Value::Integer(1),
+                Value::Null,
+                Value::Text(u64::MAX.to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use std::{collections::BTreeMap, future::Future, sync::Mutex};
+
+use anyhow::{Context, Result};
+use hypersync_client::net_types;
+
+/// Direction to search in when no block has exactly the requested timestamp
+#[napi(string_enum)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub enum TimestampDirection {
+    /// First block with a timestamp at or after the requested one
+    #[default]
+    After,
+    /// Last block with a timestamp at or before the requested one
+    Before,
+}
+
+#[napi(object)]
+pub struct BlockByTimestampOptions {
+    /// Default: After
+    pub direction: Option<TimestampDirection>,
+}
+
+/// Block range of a time range, `toBlock` is exclusive and not set if the time range
+/// ends after the last block
+#[napi(object)]
+pub struct BlockRange {
+    pub from_block: i64,
+    pub to_block: Option<i64>,
+}
+
+/// Number of probed blocks to keep, the cache is cleared when it grows larger
+const MAX_CACHED_BLOCKS: usize = 4096;
+
+/// Number and timestamp of a block
+type Probed = (u64, u64);
+
+/// Timestamps of probed blocks, kept between lookups so nearby lookups need fewer requests
+#[derive(Default)]
+pub struct TimestampCache(Mutex<BTreeMap<u64, u64>>);
+
+impl TimestampCache {
+    fn get(&self, block: u64) -> Option<u64> {
+        self.0.lock().ok()?.get(&block).copied()
+    }
+
+    fn insert(&self, block: u64, timestamp: u64) {
+        if let Ok(mut cache) = self.0.lock() {
+            if cache.len() >= MAX_CACHED_BLOCKS {
+                cache.clear();
+            }
+            cache.insert(block, timestamp);
+        }
+    }
+
+    /// Closest cached blocks below and at or above the boundary where `pred` turns true
+    fn bounds(&self, height: u64, pred: impl Fn(u64) -> bool) -> (Option<Probed>, Option<Probed>) {
+        let Ok(cache) = self.0.lock() else {
+            return (None, None);
+        };
+        let mut lo = None;
+        let mut hi = None;
+        for (&block, &timestamp) in cache.range(..=height) {
+            if pred(timestamp) {
+                hi = hi.or(Some((block, timestamp)));
+            } else {
+                lo = Some((block, timestamp));
+            }
+        }
+        (lo, hi)
+    }
+}
+
+/// Result of `find_first_block`
+#[derive(Debug, PartialEq, Eq)]
+pub enum Boundary {
+    /// First block matching the timestamp
+    Block(u64),
+    /// No block matches, holds the number of the last block
+    AfterHead(u64),
+}
+
+/// Find the first block up to `height` whose timestamp is after `target`, or at or after it
+/// if `inclusive`.
+///
+/// `probe` returns the timestamp of a block, or None if the block doesn't exist yet.
+pub async fn find_first_block<P, F>(
+    cache: &TimestampCache,
+    height: u64,
+    target: u64,
+    inclusive: bool,
+    mut probe: P,
+) -> Result<Boundary>
+where
+    P: FnMut(u64) -> F,
+    F: Future<Output = Result<Option<u64>>>,
+{
+    let pred = |timestamp: u64| {
+        if inclusive {
+            timestamp >= target
+        } else {
+            timestamp > target
+        }
+    };
+
+    let (lo, hi) = cache.bounds(height, pred);
+    let mut hi = match hi {
+        Some(hi) => hi,
+        None => {
+            let (head, timestamp) = head_timestamp(cache, &mut probe, height).await?;
+            if !pred(timestamp) {
+                return Ok(Boundary::AfterHead(head));
+            }
+            (head, timestamp)
+        }
+    };
+    let mut lo = match lo {
+        Some(lo) => lo,
+        None => {
+            let timestamp = timestamp_of(cache, &mut probe, 0)
+                .await?
+                .context("genesis block not found")?;
+            if pred(timestamp) {
+                return Ok(Boundary::Block(0));
+            }
+            (0, timestamp)
+        }
+    };
+
+    // interpolating converges in a few steps when block times are regular, alternating with
+    // bisection keeps the number of probes logarithmic when they are not
+    let mut interpolate = true;
+    while hi.0 - lo.0 > 1 {
+        let block = if interpolate && hi.1 > lo.1 {
+            let offset = u128::from(target.saturating_sub(lo.1)) * u128::from(hi.0 - lo.0)
+                / u128::from(hi.1 - lo.1);
+            lo.0.saturating_add(offset as u64)
+        } else {
+            lo.0 + (hi.0 - lo.0) / 2
+        }
+        .clamp(lo.0 + 1, hi.0 - 1);
+        interpolate = !interpolate;
+
+        let timestamp = timestamp_of(cache, &mut probe, block)
+            .await?
+            .with_context(|| format!("block {} not found", block))?;
+        if pred(timestamp) {
+            hi = (block, timestamp);
+        } else {
+            lo = (block, timestamp);
+        }
+    }
+
+    Ok(Boundary::Block(hi.0))
+}
+
+/// Timestamp of the last block, the height might point at a block that isn't available yet
+async fn head_timestamp<P, F>(cache: &TimestampCache, probe: &mut P, height: u64) -> Result<Probed>
+where
+    P: FnMut(u64) -> F,
+    F: Future<Output = Result<Option<u64>>>,
+{
+    if let Some(timestamp) = timestamp_of(cache, probe, height).await? {
+        return Ok((height, timestamp));
+    }
+    let head = height.checked_sub(1).context("no blocks available")?;
+    let timestamp = timestamp_of(cache, probe, head)
+        .await?
+        .with_context(|| format!("block {} not found", head))?;
+    Ok((head, timestamp))
+}
+
+async fn timestamp_of<P, F>(
+    cache: &TimestampCache,
+    probe: &mut P,
+    block: u64,
+) -> Result<Option<u64>>
+where
+    P: FnMut(u64) -> F,
+    F: Future<Output = Result<Option<u64>>>,
+{
+    if let Some(timestamp) = cache.get(block) {
+        return Ok(Some(timestamp));
+    }
+    let timestamp = probe(block)
+        .await
+        .with_context(|| format!("get timestamp of block {}", block))?;
+    if let Some(timestamp) = timestamp {
+        cache.insert(block, timestamp);
+    }
+    Ok(timestamp)
+}
+
+/// Timestamp of a block, None if the server doesn't have the block yet
+pub async fn probe_block_timestamp(
+    client: &hypersync_client::Client,
+    number: u64,
+) -> Result<Option<u64>> {
+    let query = net_types::Query {
+        from_block: number,
+        to_block: Some(number + 1),
+        include_all_blocks: true,
+        field_selection: net_types::FieldSelection {
+            block: [
+                net_types::BlockField::Number,
+                net_types::BlockField::Timestamp,
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let res = client.get(&query).await.context("run inner query")?;
+
+    let block = res
+        .data
+        .blocks
+        .iter()
+        .flatten()
+        .find(|block| block.number == Some(number));
+    let Some(block) = block else {
+        return Ok(None);
+    };
+    let timestamp = block.timestamp.as_ref().context("block has no timestamp")?;
+    parse_timestamp(timestamp.as_ref()).map(Some)
+}
+
+/// Read a big endian timestamp quantity
+fn parse_timestamp(bytes: &[u8]) -> Result<u64> {
+    if bytes.len() > 8 {
+        anyhow::bail!("timestamp doesn't fit in 64 bits");
+    }
+    Ok(bytes.iter().fold(0, |n, &b| (n << 8) | u64::from(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Block times of 12 seconds with a gap of a day after block 500, starting at 1000
+    fn timestamp(block: u64) -> u64 {
+        let gap = if block > 500 { 86_400 } else { 0 };
+        1000 + block * 12 + gap
+    }
+
+    fn find(
+        cache: &TimestampCache,
+        target: u64,
+        inclusive: bool,
+        probes: &Cell<usize>,
+    ) -> Boundary {
+        let probe = |block: u64| {
+            probes.set(probes.get() + 1);
+            async move { Ok((block <= 1000).then(|| timestamp(block))) }
+        };
+        let fut = find_first_block(cache, 1001, target, inclusive, probe);
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(fut)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_find_first_block() {
+        let probes = Cell::new(0);
+        let cache = TimestampCache::default();
+
+        assert_eq!(
+            find(&cache, timestamp(300), true, &probes),
+            Boundary::Block(300)
+        );
+        assert_eq!(
+            find(&cache, timestamp(300), false, &probes),
+            Boundary::Block(301)
+        );
+        assert_eq!(
+            find(&cache, timestamp(300) + 1, true, &probes),
+            Boundary::Block(301)
+        );
+        // inside the gap, the next block is after it
+        assert_eq!(
+            find(&cache, timestamp(500) + 100, true, &probes),
+            Boundary::Block(501)
+        );
+        assert_eq!(find(&cache, 0, true, &probes), Boundary::Block(0));
+        assert_eq!(
+            find(&cache, timestamp(1000), true, &probes),
+            Boundary::Block(1000)
+        );
+        assert_eq!(
+            find(&cache, timestamp(1000), false, &probes),
+            Boundary::AfterHead(1000)
+        );
+    }
+
+    #[test]
+    fn test_cache_saves_probes() {
+        let probes = Cell::new(0);
+        let cache = TimestampCache::default();
+
+        assert_eq!(
+            find(&cache, timestamp(700), true, &probes),
+            Boundary::Block(700)
+        );
+        let first = probes.get();
+        assert!(first <= 25, "took {} probes", first);
+
+        assert_eq!(
+            find(&cache, timestamp(700), true, &probes),
+            Boundary::Block(700)
+        );
+        assert_eq!(probes.get(), first);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp(&[]).unwrap(), 0);
+        assert_eq!(
+            parse_timestamp(&[0x65, 0x4a, 0x1b, 0x00]).unwrap(),
+            0x654a1b00
+        );
+        assert_eq!(parse_timestamp(&[0x01, 0x00]).unwrap(), 256);
+        assert_eq!(parse_timestamp(&[0xff; 8]).unwrap(), u64::MAX);
+        assert!(parse_timestamp(&[0x01; 9]).is_err());
+    }
+}
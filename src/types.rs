@@ -1,11 +1,250 @@
 use alloy_dyn_abi::DynSolValue;
+use alloy_json_abi::Param;
 use alloy_primitives::{Signed, U256};
 use anyhow::{Context, Result};
 use hypersync_client::{
     format::{self, FixedSizeData, Hex},
     net_types, simple_types,
 };
-use napi::bindgen_prelude::{BigInt, Either4};
+use napi::{
+    bindgen_prelude::{
+        BigInt, Buffer, BufferSlice, Either, Either3, Either4, FromNapiValue, Object, ToNapiValue,
+        TypeName, ValidateNapiValue,
+    },
+    sys, Env, ValueType,
+};
+
+use crate::config::{NumericMode, StreamConfig};
+
+/// How binary fields of responses, like hashes and addresses, and quantities are returned to JS
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoding {
+    /// Checksum addresses, only applies to hex strings
+    pub checksum_addresses: bool,
+    /// Return binary fields as Buffers instead of hex strings
+    pub binary: bool,
+    /// Representation of quantities, None keeps the default of each field
+    pub numeric: Option<NumericMode>,
+}
+
+impl Encoding {
+    /// Encoding of a stream, `binaryFields` of its config overrides the one of the client
+    pub fn for_stream(self, config: &StreamConfig) -> Self {
+        Self {
+            binary: config.binary_fields.unwrap_or(self.binary),
+            ..self
+        }
+    }
+}
+
+/// Binary value, a prefixed hex string or a Buffer depending on the `binaryFields` option.
+///
+/// The bytes of a Buffer are copied into the JS heap when it is handed over to JS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringOrBuffer {
+    String(String),
+    Buffer(Vec<u8>),
+}
+
+impl StringOrBuffer {
+    pub fn new<T: Hex + AsRef<[u8]>>(v: &T, encoding: Encoding) -> Self {
+        if encoding.binary {
+            Self::Buffer(v.as_ref().to_vec())
+        } else {
+            Self::String(v.encode_hex())
+        }
+    }
+
+    pub fn from_slice(bytes: &[u8], encoding: Encoding) -> Self {
+        if encoding.binary {
+            Self::Buffer(bytes.to_vec())
+        } else {
+            Self::String(encode_prefix_hex(bytes))
+        }
+    }
+
+    /// Address, checksummed if enabled and returned as a hex string
+    pub fn address(v: &FixedSizeData<20>, encoding: Encoding) -> Self {
+        if encoding.checksum_addresses && !encoding.binary {
+            Self::String(
+                alloy_primitives::Address(alloy_primitives::FixedBytes(***v)).to_checksum(None),
+            )
+        } else {
+            Self::new(v, encoding)
+        }
+    }
+
+    /// The hex string, None for Buffers
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            Self::Buffer(_) => None,
+        }
+    }
+
+    /// Bytes of the value, hex strings have to be prefixed with 0x
+    pub fn to_data(&self) -> Result<format::Data> {
+        match self {
+            Self::String(s) => format::Data::decode_hex(s).context("decode hex"),
+            Self::Buffer(bytes) => Ok(bytes.as_slice().into()),
+        }
+    }
+
+    /// Whether the value holds the same bytes as the hex string, ignoring case
+    pub fn matches_hex(&self, hex: &str) -> bool {
+        match self {
+            Self::String(s) => s.eq_ignore_ascii_case(hex),
+            Self::Buffer(bytes) => {
+                format::Data::decode_hex(hex).is_ok_and(|data| data.as_ref() == bytes.as_slice())
+            }
+        }
+    }
+}
+
+impl From<String> for StringOrBuffer {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<Either<String, Buffer>> for StringOrBuffer {
+    fn from(v: Either<String, Buffer>) -> Self {
+        match v {
+            Either::A(s) => Self::String(s),
+            Either::B(buf) => Self::Buffer(buf.to_vec()),
+        }
+    }
+}
+
+// Typed as the default hex strings, see `encoding.d.ts` for responses with `binaryFields`
+impl TypeName for StringOrBuffer {
+    fn type_name() -> &'static str {
+        "string"
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Unknown
+    }
+}
+
+impl ValidateNapiValue for StringOrBuffer {
+    unsafe fn validate(
+        env: sys::napi_env,
+        napi_val: sys::napi_value,
+    ) -> napi::Result<sys::napi_value> {
+        <Either<String, Buffer> as ValidateNapiValue>::validate(env, napi_val)
+    }
+}
+
+impl FromNapiValue for StringOrBuffer {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> napi::Result<Self> {
+        Either::<String, Buffer>::from_napi_value(env, napi_val).map(Self::from)
+    }
+}
+
+impl ToNapiValue for StringOrBuffer {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        match val {
+            Self::String(s) => ToNapiValue::to_napi_value(env, s),
+            // Copied into a buffer of the JS heap, a buffer wrapping the Vec would need a
+            // finalizer and collecting hundreds of thousands of them stalls the event loop
+            Self::Buffer(bytes) => {
+                ToNapiValue::to_napi_value(env, BufferSlice::copy_from(&Env::from_raw(env), bytes)?)
+            }
+        }
+    }
+}
+
+/// Quantity, a number, bigint or string depending on the `numericMode` option
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Numeric {
+    Number(i64),
+    BigInt(BigInt),
+    String(String),
+}
+
+/// Representation of a quantity if no `numericMode` is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericKind {
+    Number,
+    BigInt,
+    Hex,
+}
+
+/// Largest integer that a JS number can hold exactly
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+impl Numeric {
+    pub fn new(v: U256, kind: NumericKind, mode: Option<NumericMode>) -> Result<Self> {
+        Ok(match (mode, kind) {
+            (Some(NumericMode::BigInt), _) | (None, NumericKind::BigInt) => {
+                Self::BigInt(convert_bigint_unsigned(v))
+            }
+            (Some(NumericMode::DecimalString), _) => Self::String(v.to_string()),
+            (Some(NumericMode::Hex), _) | (None, NumericKind::Hex) => {
+                Self::String(format!("{:#x}", v))
+            }
+            (Some(NumericMode::NumberWhenSafe), _) => {
+                if v <= U256::from(MAX_SAFE_INTEGER) {
+                    Self::Number(v.to())
+                } else {
+                    Self::String(v.to_string())
+                }
+            }
+            (None, NumericKind::Number) => {
+                Self::Number(i64::try_from(v).context("converting U256 to i64")?)
+            }
+        })
+    }
+}
+
+/// Fractional quantity, a number unless `numericMode` is `decimalString` or `hex`. Both give a
+/// decimal string, since hex can't represent fractions.
+pub fn fraction(v: f64, mode: Option<NumericMode>) -> Either<f64, String> {
+    match mode {
+        Some(NumericMode::DecimalString | NumericMode::Hex) => Either::B(v.to_string()),
+        _ => Either::A(v),
+    }
+}
+
+impl TypeName for Numeric {
+    fn type_name() -> &'static str {
+        "number | bigint | string"
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Unknown
+    }
+}
+
+impl ValidateNapiValue for Numeric {
+    unsafe fn validate(
+        env: sys::napi_env,
+        napi_val: sys::napi_value,
+    ) -> napi::Result<sys::napi_value> {
+        <Either3<i64, BigInt, String> as ValidateNapiValue>::validate(env, napi_val)
+    }
+}
+
+impl FromNapiValue for Numeric {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> napi::Result<Self> {
+        Either3::<i64, BigInt, String>::from_napi_value(env, napi_val).map(|v| match v {
+            Either3::A(n) => Self::Number(n),
+            Either3::B(n) => Self::BigInt(n),
+            Either3::C(s) => Self::String(s),
+        })
+    }
+}
+
+impl ToNapiValue for Numeric {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        match val {
+            Self::Number(n) => ToNapiValue::to_napi_value(env, n),
+            Self::BigInt(n) => ToNapiValue::to_napi_value(env, n),
+            Self::String(s) => ToNapiValue::to_napi_value(env, s),
+        }
+    }
+}
 
 /// Data relating to a single event (log)
 #[napi(object)]
@@ -23,72 +262,104 @@ pub struct Event {
 ///
 /// See ethereum rpc spec for the meaning of fields
 #[napi(object)]
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Log {
     pub removed: Option<bool>,
-    pub log_index: Option<i64>,
-    pub transaction_index: Option<i64>,
-    pub transaction_hash: Option<String>,
-    pub block_hash: Option<String>,
-    pub block_number: Option<i64>,
-    pub address: Option<String>,
-    pub data: Option<String>,
-    pub topics: Vec<Option<String>>,
+    #[napi(ts_type = "number")]
+    pub log_index: Option<Numeric>,
+    #[napi(ts_type = "number")]
+    pub transaction_index: Option<Numeric>,
+    pub transaction_hash: Option<StringOrBuffer>,
+    pub block_hash: Option<StringOrBuffer>,
+    #[napi(ts_type = "number")]
+    pub block_number: Option<Numeric>,
+    pub address: Option<StringOrBuffer>,
+    pub data: Option<StringOrBuffer>,
+    pub topics: Vec<Option<StringOrBuffer>>,
 }
 
 /// Evm transaction object
 ///
 /// See ethereum rpc spec for the meaning of fields
 #[napi(object)]
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Transaction {
-    pub block_hash: Option<String>,
-    pub block_number: Option<i64>,
-    pub from: Option<String>,
-    pub gas: Option<BigInt>,
-    pub gas_price: Option<BigInt>,
-    pub hash: Option<String>,
-    pub input: Option<String>,
-    pub nonce: Option<BigInt>,
-    pub to: Option<String>,
-    pub transaction_index: Option<i64>,
-    pub value: Option<BigInt>,
+    pub block_hash: Option<StringOrBuffer>,
+    #[napi(ts_type = "number")]
+    pub block_number: Option<Numeric>,
+    pub from: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub gas: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub gas_price: Option<Numeric>,
+    pub hash: Option<StringOrBuffer>,
+    pub input: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub nonce: Option<Numeric>,
+    pub to: Option<StringOrBuffer>,
+    #[napi(ts_type = "number")]
+    pub transaction_index: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub value: Option<Numeric>,
     pub v: Option<String>,
     pub r: Option<String>,
     pub s: Option<String>,
     pub y_parity: Option<String>,
-    pub max_priority_fee_per_gas: Option<BigInt>,
-    pub max_fee_per_gas: Option<BigInt>,
-    pub chain_id: Option<i64>,
+    #[napi(ts_type = "bigint")]
+    pub max_priority_fee_per_gas: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub max_fee_per_gas: Option<Numeric>,
+    #[napi(ts_type = "number")]
+    pub chain_id: Option<Numeric>,
     pub access_list: Option<Vec<AccessList>>,
     pub authorization_list: Option<Vec<Authorization>>,
-    pub max_fee_per_blob_gas: Option<BigInt>,
-    pub blob_versioned_hashes: Option<Vec<String>>,
-    pub cumulative_gas_used: Option<BigInt>,
-    pub effective_gas_price: Option<BigInt>,
-    pub gas_used: Option<BigInt>,
-    pub contract_address: Option<String>,
-    pub logs_bloom: Option<String>,
-    #[napi(js_name = "type")]
-    pub type_: Option<i64>,
-    pub root: Option<String>,
-    pub status: Option<i64>,
-    pub l1_fee: Option<BigInt>,
-    pub l1_gas_price: Option<BigInt>,
-    pub l1_gas_used: Option<BigInt>,
-    pub l1_fee_scalar: Option<f64>,
-    pub gas_used_for_l1: Option<BigInt>,
-    pub blob_gas_price: Option<BigInt>,
-    pub blob_gas_used: Option<BigInt>,
-    pub deposit_nonce: Option<BigInt>,
-    pub deposit_receipt_version: Option<BigInt>,
-    pub l1_base_fee_scalar: Option<BigInt>,
-    pub l1_blob_base_fee: Option<BigInt>,
-    pub l1_blob_base_fee_scalar: Option<BigInt>,
-    pub l1_block_number: Option<i64>,
-    pub mint: Option<BigInt>,
-    pub sighash: Option<String>,
-    pub source_hash: Option<String>,
+    #[napi(ts_type = "bigint")]
+    pub max_fee_per_blob_gas: Option<Numeric>,
+    pub blob_versioned_hashes: Option<Vec<StringOrBuffer>>,
+    #[napi(ts_type = "bigint")]
+    pub cumulative_gas_used: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub effective_gas_price: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub gas_used: Option<Numeric>,
+    pub contract_address: Option<StringOrBuffer>,
+    pub logs_bloom: Option<StringOrBuffer>,
+    #[napi(js_name = "type", ts_type = "number")]
+    pub type_: Option<Numeric>,
+    pub root: Option<StringOrBuffer>,
+    #[napi(ts_type = "number")]
+    pub status: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub l1_fee: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub l1_gas_price: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub l1_gas_used: Option<Numeric>,
+    /// Fractional, a decimal string if `numericMode` is `decimalString` or `hex`
+    #[napi(ts_type = "number")]
+    pub l1_fee_scalar: Option<Either<f64, String>>,
+    #[napi(ts_type = "bigint")]
+    pub gas_used_for_l1: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub blob_gas_price: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub blob_gas_used: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub deposit_nonce: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub deposit_receipt_version: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub l1_base_fee_scalar: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub l1_blob_base_fee: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub l1_blob_base_fee_scalar: Option<Numeric>,
+    #[napi(ts_type = "number")]
+    pub l1_block_number: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub mint: Option<Numeric>,
+    pub sighash: Option<StringOrBuffer>,
+    pub source_hash: Option<StringOrBuffer>,
 }
 
 /// Evm withdrawal object
@@ -97,20 +368,23 @@ pub struct Transaction {
 #[napi(object)]
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Withdrawal {
-    pub index: Option<String>,
-    pub validator_index: Option<String>,
-    pub address: Option<String>,
-    pub amount: Option<String>,
+    #[napi(ts_type = "string")]
+    pub index: Option<Numeric>,
+    #[napi(ts_type = "string")]
+    pub validator_index: Option<Numeric>,
+    pub address: Option<StringOrBuffer>,
+    #[napi(ts_type = "string")]
+    pub amount: Option<Numeric>,
 }
 
-impl From<&format::Withdrawal> for Withdrawal {
-    fn from(w: &format::Withdrawal) -> Self {
-        Self {
-            index: map_hex_string(&w.index),
-            validator_index: map_hex_string(&w.validator_index),
-            address: map_hex_string(&w.address),
-            amount: map_hex_string(&w.amount),
-        }
+impl Withdrawal {
+    pub fn new(w: &format::Withdrawal, encoding: Encoding) -> Result<Self> {
+        Ok(Self {
+            index: map_numeric(&w.index, NumericKind::Hex, encoding)?,
+            validator_index: map_numeric(&w.validator_index, NumericKind::Hex, encoding)?,
+            address: map_binary(&w.address, encoding),
+            amount: map_numeric(&w.amount, NumericKind::Hex, encoding)?,
+        })
     }
 }
 
@@ -120,18 +394,19 @@ impl From<&format::Withdrawal> for Withdrawal {
 #[napi(object)]
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct AccessList {
-    pub address: Option<String>,
-    pub storage_keys: Option<Vec<String>>,
+    pub address: Option<StringOrBuffer>,
+    pub storage_keys: Option<Vec<StringOrBuffer>>,
 }
 
-impl From<&format::AccessList> for AccessList {
-    fn from(a: &format::AccessList) -> Self {
+impl AccessList {
+    pub fn new(a: &format::AccessList, encoding: Encoding) -> Self {
         Self {
-            address: map_hex_string(&a.address),
-            storage_keys: a
-                .storage_keys
-                .as_ref()
-                .map(|arr| arr.iter().map(|x| x.encode_hex()).collect()),
+            address: map_binary(&a.address, encoding),
+            storage_keys: a.storage_keys.as_ref().map(|arr| {
+                arr.iter()
+                    .map(|x| StringOrBuffer::new(x, encoding))
+                    .collect()
+            }),
         }
     }
 }
@@ -143,11 +418,13 @@ impl From<&format::AccessList> for AccessList {
 #[derive(Debug, Clone)]
 pub struct Authorization {
     /// uint256
-    pub chain_id: BigInt,
-    /// 20-byte hex
-    pub address: String,
+    #[napi(ts_type = "bigint")]
+    pub chain_id: Numeric,
+    /// 20 bytes
+    pub address: StringOrBuffer,
     /// uint64
-    pub nonce: i64,
+    #[napi(ts_type = "number")]
+    pub nonce: Numeric,
     /// 0 | 1
     pub y_parity: i64,
     /// 32-byte hex
@@ -156,23 +433,21 @@ pub struct Authorization {
     pub s: String,
 }
 
-impl From<&format::Authorization> for Authorization {
-    fn from(a: &format::Authorization) -> Self {
-        Self {
-            chain_id: convert_bigint_unsigned(
-                ruint::aliases::U256::try_from_be_slice(&a.chain_id)
-                    .expect("convert chain_id bytes to U256"),
-            ),
-            address: a.address.encode_hex(),
-            nonce: alloy_primitives::I64::try_from_be_slice(&a.nonce)
-                .expect("convert nonce bytes to I64")
-                .as_i64(),
+impl Authorization {
+    pub fn new(a: &format::Authorization, encoding: Encoding) -> Result<Self> {
+        let chain_id = U256::try_from_be_slice(&a.chain_id).context("convert chain_id to U256")?;
+        let nonce = U256::try_from_be_slice(&a.nonce).context("convert nonce to U256")?;
+        Ok(Self {
+            chain_id: Numeric::new(chain_id, NumericKind::BigInt, encoding.numeric)?,
+            address: StringOrBuffer::new(&a.address, encoding),
+            nonce: Numeric::new(nonce, NumericKind::Number, encoding.numeric)
+                .context("mapping authorization.nonce")?,
             y_parity: alloy_primitives::I64::try_from_be_slice(&a.y_parity)
                 .expect("convert y_parity bytes to I64")
                 .as_i64(),
             r: a.r.encode_hex(),
             s: a.s.encode_hex(),
-        }
+        })
     }
 }
 
@@ -180,116 +455,171 @@ impl From<&format::Authorization> for Authorization {
 ///
 /// See ethereum rpc spec for the meaning of fields
 #[napi(object)]
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Block {
-    pub number: Option<i64>,
-    pub hash: Option<String>,
-    pub parent_hash: Option<String>,
-    pub nonce: Option<BigInt>,
-    pub sha3_uncles: Option<String>,
-    pub logs_bloom: Option<String>,
-    pub transactions_root: Option<String>,
-    pub state_root: Option<String>,
-    pub receipts_root: Option<String>,
-    pub miner: Option<String>,
-    pub difficulty: Option<BigInt>,
-    pub total_difficulty: Option<BigInt>,
-    pub extra_data: Option<String>,
-    pub size: Option<BigInt>,
-    pub gas_limit: Option<BigInt>,
-    pub gas_used: Option<BigInt>,
-    pub timestamp: Option<i64>,
-    pub uncles: Option<Vec<String>>,
-    pub base_fee_per_gas: Option<BigInt>,
-    pub blob_gas_used: Option<BigInt>,
-    pub excess_blob_gas: Option<BigInt>,
-    pub parent_beacon_block_root: Option<String>,
-    pub withdrawals_root: Option<String>,
+    #[napi(ts_type = "number")]
+    pub number: Option<Numeric>,
+    pub hash: Option<StringOrBuffer>,
+    pub parent_hash: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub nonce: Option<Numeric>,
+    pub sha3_uncles: Option<StringOrBuffer>,
+    pub logs_bloom: Option<StringOrBuffer>,
+    pub transactions_root: Option<StringOrBuffer>,
+    pub state_root: Option<StringOrBuffer>,
+    pub receipts_root: Option<StringOrBuffer>,
+    pub miner: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub difficulty: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub total_difficulty: Option<Numeric>,
+    pub extra_data: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub size: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub gas_limit: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub gas_used: Option<Numeric>,
+    #[napi(ts_type = "number")]
+    pub timestamp: Option<Numeric>,
+    pub uncles: Option<Vec<StringOrBuffer>>,
+    #[napi(ts_type = "bigint")]
+    pub base_fee_per_gas: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub blob_gas_used: Option<Numeric>,
+    #[napi(ts_type = "bigint")]
+    pub excess_blob_gas: Option<Numeric>,
+    pub parent_beacon_block_root: Option<StringOrBuffer>,
+    pub withdrawals_root: Option<StringOrBuffer>,
     pub withdrawals: Option<Vec<Withdrawal>>,
-    pub l1_block_number: Option<i64>,
-    pub send_count: Option<String>,
-    pub send_root: Option<String>,
-    pub mix_hash: Option<String>,
+    #[napi(ts_type = "number")]
+    pub l1_block_number: Option<Numeric>,
+    #[napi(ts_type = "string")]
+    pub send_count: Option<Numeric>,
+    pub send_root: Option<StringOrBuffer>,
+    pub mix_hash: Option<StringOrBuffer>,
 }
 
 /// Evm trace object
 ///
 /// See ethereum rpc spec for the meaning of fields
 #[napi(object)]
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Trace {
-    pub from: Option<String>,
-    pub to: Option<String>,
+    pub from: Option<StringOrBuffer>,
+    pub to: Option<StringOrBuffer>,
     pub call_type: Option<String>,
-    pub gas: Option<BigInt>,
-    pub input: Option<String>,
-    pub init: Option<String>,
-    pub value: Option<BigInt>,
-    pub author: Option<String>,
+    #[napi(ts_type = "bigint")]
+    pub gas: Option<Numeric>,
+    pub input: Option<StringOrBuffer>,
+    pub init: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub value: Option<Numeric>,
+    pub author: Option<StringOrBuffer>,
     pub reward_type: Option<String>,
-    pub block_hash: Option<String>,
-    pub block_number: Option<i64>,
-    pub address: Option<String>,
-    pub code: Option<String>,
-    pub gas_used: Option<BigInt>,
-    pub output: Option<String>,
-    pub subtraces: Option<i64>,
+    pub block_hash: Option<StringOrBuffer>,
+    #[napi(ts_type = "number")]
+    pub block_number: Option<Numeric>,
+    pub address: Option<StringOrBuffer>,
+    pub code: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub gas_used: Option<Numeric>,
+    pub output: Option<StringOrBuffer>,
+    #[napi(ts_type = "number")]
+    pub subtraces: Option<Numeric>,
     pub trace_address: Option<Vec<i64>>,
-    pub transaction_hash: Option<String>,
-    pub transaction_position: Option<i64>,
+    pub transaction_hash: Option<StringOrBuffer>,
+    #[napi(ts_type = "number")]
+    pub transaction_position: Option<Numeric>,
     #[napi(js_name = "type")]
     pub type_: Option<String>,
     pub error: Option<String>,
-    pub action_address: Option<String>,
-    pub balance: Option<BigInt>,
-    pub refund_address: Option<String>,
-    pub sighash: Option<String>,
+    pub action_address: Option<StringOrBuffer>,
+    #[napi(ts_type = "bigint")]
+    pub balance: Option<Numeric>,
+    pub refund_address: Option<StringOrBuffer>,
+    pub sighash: Option<StringOrBuffer>,
 }
 
 /// Decoded EVM log
-#[napi(object)]
+#[napi(object, object_from_js = false)]
 #[derive(Default)]
 pub struct DecodedEvent {
     pub indexed: Vec<DecodedSolValue>,
     pub body: Vec<DecodedSolValue>,
+    /// Name of the event, only set if named params are enabled on the decoder
+    pub name: Option<String>,
+    /// Canonical signature of the event, e.g. `Transfer(address,address,uint256)`.
+    /// Only set if named params are enabled on the decoder.
+    pub signature: Option<String>,
+    /// Topic0 of the event, a Buffer if binary values are enabled on the decoder.
+    /// Only set if named params are enabled on the decoder.
+    pub topic0: Option<StringOrBuffer>,
+    /// Params keyed by name, tuples with named components become nested objects.
+    /// Params without a name are keyed by their position.
+    /// Only set if named params are enabled on the decoder.
+    #[napi(ts_type = "Record<string, NamedSolValue>")]
+    pub params: Option<NamedSolValue>,
+}
+
+/// Decoded revert data of a failed call
+#[napi(object)]
+pub struct DecodedRevert {
+    /// Name of the error, `Error` for reverts with a reason string and `Panic` for failed
+    /// assertions and other checks inserted by the compiler
+    pub name: String,
+    /// Canonical signature of the error, e.g. `Error(string)`
+    pub signature: String,
+    pub params: Vec<DecodedSolValue>,
+    /// Reason string of `Error(string)` or description of the `Panic(uint256)` code
+    pub message: Option<String>,
 }
 
 #[napi(object)]
 #[derive(Clone)]
 pub struct DecodedSolValue {
-    pub val: Either4<bool, BigInt, String, Vec<DecodedSolValue>>,
+    #[napi(ts_type = "boolean | bigint | string | Array<DecodedSolValue>")]
+    pub val: Either4<bool, BigInt, StringOrBuffer, Vec<DecodedSolValue>>,
 }
 
 impl DecodedSolValue {
-    pub fn new(val: DynSolValue, checksummed_addresses: bool) -> Self {
+    pub fn new(val: DynSolValue, encoding: Encoding) -> Self {
         let val = match val {
             DynSolValue::Bool(b) => Either4::A(b),
             DynSolValue::Int(v, _) => Either4::B(convert_bigint_signed(v)),
             DynSolValue::Uint(v, _) => Either4::B(convert_bigint_unsigned(v)),
-            DynSolValue::FixedBytes(bytes, _) => Either4::C(encode_prefix_hex(bytes.as_slice())),
+            DynSolValue::FixedBytes(bytes, _) => {
+                Either4::C(StringOrBuffer::from_slice(bytes.as_slice(), encoding))
+            }
             DynSolValue::Address(addr) => {
-                if !checksummed_addresses {
-                    Either4::C(encode_prefix_hex(addr.as_slice()))
+                if encoding.checksum_addresses && !encoding.binary {
+                    Either4::C(StringOrBuffer::String(addr.to_checksum(None)))
                 } else {
-                    Either4::C(addr.to_checksum(None))
+                    Either4::C(StringOrBuffer::from_slice(addr.as_slice(), encoding))
                 }
             }
-            DynSolValue::Function(bytes) => Either4::C(encode_prefix_hex(bytes.as_slice())),
-            DynSolValue::Bytes(bytes) => Either4::C(encode_prefix_hex(bytes.as_slice())),
-            DynSolValue::String(s) => Either4::C(s),
+            DynSolValue::Function(bytes) => {
+                Either4::C(StringOrBuffer::from_slice(bytes.as_slice(), encoding))
+            }
+            DynSolValue::Bytes(bytes) => Either4::C(if encoding.binary {
+                StringOrBuffer::Buffer(bytes)
+            } else {
+                StringOrBuffer::String(encode_prefix_hex(&bytes))
+            }),
+            DynSolValue::String(s) => Either4::C(StringOrBuffer::String(s)),
             DynSolValue::Array(vals) => Either4::D(
                 vals.into_iter()
-                    .map(|v| DecodedSolValue::new(v, checksummed_addresses))
+                    .map(|v| DecodedSolValue::new(v, encoding))
                     .collect(),
             ),
             DynSolValue::FixedArray(vals) => Either4::D(
                 vals.into_iter()
-                    .map(|v| DecodedSolValue::new(v, checksummed_addresses))
+                    .map(|v| DecodedSolValue::new(v, encoding))
                     .collect(),
             ),
             DynSolValue::Tuple(vals) => Either4::D(
                 vals.into_iter()
-                    .map(|v| DecodedSolValue::new(v, checksummed_addresses))
+                    .map(|v| DecodedSolValue::new(v, encoding))
                     .collect(),
             ),
         };
@@ -298,6 +628,75 @@ impl DecodedSolValue {
     }
 }
 
+/// Decoded value where tuples with named components are objects keyed by component name
+pub enum NamedSolValue {
+    Value(Either3<bool, BigInt, StringOrBuffer>),
+    Array(Vec<NamedSolValue>),
+    Struct(Vec<(String, NamedSolValue)>),
+}
+
+impl NamedSolValue {
+    /// Convert a decoded value of a param with the given tuple components
+    pub fn new(val: DynSolValue, components: &[Param], encoding: Encoding) -> Self {
+        match val {
+            DynSolValue::Array(vals) | DynSolValue::FixedArray(vals) => Self::Array(
+                vals.into_iter()
+                    .map(|v| Self::new(v, components, encoding))
+                    .collect(),
+            ),
+            // Components of signatures that don't name them are kept positional
+            DynSolValue::Tuple(vals)
+                if components.len() != vals.len()
+                    || components.iter().any(|c| c.name.is_empty()) =>
+            {
+                Self::Array(
+                    vals.into_iter()
+                        .zip(components.iter().map(Some).chain(std::iter::repeat(None)))
+                        .map(|(v, c)| {
+                            let components = c.map(|c| c.components.as_slice()).unwrap_or_default();
+                            Self::new(v, components, encoding)
+                        })
+                        .collect(),
+                )
+            }
+            DynSolValue::Tuple(vals) => Self::Struct(
+                vals.into_iter()
+                    .zip(components)
+                    .map(|(v, c)| (c.name.clone(), Self::new(v, &c.components, encoding)))
+                    .collect(),
+            ),
+            val => DecodedSolValue::new(val, encoding).into(),
+        }
+    }
+}
+
+impl From<DecodedSolValue> for NamedSolValue {
+    fn from(v: DecodedSolValue) -> Self {
+        match v.val {
+            Either4::A(v) => Self::Value(Either3::A(v)),
+            Either4::B(v) => Self::Value(Either3::B(v)),
+            Either4::C(v) => Self::Value(Either3::C(v)),
+            Either4::D(vals) => Self::Array(vals.into_iter().map(Self::from).collect()),
+        }
+    }
+}
+
+impl ToNapiValue for NamedSolValue {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        match val {
+            Self::Value(v) => ToNapiValue::to_napi_value(env, v),
+            Self::Array(vals) => ToNapiValue::to_napi_value(env, vals),
+            Self::Struct(fields) => {
+                let mut obj = Object::new(&Env::from_raw(env))?;
+                for (name, val) in fields {
+                    obj.set(name, val)?;
+                }
+                ToNapiValue::to_napi_value(env, obj)
+            }
+        }
+    }
+}
+
 fn encode_prefix_hex(bytes: &[u8]) -> String {
     if bytes.is_empty() {
         return "0x".into();
@@ -306,218 +705,218 @@ fn encode_prefix_hex(bytes: &[u8]) -> String {
     format!("0x{}", faster_hex::hex_string(bytes))
 }
 
-fn map_address_string(v: &Option<FixedSizeData<20>>, should_checksum: bool) -> Option<String> {
-    v.as_ref().map(|v| {
-        if should_checksum {
-            alloy_primitives::Address(alloy_primitives::FixedBytes(***v)).to_checksum(None)
-        } else {
-            v.encode_hex()
-        }
-    })
+fn map_address(v: &Option<FixedSizeData<20>>, encoding: Encoding) -> Option<StringOrBuffer> {
+    v.as_ref().map(|v| StringOrBuffer::address(v, encoding))
+}
+
+fn map_binary<T: Hex + AsRef<[u8]>>(v: &Option<T>, encoding: Encoding) -> Option<StringOrBuffer> {
+    v.as_ref().map(|v| StringOrBuffer::new(v, encoding))
 }
 
 fn map_hex_string<T: Hex>(v: &Option<T>) -> Option<String> {
     v.as_ref().map(|v| v.encode_hex())
 }
 
-fn map_i64<T: AsRef<[u8]>>(opt: &Option<T>) -> Result<Option<i64>> {
+fn map_numeric<T: AsRef<[u8]>>(
+    opt: &Option<T>,
+    kind: NumericKind,
+    encoding: Encoding,
+) -> Result<Option<Numeric>> {
     opt.as_ref()
         .map(|v| {
-            i64::try_from(ruint::aliases::U256::from_be_slice(v.as_ref()))
-                .context("converting U256 to i64")
+            let v = U256::try_from_be_slice(v.as_ref()).context("quantity exceeds 256 bits")?;
+            Numeric::new(v, kind, encoding.numeric)
         })
         .transpose()
 }
 
-fn map_bigint<T: AsRef<[u8]>>(opt: &Option<T>) -> Option<BigInt> {
-    opt.as_ref()
-        .map(|v| convert_bigint_unsigned(ruint::aliases::U256::from_be_slice(v.as_ref())))
+fn map_u64(opt: Option<u64>, encoding: Encoding) -> Result<Option<Numeric>> {
+    opt.map(|v| Numeric::new(U256::from(v), NumericKind::Number, encoding.numeric))
+        .transpose()
 }
 
 impl Block {
-    pub fn from_simple(b: &simple_types::Block, should_checksum: bool) -> Result<Self> {
+    pub fn from_simple(b: &simple_types::Block, encoding: Encoding) -> Result<Self> {
         Ok(Self {
-            number: b
-                .number
-                .map(i64::try_from)
-                .transpose()
-                .context("mapping block.number")?,
-            hash: map_hex_string(&b.hash),
-            parent_hash: map_hex_string(&b.parent_hash),
-            nonce: map_bigint(&b.nonce),
-            sha3_uncles: map_hex_string(&b.sha3_uncles),
-            logs_bloom: map_hex_string(&b.logs_bloom),
-            transactions_root: map_hex_string(&b.transactions_root),
-            state_root: map_hex_string(&b.state_root),
-            receipts_root: map_hex_string(&b.receipts_root),
-            miner: map_address_string(&b.miner, should_checksum),
-            difficulty: map_bigint(&b.difficulty),
-            total_difficulty: map_bigint(&b.total_difficulty),
-            extra_data: map_hex_string(&b.extra_data),
-            size: map_bigint(&b.size),
-            gas_limit: map_bigint(&b.gas_limit),
-            gas_used: map_bigint(&b.gas_used),
-            timestamp: map_i64(&b.timestamp).context("mapping block.timestamp")?,
-            uncles: b
-                .uncles
-                .as_ref()
-                .map(|arr| arr.iter().map(|u| u.encode_hex()).collect()),
-            base_fee_per_gas: map_bigint(&b.base_fee_per_gas),
-            blob_gas_used: map_bigint(&b.blob_gas_used),
-            excess_blob_gas: map_bigint(&b.excess_blob_gas),
-            parent_beacon_block_root: map_hex_string(&b.parent_beacon_block_root),
-            withdrawals_root: map_hex_string(&b.withdrawals_root),
+            number: map_u64(b.number, encoding).context("mapping block.number")?,
+            hash: map_binary(&b.hash, encoding),
+            parent_hash: map_binary(&b.parent_hash, encoding),
+            nonce: map_numeric(&b.nonce, NumericKind::BigInt, encoding)?,
+            sha3_uncles: map_binary(&b.sha3_uncles, encoding),
+            logs_bloom: map_binary(&b.logs_bloom, encoding),
+            transactions_root: map_binary(&b.transactions_root, encoding),
+            state_root: map_binary(&b.state_root, encoding),
+            receipts_root: map_binary(&b.receipts_root, encoding),
+            miner: map_address(&b.miner, encoding),
+            difficulty: map_numeric(&b.difficulty, NumericKind::BigInt, encoding)?,
+            total_difficulty: map_numeric(&b.total_difficulty, NumericKind::BigInt, encoding)?,
+            extra_data: map_binary(&b.extra_data, encoding),
+            size: map_numeric(&b.size, NumericKind::BigInt, encoding)?,
+            gas_limit: map_numeric(&b.gas_limit, NumericKind::BigInt, encoding)?,
+            gas_used: map_numeric(&b.gas_used, NumericKind::BigInt, encoding)?,
+            timestamp: map_numeric(&b.timestamp, NumericKind::Number, encoding)
+                .context("mapping block.timestamp")?,
+            uncles: b.uncles.as_ref().map(|arr| {
+                arr.iter()
+                    .map(|u| StringOrBuffer::new(u, encoding))
+                    .collect()
+            }),
+            base_fee_per_gas: map_numeric(&b.base_fee_per_gas, NumericKind::BigInt, encoding)?,
+            blob_gas_used: map_numeric(&b.blob_gas_used, NumericKind::BigInt, encoding)?,
+            excess_blob_gas: map_numeric(&b.excess_blob_gas, NumericKind::BigInt, encoding)?,
+            parent_beacon_block_root: map_binary(&b.parent_beacon_block_root, encoding),
+            withdrawals_root: map_binary(&b.withdrawals_root, encoding),
             withdrawals: b
                 .withdrawals
                 .as_ref()
-                .map(|w| w.iter().map(Withdrawal::from).collect()),
-            l1_block_number: b
-                .l1_block_number
-                .map(|n| u64::from(n).try_into())
+                .map(|w| w.iter().map(|w| Withdrawal::new(w, encoding)).collect())
                 .transpose()
+                .context("mapping block.withdrawals")?,
+            l1_block_number: map_u64(b.l1_block_number.map(u64::from), encoding)
                 .context("mapping l1_block_number")?,
-            send_count: map_hex_string(&b.transactions_root),
-            send_root: map_hex_string(&b.transactions_root),
-            mix_hash: map_hex_string(&b.transactions_root),
+            send_count: map_numeric(&b.send_count, NumericKind::Hex, encoding)?,
+            send_root: map_binary(&b.send_root, encoding),
+            mix_hash: map_binary(&b.mix_hash, encoding),
         })
     }
 }
 
 impl Transaction {
-    pub fn from_simple(t: &simple_types::Transaction, should_checksum: bool) -> Result<Self> {
+    pub fn from_simple(t: &simple_types::Transaction, encoding: Encoding) -> Result<Self> {
         Ok(Self {
-            block_hash: map_hex_string(&t.block_hash),
-            block_number: t
-                .block_number
-                .map(|n| u64::from(n).try_into())
-                .transpose()
+            block_hash: map_binary(&t.block_hash, encoding),
+            block_number: map_u64(t.block_number.map(u64::from), encoding)
                 .context("mapping transaction.block_number")?,
-            from: map_address_string(&t.from, should_checksum),
-            gas: map_bigint(&t.gas),
-            gas_price: map_bigint(&t.gas_price),
-            hash: map_hex_string(&t.hash),
-            input: map_hex_string(&t.input),
-            nonce: map_bigint(&t.nonce),
-            to: map_address_string(&t.to, should_checksum),
-            transaction_index: t
-                .transaction_index
-                .map(|n| u64::from(n).try_into())
-                .transpose()
+            from: map_address(&t.from, encoding),
+            gas: map_numeric(&t.gas, NumericKind::BigInt, encoding)?,
+            gas_price: map_numeric(&t.gas_price, NumericKind::BigInt, encoding)?,
+            hash: map_binary(&t.hash, encoding),
+            input: map_binary(&t.input, encoding),
+            nonce: map_numeric(&t.nonce, NumericKind::BigInt, encoding)?,
+            to: map_address(&t.to, encoding),
+            transaction_index: map_u64(t.transaction_index.map(u64::from), encoding)
                 .context("mapping transaction.transaction_index")?,
-            value: map_bigint(&t.value),
+            value: map_numeric(&t.value, NumericKind::BigInt, encoding)?,
             v: map_hex_string(&t.v),
             r: map_hex_string(&t.r),
             s: map_hex_string(&t.s),
             y_parity: map_hex_string(&t.y_parity),
-            max_priority_fee_per_gas: map_bigint(&t.max_priority_fee_per_gas),
-            max_fee_per_gas: map_bigint(&t.max_fee_per_gas),
-            chain_id: t
-                .chain_id
-                .as_ref()
-                .map(|n| ruint::aliases::U256::from_be_slice(n).try_into())
-                .transpose()
+            max_priority_fee_per_gas: map_numeric(
+                &t.max_priority_fee_per_gas,
+                NumericKind::BigInt,
+                encoding,
+            )?,
+            max_fee_per_gas: map_numeric(&t.max_fee_per_gas, NumericKind::BigInt, encoding)?,
+            chain_id: map_numeric(&t.chain_id, NumericKind::Number, encoding)
                 .context("mapping transaction.chain_id")?,
             access_list: t
                 .access_list
                 .as_ref()
-                .map(|arr| arr.iter().map(AccessList::from).collect()),
+                .map(|arr| arr.iter().map(|a| AccessList::new(a, encoding)).collect()),
             authorization_list: t
                 .authorization_list
                 .as_ref()
-                .map(|al| al.iter().map(Authorization::from).collect()),
-            max_fee_per_blob_gas: map_bigint(&t.max_fee_per_blob_gas),
-            blob_versioned_hashes: t
-                .blob_versioned_hashes
-                .as_ref()
-                .map(|arr| arr.iter().map(|h| h.encode_hex()).collect()),
-            cumulative_gas_used: map_bigint(&t.cumulative_gas_used),
-            effective_gas_price: map_bigint(&t.effective_gas_price),
-            gas_used: map_bigint(&t.gas_used),
-            contract_address: map_address_string(&t.contract_address, should_checksum),
-            logs_bloom: map_hex_string(&t.logs_bloom),
-            type_: t.type_.map(|v| u8::from(v).into()),
-            root: map_hex_string(&t.root),
-            status: t.status.map(|v| v.to_u8().into()),
-            l1_fee: map_bigint(&t.l1_fee),
-            l1_gas_price: map_bigint(&t.l1_gas_price),
-            l1_gas_used: map_bigint(&t.l1_gas_used),
-            l1_fee_scalar: t.l1_fee_scalar,
-            gas_used_for_l1: map_bigint(&t.gas_used_for_l1),
-            blob_gas_price: map_bigint(&t.blob_gas_price),
-            blob_gas_used: map_bigint(&t.blob_gas_used),
-            deposit_nonce: map_bigint(&t.deposit_nonce),
-            deposit_receipt_version: map_bigint(&t.deposit_receipt_version),
-            l1_base_fee_scalar: map_bigint(&t.l1_base_fee_scalar),
-            l1_blob_base_fee: map_bigint(&t.l1_blob_base_fee),
-            l1_blob_base_fee_scalar: map_bigint(&t.l1_blob_base_fee_scalar),
-            l1_block_number: map_i64(&t.l1_block_number)
+                .map(|al| al.iter().map(|a| Authorization::new(a, encoding)).collect())
+                .transpose()
+                .context("mapping transaction.authorization_list")?,
+            max_fee_per_blob_gas: map_numeric(
+                &t.max_fee_per_blob_gas,
+                NumericKind::BigInt,
+                encoding,
+            )?,
+            blob_versioned_hashes: t.blob_versioned_hashes.as_ref().map(|arr| {
+                arr.iter()
+                    .map(|h| StringOrBuffer::new(h, encoding))
+                    .collect()
+            }),
+            cumulative_gas_used: map_numeric(
+                &t.cumulative_gas_used,
+                NumericKind::BigInt,
+                encoding,
+            )?,
+            effective_gas_price: map_numeric(
+                &t.effective_gas_price,
+                NumericKind::BigInt,
+                encoding,
+            )?,
+            gas_used: map_numeric(&t.gas_used, NumericKind::BigInt, encoding)?,
+            contract_address: map_address(&t.contract_address, encoding),
+            logs_bloom: map_binary(&t.logs_bloom, encoding),
+            type_: map_u64(t.type_.map(|v| u8::from(v).into()), encoding)?,
+            root: map_binary(&t.root, encoding),
+            status: map_u64(t.status.map(|v| v.to_u8().into()), encoding)?,
+            l1_fee: map_numeric(&t.l1_fee, NumericKind::BigInt, encoding)?,
+            l1_gas_price: map_numeric(&t.l1_gas_price, NumericKind::BigInt, encoding)?,
+            l1_gas_used: map_numeric(&t.l1_gas_used, NumericKind::BigInt, encoding)?,
+            l1_fee_scalar: t.l1_fee_scalar.map(|v| fraction(v, encoding.numeric)),
+            gas_used_for_l1: map_numeric(&t.gas_used_for_l1, NumericKind::BigInt, encoding)?,
+            blob_gas_price: map_numeric(&t.blob_gas_price, NumericKind::BigInt, encoding)?,
+            blob_gas_used: map_numeric(&t.blob_gas_used, NumericKind::BigInt, encoding)?,
+            deposit_nonce: map_numeric(&t.deposit_nonce, NumericKind::BigInt, encoding)?,
+            deposit_receipt_version: map_numeric(
+                &t.deposit_receipt_version,
+                NumericKind::BigInt,
+                encoding,
+            )?,
+            l1_base_fee_scalar: map_numeric(&t.l1_base_fee_scalar, NumericKind::BigInt, encoding)?,
+            l1_blob_base_fee: map_numeric(&t.l1_blob_base_fee, NumericKind::BigInt, encoding)?,
+            l1_blob_base_fee_scalar: map_numeric(
+                &t.l1_blob_base_fee_scalar,
+                NumericKind::BigInt,
+                encoding,
+            )?,
+            l1_block_number: map_numeric(&t.l1_block_number, NumericKind::Number, encoding)
                 .context("mapping transaction.l1_block_number")?,
-            mint: map_bigint(&t.mint),
-            sighash: map_hex_string(&t.sighash),
-            source_hash: map_hex_string(&t.source_hash),
+            mint: map_numeric(&t.mint, NumericKind::BigInt, encoding)?,
+            sighash: map_binary(&t.sighash, encoding),
+            source_hash: map_binary(&t.source_hash, encoding),
         })
     }
 }
 
 impl Log {
-    pub fn from_simple(l: &simple_types::Log, should_checksum: bool) -> Result<Self> {
+    pub fn from_simple(l: &simple_types::Log, encoding: Encoding) -> Result<Self> {
         Ok(Self {
             removed: l.removed,
-            log_index: l
-                .log_index
-                .map(|n| u64::from(n).try_into())
-                .transpose()
+            log_index: map_u64(l.log_index.map(u64::from), encoding)
                 .context("mapping log.log_index")?,
-            transaction_index: l
-                .transaction_index
-                .map(|n| u64::from(n).try_into())
-                .transpose()
+            transaction_index: map_u64(l.transaction_index.map(u64::from), encoding)
                 .context("mapping log.transaction_index")?,
-            transaction_hash: map_hex_string(&l.transaction_hash),
-            block_hash: map_hex_string(&l.block_hash),
-            block_number: l
-                .block_number
-                .map(|n| u64::from(n).try_into())
-                .transpose()
+            transaction_hash: map_binary(&l.transaction_hash, encoding),
+            block_hash: map_binary(&l.block_hash, encoding),
+            block_number: map_u64(l.block_number.map(u64::from), encoding)
                 .context("mapping log.block_number")?,
-            address: map_address_string(&l.address, should_checksum),
-            data: map_hex_string(&l.data),
+            address: map_address(&l.address, encoding),
+            data: map_binary(&l.data, encoding),
             topics: l
                 .topics
                 .iter()
-                .map(|t| t.as_ref().map(|v| v.encode_hex()))
+                .map(|t| t.as_ref().map(|v| StringOrBuffer::new(v, encoding)))
                 .collect(),
         })
     }
 }
 
 impl Trace {
-    pub fn from_simple(t: &simple_types::Trace, should_checksum: bool) -> Result<Self> {
+    pub fn from_simple(t: &simple_types::Trace, encoding: Encoding) -> Result<Self> {
         Ok(Self {
-            from: map_address_string(&t.from, should_checksum),
-            to: map_address_string(&t.to, should_checksum),
+            from: map_address(&t.from, encoding),
+            to: map_address(&t.to, encoding),
             call_type: t.call_type.clone(),
-            gas: map_bigint(&t.gas),
-            input: map_hex_string(&t.input),
-            init: map_hex_string(&t.init),
-            value: map_bigint(&t.value),
-            author: map_address_string(&t.author, should_checksum),
+            gas: map_numeric(&t.gas, NumericKind::BigInt, encoding)?,
+            input: map_binary(&t.input, encoding),
+            init: map_binary(&t.init, encoding),
+            value: map_numeric(&t.value, NumericKind::BigInt, encoding)?,
+            author: map_address(&t.author, encoding),
             reward_type: t.reward_type.clone(),
-            block_hash: map_hex_string(&t.block_hash),
-            block_number: t
-                .block_number
-                .map(|n| n.try_into())
-                .transpose()
+            block_hash: map_binary(&t.block_hash, encoding),
+            block_number: map_u64(t.block_number, encoding)
                 .context("mapping trace.block_number")?,
-            address: map_address_string(&t.address, should_checksum),
-            code: map_hex_string(&t.code),
-            gas_used: map_bigint(&t.gas_used),
-            output: map_hex_string(&t.output),
-            subtraces: t
-                .subtraces
-                .map(|n| n.try_into())
-                .transpose()
-                .context("mapping trace.subtraces")?,
+            address: map_address(&t.address, encoding),
+            code: map_binary(&t.code, encoding),
+            gas_used: map_numeric(&t.gas_used, NumericKind::BigInt, encoding)?,
+            output: map_binary(&t.output, encoding),
+            subtraces: map_u64(t.subtraces, encoding).context("mapping trace.subtraces")?,
             trace_address: t
                 .trace_address
                 .as_ref()
@@ -528,18 +927,15 @@ impl Trace {
                 })
                 .transpose()
                 .context("mapping trace.trace_address")?,
-            transaction_hash: map_hex_string(&t.transaction_hash),
-            transaction_position: t
-                .transaction_position
-                .map(|n| n.try_into())
-                .transpose()
+            transaction_hash: map_binary(&t.transaction_hash, encoding),
+            transaction_position: map_u64(t.transaction_position, encoding)
                 .context("mapping trace.transaction_position")?,
             type_: t.type_.clone(),
             error: t.error.clone(),
-            action_address: map_address_string(&t.action_address, should_checksum),
-            balance: map_bigint(&t.balance),
-            refund_address: map_address_string(&t.refund_address, should_checksum),
-            sighash: map_hex_string(&t.sighash),
+            action_address: map_address(&t.action_address, encoding),
+            balance: map_numeric(&t.balance, NumericKind::BigInt, encoding)?,
+            refund_address: map_address(&t.refund_address, encoding),
+            sighash: map_binary(&t.sighash, encoding),
         })
     }
 }
@@ -766,6 +1162,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_binary_fields() {
+        let address = [0xab; 20];
+        let log = simple_types::Log {
+            address: Some(FixedSizeData::from(address)),
+            data: Some(format::Data::from(vec![1, 2])),
+            topics: [Some(FixedSizeData::from([0xcd; 32])), None]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+
+        let binary = Encoding {
+            checksum_addresses: true,
+            binary: true,
+            ..Default::default()
+        };
+        let converted = Log::from_simple(&log, binary).unwrap();
+        // checksumming only applies to hex strings
+        assert_eq!(
+            converted.address,
+            Some(StringOrBuffer::Buffer(address.to_vec()))
+        );
+        assert_eq!(converted.data, Some(StringOrBuffer::Buffer(vec![1, 2])));
+        assert_eq!(
+            converted.topics,
+            vec![Some(StringOrBuffer::Buffer(vec![0xcd; 32])), None]
+        );
+
+        let converted = Log::from_simple(&log, Encoding::default()).unwrap();
+        assert_eq!(converted.data, Some("0x0102".to_owned().into()));
+        assert_eq!(
+            converted.address.unwrap().to_data().unwrap().as_ref(),
+            address
+        );
+
+        let config = StreamConfig {
+            binary_fields: Some(false),
+            ..Default::default()
+        };
+        assert!(!binary.for_stream(&config).binary);
+        assert!(binary.for_stream(&StreamConfig::default()).binary);
+    }
+
+    #[test]
+    fn test_block_own_columns() {
+        let block = simple_types::Block {
+            transactions_root: Some(FixedSizeData::from([1; 32])),
+            send_count: Some(format::Quantity::from(2u64)),
+            send_root: Some(FixedSizeData::from([3; 32])),
+            mix_hash: Some(FixedSizeData::from([4; 32])),
+            ..Default::default()
+        };
+
+        let converted = Block::from_simple(&block, Encoding::default()).unwrap();
+        assert_eq!(converted.send_count, Some(Numeric::String("0x2".into())));
+        assert_eq!(
+            converted.send_root,
+            Some(StringOrBuffer::String(format!("0x{}", "03".repeat(32))))
+        );
+        assert_eq!(
+            converted.mix_hash,
+            Some(StringOrBuffer::String(format!("0x{}", "04".repeat(32))))
+        );
+    }
+
+    #[test]
+    fn test_numeric_mode() {
+        let block = simple_types::Block {
+            number: Some(436),
+            gas_limit: Some(format::Quantity::from(1u64 << 60)),
+            send_count: Some(format::Quantity::from(436u64)),
+            ..Default::default()
+        };
+        let with_mode = |numeric| Encoding {
+            numeric,
+            ..Default::default()
+        };
+
+        let converted = Block::from_simple(&block, Encoding::default()).unwrap();
+        assert_eq!(converted.number, Some(Numeric::Number(436)));
+        assert_eq!(
+            converted.gas_limit,
+            Some(Numeric::BigInt(BigInt::from(1u64 << 60)))
+        );
+        assert_eq!(converted.send_count, Some(Numeric::String("0x1b4".into())));
+
+        let converted = Block::from_simple(&block, with_mode(Some(NumericMode::BigInt))).unwrap();
+        assert_eq!(
+            converted.number,
+            Some(Numeric::BigInt(BigInt::from(436u64)))
+        );
+        assert_eq!(
+            converted.send_count,
+            Some(Numeric::BigInt(BigInt::from(436u64)))
+        );
+
+        let converted =
+            Block::from_simple(&block, with_mode(Some(NumericMode::DecimalString))).unwrap();
+        assert_eq!(converted.number, Some(Numeric::String("436".into())));
+        assert_eq!(
+            converted.gas_limit,
+            Some(Numeric::String("1152921504606846976".into()))
+        );
+
+        let converted = Block::from_simple(&block, with_mode(Some(NumericMode::Hex))).unwrap();
+        assert_eq!(converted.number, Some(Numeric::String("0x1b4".into())));
+        assert_eq!(
+            converted.gas_limit,
+            Some(Numeric::String("0x1000000000000000".into()))
+        );
+
+        let converted =
+            Block::from_simple(&block, with_mode(Some(NumericMode::NumberWhenSafe))).unwrap();
+        assert_eq!(converted.number, Some(Numeric::Number(436)));
+        assert_eq!(converted.send_count, Some(Numeric::Number(436)));
+        assert_eq!(
+            converted.gas_limit,
+            Some(Numeric::String("1152921504606846976".into()))
+        );
+        let max_safe = Numeric::new(
+            U256::from(MAX_SAFE_INTEGER),
+            NumericKind::BigInt,
+            Some(NumericMode::NumberWhenSafe),
+        )
+        .unwrap();
+        assert_eq!(max_safe, Numeric::Number(9007199254740991));
+
+        let tx = simple_types::Transaction {
+            l1_fee_scalar: Some(0.684),
+            ..Default::default()
+        };
+        let l1_fee_scalar = |mode| {
+            Transaction::from_simple(&tx, with_mode(mode))
+                .unwrap()
+                .l1_fee_scalar
+        };
+        assert!(matches!(l1_fee_scalar(None), Some(Either::A(v)) if v == 0.684));
+        assert!(matches!(
+            l1_fee_scalar(Some(NumericMode::BigInt)),
+            Some(Either::A(v)) if v == 0.684
+        ));
+        for mode in [NumericMode::DecimalString, NumericMode::Hex] {
+            assert!(matches!(
+                l1_fee_scalar(Some(mode)),
+                Some(Either::B(v)) if v == "0.684"
+            ));
+        }
+    }
+
     #[test]
     fn test_bigint_convert_signed() {
         for i in (i128::from(i64::MIN)..i128::from(u64::MAX))
@@ -0,0 +1,343 @@
+use hypersync_client::net_types;
+use napi::bindgen_prelude::{ClassInstance, Either, Object};
+
+use crate::{
+    decode::Decoder,
+    query::{FieldSelection, LogField, LogFilter, LogSelection, Query},
+};
+
+/// Kind of a problem found by `validateQuery`
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum QueryIssueKind {
+    /// A value can't be parsed, e.g. an address or topic that isn't valid hex
+    InvalidValue,
+    /// The block range or a limit is out of range
+    InvalidRange,
+    /// A log filter has more than 4 topics
+    TooManyTopics,
+    /// Fields needed for the selected data or for decoding are not selected
+    MissingFields,
+    /// The query doesn't select any data
+    EmptyQuery,
+}
+
+#[napi(object)]
+pub struct QueryIssue {
+    pub kind: QueryIssueKind,
+    /// Path of the offending part of the query, e.g. `logs[0].topics`
+    pub path: String,
+    pub message: String,
+}
+
+/// Result of `validateQuery`. The server rejects queries with errors, while warnings point
+/// at queries that likely don't return what was intended.
+#[napi(object)]
+#[derive(Default)]
+pub struct QueryValidation {
+    pub errors: Vec<QueryIssue>,
+    pub warnings: Vec<QueryIssue>,
+}
+
+impl QueryValidation {
+    fn error(&mut self, kind: QueryIssueKind, path: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(QueryIssue {
+            kind,
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    fn warning(
+        &mut self,
+        kind: QueryIssueKind,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.warnings.push(QueryIssue {
+            kind,
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// One line summary of the errors
+    pub fn error_summary(&self) -> String {
+        self.errors
+            .iter()
+            .map(|issue| format!("{}: {}", issue.path, issue.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Check a query for mistakes without sending it.
+///
+/// Pass the decoder the logs will be decoded with to also check that the fields it needs
+/// are selected.
+#[napi(ts_args_type = "query: Query, options?: { decoder?: Decoder }")]
+pub fn validate_query(query: Query, options: Option<Object>) -> napi::Result<QueryValidation> {
+    let decoder = match options {
+        Some(options) => options.get::<ClassInstance<Decoder>>("decoder")?,
+        None => None,
+    };
+    let decoder_fields = decoder.map(|decoder| decoder.required_log_fields());
+
+    Ok(validate(&query, decoder_fields.as_deref()))
+}
+
+/// Validate the query, `decoder_fields` are the log fields needed to decode the logs
+pub fn validate(query: &Query, decoder_fields: Option<&[LogField]>) -> QueryValidation {
+    let mut res = QueryValidation::default();
+
+    if query.from_block < 0 {
+        res.error(
+            QueryIssueKind::InvalidRange,
+            "fromBlock",
+            "must not be negative",
+        );
+    }
+    if let Some(to_block) = query.to_block {
+        if to_block <= query.from_block {
+            res.error(
+                QueryIssueKind::InvalidRange,
+                "toBlock",
+                format!(
+                    "must be greater than fromBlock ({}), toBlock is exclusive",
+                    query.from_block
+                ),
+            );
+        }
+    }
+    for (path, limit) in [
+        ("maxNumBlocks", query.max_num_blocks),
+        ("maxNumTransactions", query.max_num_transactions),
+        ("maxNumLogs", query.max_num_logs),
+        ("maxNumTraces", query.max_num_traces),
+    ] {
+        if limit.is_some_and(|limit| limit < 0) {
+            res.error(QueryIssueKind::InvalidRange, path, "must not be negative");
+        }
+    }
+
+    for (i, selection) in query.logs.iter().flatten().enumerate() {
+        for (path, filter) in log_filters(i, selection) {
+            validate_log_filter(&mut res, path, filter);
+        }
+    }
+    for (i, selection) in query.transactions.iter().flatten().enumerate() {
+        let converted = match selection {
+            Either::A(s) => net_types::TransactionSelection::try_from(s.clone()).map(drop),
+            Either::B(f) => net_types::TransactionFilter::try_from(f.clone()).map(drop),
+        };
+        if let Err(e) = converted {
+            res.error(
+                QueryIssueKind::InvalidValue,
+                format!("transactions[{}]", i),
+                format!("{:#}", e),
+            );
+        }
+    }
+    for (i, selection) in query.traces.iter().flatten().enumerate() {
+        let converted = match selection {
+            Either::A(s) => net_types::TraceSelection::try_from(s.clone()).map(drop),
+            Either::B(f) => net_types::TraceFilter::try_from(f.clone()).map(drop),
+        };
+        if let Err(e) = converted {
+            res.error(
+                QueryIssueKind::InvalidValue,
+                format!("traces[{}]", i),
+                format!("{:#}", e),
+            );
+        }
+    }
+    for (i, selection) in query.blocks.iter().flatten().enumerate() {
+        let converted = match selection {
+            Either::A(s) => net_types::BlockSelection::try_from(s.clone()).map(drop),
+            Either::B(f) => net_types::BlockFilter::try_from(f.clone()).map(drop),
+        };
+        if let Err(e) = converted {
+            res.error(
+                QueryIssueKind::InvalidValue,
+                format!("blocks[{}]", i),
+                format!("{:#}", e),
+            );
+        }
+    }
+    if let Err(e) = net_types::FieldSelection::try_from(query.field_selection.clone()) {
+        res.error(
+            QueryIssueKind::InvalidValue,
+            "fieldSelection",
+            format!("{:#}", e),
+        );
+    }
+
+    validate_fields(&mut res, query, decoder_fields);
+
+    res
+}
+
+fn log_filters(i: usize, selection: &Either<LogSelection, LogFilter>) -> Vec<(String, &LogFilter)> {
+    match selection {
+        Either::A(selection) => {
+            let mut filters = vec![(format!("logs[{}].include", i), &selection.include)];
+            if let Some(exclude) = &selection.exclude {
+                filters.push((format!("logs[{}].exclude", i), exclude));
+            }
+            filters
+        }
+        Either::B(filter) => vec![(format!("logs[{}]", i), filter)],
+    }
+}
+
+fn validate_log_filter(res: &mut QueryValidation, path: String, filter: &LogFilter) {
+    let num_topics = filter.topics.as_ref().map_or(0, Vec::len);
+    if num_topics > 4 {
+        res.error(
+            QueryIssueKind::TooManyTopics,
+            format!("{}.topics", path),
+            format!("logs have at most 4 topics, got {}", num_topics),
+        );
+        return;
+    }
+    if let Err(e) = net_types::LogFilter::try_from(filter.clone()) {
+        res.error(QueryIssueKind::InvalidValue, path, format!("{:#}", e));
+    }
+}
+
+fn validate_fields(res: &mut QueryValidation, query: &Query, decoder_fields: Option<&[LogField]>) {
+    let FieldSelection {
+        block,
+        transaction,
+        log,
+        trace,
+    } = &query.field_selection;
+
+    let selects_blocks = !is_empty(&query.blocks) || query.include_all_blocks == Some(true);
+    for (selected, fields_empty, table) in [
+        (selects_blocks, is_empty(block), "block"),
+        (
+            !is_empty(&query.transactions),
+            is_empty(transaction),
+            "transaction",
+        ),
+        (!is_empty(&query.logs), is_empty(log), "log"),
+        (!is_empty(&query.traces), is_empty(trace), "trace"),
+    ] {
+        if selected && fields_empty {
+            res.warning(
+                QueryIssueKind::MissingFields,
+                format!("fieldSelection.{}", table),
+                format!(
+                    "{}s are selected but no {} fields, the response won't contain them",
+                    table, table
+                ),
+            );
+        }
+    }
+
+    if !selects_blocks
+        && is_empty(&query.transactions)
+        && is_empty(&query.logs)
+        && is_empty(&query.traces)
+    {
+        res.warning(
+            QueryIssueKind::EmptyQuery,
+            "",
+            "no blocks, transactions, logs or traces are selected",
+        );
+    }
+
+    if let Some(required) = decoder_fields {
+        let selected = log.as_deref().unwrap_or_default();
+        let missing = required
+            .iter()
+            .filter(|field| !selected.contains(field))
+            .map(|field| format!("{:?}", field))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            res.error(
+                QueryIssueKind::MissingFields,
+                "fieldSelection.log",
+                format!("the decoder needs {} to decode logs", missing.join(", ")),
+            );
+        }
+    }
+}
+
+fn is_empty<T>(items: &Option<Vec<T>>) -> bool {
+    items.as_ref().is_none_or(Vec::is_empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(issues: &[QueryIssue]) -> Vec<(&QueryIssueKind, &str)> {
+        issues
+            .iter()
+            .map(|issue| (&issue.kind, issue.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_errors() {
+        let query = Query {
+            from_block: 10,
+            to_block: Some(10),
+            max_num_logs: Some(-1),
+            logs: Some(vec![
+                Either::B(LogFilter {
+                    address: None,
+                    topics: Some(vec![vec![]; 5]),
+                }),
+                Either::A(LogSelection {
+                    include: LogFilter {
+                        address: Some(vec!["0x12".into()]),
+                        topics: None,
+                    },
+                    exclude: None,
+                }),
+            ]),
+            field_selection: FieldSelection {
+                log: Some(vec![LogField::Data]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let res = validate(&query, Some(&[LogField::Data, LogField::Topic0]));
+        assert_eq!(
+            kinds(&res.errors),
+            vec![
+                (&QueryIssueKind::InvalidRange, "toBlock"),
+                (&QueryIssueKind::InvalidRange, "maxNumLogs"),
+                (&QueryIssueKind::TooManyTopics, "logs[0].topics"),
+                (&QueryIssueKind::InvalidValue, "logs[1].include"),
+                (&QueryIssueKind::MissingFields, "fieldSelection.log"),
+            ]
+        );
+        assert!(res.errors[4].message.contains("Topic0"));
+        assert!(res.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_validate_warnings() {
+        let res = validate(&Query::default(), None);
+        assert!(res.errors.is_empty());
+        assert_eq!(
+            kinds(&res.warnings),
+            vec![(&QueryIssueKind::EmptyQuery, "")]
+        );
+
+        let query = Query {
+            transactions: Some(vec![Either::B(Default::default())]),
+            ..Default::default()
+        };
+        let res = validate(&query, None);
+        assert_eq!(
+            kinds(&res.warnings),
+            vec![(&QueryIssueKind::MissingFields, "fieldSelection.transaction")]
+        );
+    }
+}